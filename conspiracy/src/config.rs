@@ -122,7 +122,7 @@
 //! > not the present mechanism still prevents this dependency form leaking into the code that is
 //! > consuming configuration.
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{any::Any, fmt::Display, sync::Arc};
 
 /// Define a configuration as a set of nested structs. This reduces boilerplate and makes it easier
 /// to maintain the struct definition of a config that you track against a file. Additionally, the
@@ -163,13 +163,100 @@ use std::{marker::PhantomData, sync::Arc};
 /// # Requirements
 ///
 /// Every type in a config struct hierarchy must be unique. This is so that conversions from a
-/// config to a sub-config aren't ambiguous.
+/// config to a sub-config aren't ambiguous. Within one `config_struct!`, a type reachable by two
+/// paths, e.g. the same external sub-config used by two fields, is rejected at compile time with an
+/// error naming both paths. The check can't see into other invocations, such as the hierarchy behind
+/// an external sub-config, but those types aren't projected to from this config either.
 ///
 /// Additionally, any type you use, that isn't itself being generated by the macro, must implement:
 ///
 /// - [`Clone`]
 /// - [`PartialEq`]
 ///
+/// # External Sub-Configs
+///
+/// A sub-config that was defined elsewhere, e.g. by [`config_struct!`] in another crate, can be
+/// embedded by prefixing its type with `@`:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{as_shared_fetcher, config_struct, shared_fetcher_from_static, SharedConfigFetcher};
+///
+/// mod database {
+///     conspiracy::config::config_struct!(
+///         pub struct DatabaseConfig {
+///             pub name: String,
+///         }
+///     );
+/// }
+///
+/// config_struct!(
+///     pub struct AppConfig {
+///         database: @database::DatabaseConfig,
+///     }
+/// );
+///
+/// let fetcher = shared_fetcher_from_static(Arc::new(AppConfig {
+///     database: Arc::new(database::DatabaseConfig { name: "prod".to_string() }),
+/// }));
+/// let database: SharedConfigFetcher<database::DatabaseConfig> = as_shared_fetcher(&fetcher);
+/// ```
+///
+/// Like inline sub-configs, the field is stored behind an [`Arc`] and [`AsField`] is generated for
+/// every ancestor. The external type must implement [`RestartRequired`] (which is incorporated into
+/// the parent's) in addition to the usual requirements. Because the macro can't see inside it:
+///
+/// - [`AsField`] isn't generated for the external type's own sub-configs. Project to the external
+///   type first, then from there.
+/// - The compact form holds the `Arc` as-is rather than the external type's compact form.
+///
+/// An external sub-config that may be absent is declared as `field: Option<@Type>` and stored as
+/// `Option<Arc<Type>>`, defaulting to [`None`] when the key is missing. Since there may be nothing
+/// to project to, [`AsVariant`] is generated for every ancestor instead of [`AsField`], so a
+/// fetcher for it is created with [`as_optional_shared_fetcher`]. The sub-config appearing or
+/// disappearing always signals a restart, otherwise its own restart fields are compared.
+///
+/// # Enum Sub-Configs
+///
+/// A sub-config can also be an enum where each variant wraps its own config struct, e.g. to select
+/// one of several backends with a serde tagged enum:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{config_struct, full_serde, AsVariant};
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct StorageConfig {
+///         backend:
+///             #[full_serde]
+///             #[serde(tag = "type", rename_all = "snake_case")]
+///             pub enum Backend {
+///                 Postgres(#[full_serde] pub struct PostgresConfig { url: String }),
+///                 Sqlite(#[full_serde] pub struct SqliteConfig { path: String }),
+///             }
+///     }
+/// );
+///
+/// let config: Arc<StorageConfig> =
+///     serde_json::from_str(r#"{ "backend": { "type": "sqlite", "path": "/tmp/db" } }"#).unwrap();
+///
+/// let sqlite: Option<Arc<SqliteConfig>> = config.share_variant();
+/// assert_eq!("/tmp/db", sqlite.unwrap().path);
+/// let postgres: Option<Arc<PostgresConfig>> = config.share_variant();
+/// assert!(postgres.is_none());
+/// ```
+///
+/// The enum is stored behind an [`Arc`] and [`AsField`] is generated for it like any other
+/// sub-config. Since only one variant is active, the variant configs are reached with
+/// [`AsVariant`] instead, which yields [`None`] for inactive variants. The variant configs are
+/// otherwise regular generated structs, with [`AsField`] for their own sub-configs. A fetcher for a
+/// variant config is created with [`as_optional_shared_fetcher`].
+///
+/// Restart fields within a variant are compared while the variant stays active. Switching to a
+/// different variant signals a restart if any variant has restart fields, and is reported by
+/// `restart_reasons` of the enum as the name of the newly active variant.
+///
 /// # Attributes
 ///
 /// The macro is compatible with any named struct definition syntax with named fields, including
@@ -183,6 +270,195 @@ use std::{marker::PhantomData, sync::Arc};
 /// | Attribute | Behavior |
 /// |--|--|
 /// | `#[conspiracy(restart)]` | Includes in the generated [`RestartRequired`]. When comparing two config snapshots, if this field changed the struct signals a need to restart. If your [`ConfigFetcher`] supports this, it will automatically gracefully restart your application. |
+/// | `#[conspiracy(change = "reload")]` | Like `restart`, but for changes that only need a subsystem reload. Tracked separately by [`RestartRequired::reload_required`] and [`RestartRequired::reload_reasons`], so it doesn't signal a restart. `change = "restart"` is the same as `restart`; a field can only be in one category. |
+/// | `#[conspiracy(restart, compare = "path::to::fn")]` | For a leaf whose [`PartialEq`] isn't the right notion of "changed", decides with a `fn(&T, &T) -> bool` instead, returning `true` when the change is significant. E.g. a `Duration` compared only to the second. Also applies to `change = "reload"` fields. |
+/// | `#[conspiracy(no_default)]` | Opts an `Option<_>` field out of the automatic `#[serde(default)]`. |
+/// | `#[conspiracy(env = "VAR")]` | Allows the field to be overridden by the environment variable `VAR`, see [Environment Overrides](#environment-overrides). |
+/// | `#[conspiracy(shared)]` | Stores a leaf field of type `T` as `Arc<T>`, for types that are expensive or impossible to clone. The type no longer needs to implement [`Clone`] or [`PartialEq`]; equality and restart comparisons of the field become pointer identity. Deserializing requires serde's `rc` feature. |
+/// | `#[conspiracy(restart, key = "id")]` | For a collection of entries that implement [`RestartRequired`] (e.g. a `Vec` of configs), compares the collections as maps keyed by the entries' `id` field instead of by position. Reordering the entries doesn't require a restart; adding or removing an entry, or a change to an entry's restart fields, does. |
+/// | `#[conspiracy(deprecated = "use foo instead")]` | Keeps accepting the field, but reports it with the message when it's present in a loaded document, see [`deprecated`] (requires the `json` feature). |
+/// | `#[conspiracy(secret)]` | Redacts the field (or whole sub-config) from `to_dotted_string`, see [Dotted Dumps](#dotted-dumps). |
+///
+/// Field attributes may be combined, e.g. `#[conspiracy(restart, no_default)]`.
+///
+/// For structs that derive `Deserialize` (directly or via [`full_serde`] / [`full_serde_as`]),
+/// `Option<_>` fields are given `#[serde(default)]` so an absent key deserializes to [`None`]. serde
+/// already does this for plain `Option` fields, but not once a field has a custom deserializer such
+/// as `#[serde(deserialize_with = "...")]`. Fields that already specify their own
+/// `#[serde(default)]` are left as-is. Opting out with `#[conspiracy(no_default)]` makes the key
+/// required for such fields.
+///
+/// Within a struct that derives `Deserialize`, two fields that deserialize from the same key (after
+/// applying `#[serde(rename)]`, `#[serde(rename_all)]`, and `#[serde(alias)]`) are rejected at
+/// compile time, rather than silently leaving one of them unpopulated.
+///
+/// A struct with any `#[serde_as(...)]` field is given serde_with's `#[serde_as]` attribute if it
+/// doesn't already have it, so [`full_serde`] works as well as [`full_serde_as`] for such structs.
+///
+/// Serde attributes are kept on the generated struct and its compact form, e.g.
+/// `#[serde(skip_serializing_if = "Option::is_none")]` to write back minimal configs. A renamed
+/// field can keep accepting its old name from existing config files with `#[serde(alias = "...")]`:
+///
+/// ```rust
+/// # use conspiracy::config::{config_struct, full_serde};
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         #[serde(alias = "max_conns")]
+///         pub max_connections: u32,
+///     }
+/// );
+///
+/// let config: AppConfig = serde_json::from_str(r#"{ "max_conns": 16 }"#).unwrap();
+/// assert_eq!(16, config.max_connections);
+/// ```
+///
+/// Restart comparisons use the field's [`PartialEq`] implementation, so "changed" means exactly what
+/// `!=` means for that type. For collections such as [`Vec`] this is order-sensitive: reordering the
+/// elements of a restart-marked `Vec` signals a restart even if the contents are otherwise identical.
+/// [`RestartRequired::restart_reasons`] reports which of the marked fields changed by their dotted
+/// path, e.g. `"web_server.addr"`.
+///
+/// And struct attributes:
+///
+/// | Attribute | Behavior |
+/// |--|--|
+/// | `#[conspiracy(strict)]` | Applies `#[serde(deny_unknown_fields)]` to the struct and every nested struct beneath it that derives `Deserialize`, so a typo anywhere in a config file is rejected. Can't be combined with `#[serde(flatten)]` fields, which serde doesn't support alongside `deny_unknown_fields`. |
+/// | `#[conspiracy(schema)]` | Derives [`schemars::JsonSchema`](https://docs.rs/schemars/latest/schemars/trait.JsonSchema.html) for the struct and every nested struct beneath it, and generates `fn schema() -> schemars::Schema`. Since the schema is derived from the same serde attributes, it matches the wire format. Requires a dependency on `schemars`. |
+/// | `#[conspiracy(arbitrary)]` | Derives [`arbitrary::Arbitrary`](https://docs.rs/arbitrary/latest/arbitrary/trait.Arbitrary.html) for the struct, its compact form, and every nested struct and enum beneath it, so a fuzzer can synthesize configs from raw bytes. Leaf types must implement `Arbitrary` as well. Requires a dependency on `arbitrary` with its `derive` feature. |
+/// | `#[conspiracy(hashable)]` | Derives [`Eq`] and [`Hash`][std::hash::Hash] for the struct and every nested struct and enum beneath it, e.g. to key a cache by config. Leaf types must implement both, a float leaf is rejected at compile time naming the field. Can't be combined with `#[conspiracy(no_restart)]`. |
+/// | `#[conspiracy(builder)]` | Generates a builder for the struct and every nested struct beneath it, see [Builders](#builders). |
+/// | `#[conspiracy(transparent)]` | For a struct with exactly one field, implements [`Deref`][std::ops::Deref] and [`AsRef`] to that field, so `config.timeout.value` can be written as `*config.timeout`. Only affects access in code; the serialized form still has the field. |
+/// | `#[conspiracy(no_restart)]` | Skips generating [`RestartRequired`] and the `PartialEq` derive it relies on, reducing compile time and binary size for large configs that are never compared. Only valid on the root struct, where it applies to the whole config, and can't be combined with `#[conspiracy(restart)]` fields. |
+///
+/// # Builders
+///
+/// With `#[conspiracy(builder)]`, each struct gets `builder()`, e.g. returning an `AppConfigBuilder`
+/// with a setter per field. Setters accept `impl Into<FieldType>`, so a `&str` can be passed for a
+/// `String` and a sub-config for its `Arc`. Primitive fields take their type directly, since an
+/// integer literal can't infer its type through `Into`. `Option` fields are set by their inner type
+/// and default to `None`; `build()` fails with a [`MissingConfigFieldsError`] naming every other
+/// field that wasn't set.
+///
+/// ```rust
+/// use conspiracy::config::config_struct;
+///
+/// config_struct!(
+///     #[conspiracy(builder)]
+///     pub struct AppConfig {
+///         pub port: u16,
+///         pub name: String,
+///         pub description: Option<String>,
+///         pub database: pub struct DatabaseConfig {
+///             pub url: String,
+///         }
+///     }
+/// );
+///
+/// let config = AppConfig::builder()
+///     .port(8080)
+///     .name("app")
+///     .database(DatabaseConfig::builder().url("db:5432").build().unwrap())
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!("app", config.name);
+/// assert_eq!(None, config.description);
+/// assert!(AppConfig::builder().port(8080).build().is_err());
+/// ```
+///
+/// # Environment Overrides
+///
+/// For a hybrid file and environment config, leaf fields marked `#[conspiracy(env = "VAR")]` can
+/// be overridden after loading. Every generated struct has `apply_env_overrides`, which replaces the
+/// value of each marked field (including those of nested sub-configs) with its environment
+/// variable, parsed with [`FromStr`][std::str::FromStr]. The environment wins over the file; fields
+/// whose variable isn't set keep their loaded value.
+///
+/// ```rust
+/// # use conspiracy::config::{config_struct, full_serde};
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         #[conspiracy(env = "MY_APP_DATABASE_URL")]
+///         pub database_url: String,
+///     }
+/// );
+///
+/// # std::env::remove_var("MY_APP_DATABASE_URL");
+/// let mut config: AppConfig = serde_json::from_str(r#"{ "database_url": "file" }"#).unwrap();
+/// config.apply_env_overrides().unwrap();
+/// assert_eq!("file", config.database_url);
+/// ```
+///
+/// Overrides aren't applied to external (`@Type` or `Option<@Type>`) sub-configs, call their
+/// `apply_env_overrides` directly if they have one.
+///
+/// # Dotted Dumps
+///
+/// For logging the effective config, e.g. at boot, every generated struct has `to_dotted_string`,
+/// which renders one `path = value` line per leaf field. Fields marked `#[conspiracy(secret)]` are
+/// redacted:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use conspiracy::config::config_struct;
+/// config_struct!(
+///     pub struct AppConfig {
+///         pub port: u16,
+///         pub database: pub struct DatabaseConfig {
+///             pub name: String,
+///             #[conspiracy(secret)]
+///             pub password: String,
+///         }
+///     }
+/// );
+///
+/// let config = AppConfig {
+///     port: 8080,
+///     database: Arc::new(DatabaseConfig {
+///         name: "prod".to_string(),
+///         password: "hunter2".to_string(),
+///     }),
+/// };
+///
+/// assert_eq!(
+///     "port = 8080\ndatabase.name = prod\ndatabase.password = <redacted>",
+///     config.to_dotted_string(),
+/// );
+/// ```
+///
+/// Paths use the field names in code (not their serialized names) and the lines follow declaration
+/// order. The active variant of an enum sub-config is part of the path, e.g. `backend.Postgres.url`.
+/// Leaves are written with their [`Display`] implementation, falling back to
+/// [`Debug`] (e.g. for `Option` and `Vec`), or `<unprintable>` if they implement neither.
+///
+/// # Catch-All Fields
+///
+/// For forward compatibility, unknown keys can be captured rather than dropped with a flattened map
+/// leaf. They're kept through a round-trip, so a newer config file survives being rewritten by an
+/// older build:
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// # use conspiracy::config::{config_struct, full_serde};
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub port: u16,
+///         #[serde(flatten)]
+///         pub extra: HashMap<String, serde_json::Value>,
+///     }
+/// );
+///
+/// let config: AppConfig = serde_json::from_str(r#"{ "port": 80, "added_later": true }"#).unwrap();
+/// assert_eq!(serde_json::Value::Bool(true), config.extra["added_later"]);
+/// ```
+///
+/// A catch-all contradicts `#[serde(deny_unknown_fields)]`, which serde doesn't support alongside
+/// `flatten`, so it can't be used within a `#[conspiracy(strict)]` config. Marked
+/// `#[conspiracy(restart)]`, the map is compared by its entries, so the order of the keys never
+/// requires a restart.
 ///
 /// # Injection (Usage)
 ///
@@ -235,6 +511,9 @@ use std::{marker::PhantomData, sync::Arc};
 /// - `&SharedConfigFetcher<Config>`
 /// - `SharedConfigFetcher<Config>`
 ///
+/// Where injection genuinely can't reach, e.g. a context-free callback required by another library,
+/// [`ambient`] offers a clearly labeled escape hatch.
+///
 /// # Working with sub-configs
 ///
 /// A config struct snapshot can be converted into a snapshot of any held sub-config. This is also
@@ -259,6 +538,18 @@ use std::{marker::PhantomData, sync::Arc};
 /// let sub_config: Arc<SubConfig> = config.share();
 /// ```
 ///
+/// [`AsField`] is also implemented for `Arc<Config>` and `&Config`, so generic code accepting
+/// `impl AsField<SubConfig>` can be handed a snapshot or a reference alike.
+///
+/// The same projection is available through [`From`] / [`Into`] for generic code, e.g.
+/// `let sub_config: Arc<SubConfig> = (&*config).into();`. The conversion is from a reference to the
+/// config rather than from its [`Arc`], since `impl From<Arc<Config>> for Arc<SubConfig>` isn't
+/// permitted by Rust's orphan rules.
+///
+/// Outside of generic code, each sub-config field `foo` also has a named accessor `foo()` returning
+/// its [`Arc`], e.g. `let sub_config = config.sub_config();`. Unlike `share()` it needs neither the
+/// target type in scope nor a type annotation. The accessor has the same visibility as the field.
+///
 /// ## Convert to sub-config fetcher
 ///
 /// ```rust
@@ -277,6 +568,43 @@ use std::{marker::PhantomData, sync::Arc};
 /// let sub_config: SharedConfigFetcher<SubConfig> = as_shared_fetcher(&config_fetcher);
 /// ```
 ///
+/// A config can also be projected to its own type, so generic code over [`AsField`] works without
+/// special casing the root. Unlike sub-config projection this isn't a reference count bump, it
+/// clones the config's own fields (nested sub-configs are still shared). Where the identity
+/// projection is on a hot path, prefer using the original fetcher directly.
+///
+/// Projection isn't limited to direct children. [`AsField`] is generated for every ancestor /
+/// descendant pair within a single [`config_struct`] invocation, so a root fetcher can be projected
+/// straight to a deeply nested sub-config in one call without chaining through each intermediate
+/// level. The only requirement is that the sub-config types are unique within the hierarchy, since
+/// the target is selected by type. Sub-configs declared as external (`@Type`) are the exception,
+/// their own descendants must be reached by projecting to the external type first.
+///
+/// ## Replace a sub-config
+///
+/// The reverse direction is a copy-on-write update. For every sub-config field `foo`, a
+/// `replace_foo` method returns a new snapshot with that branch swapped, sharing the [`Arc`] of
+/// every other branch with the original. This is useful for editing a single sub-config in process
+/// (e.g. before storing it in a [`SwapFetcher`][fetcher::SwapFetcher]) without rebuilding the whole config.
+/// The method has the same visibility as the field, so a private sub-config can't be replaced from
+/// outside its module:
+///
+/// ```rust
+/// # use conspiracy_macros::config_struct;
+/// # use std::sync::Arc;
+/// # config_struct!(
+/// #    pub struct Config {
+/// #        sub_config: pub struct SubConfig {
+/// #            foo: u32,
+/// #        }
+/// #    }
+/// # );
+/// # let config = Arc::new(Config { sub_config: Arc::new(SubConfig { foo: 0 }) });
+/// // Assume config: Arc<Config> exists
+/// let updated: Arc<Config> = config.replace_sub_config(Arc::new(SubConfig { foo: 1 }));
+/// assert_eq!(1, updated.sub_config.foo);
+/// ```
+///
 /// # Mock Configs / Testing
 ///
 /// Internally, generated config structs store nested config structs behind [`Arc`]. This is to
@@ -336,6 +664,14 @@ use std::{marker::PhantomData, sync::Arc};
 ///
 /// This pattern can be combined with a pre-parsed const or `lazy_static!` to reduce boilerplate.
 ///
+/// ## Serialized Fixtures
+///
+/// When the config struct derives serde, so does its compact form, with the same serde attributes.
+/// Both have the same wire format, so a compact fixture can be written to or read from a file
+/// without going through `arcify`. The exception are the container conversions
+/// `#[serde(from = "...", try_from = "...", into = "...")]`, which are implemented for the struct
+/// rather than its compact form, so the compact form (de)serializes its fields directly.
+///
 /// ## With Default
 ///
 /// > Consider carefully if you really want defaults. Defaults can unwittingly lead to untested or even
@@ -364,6 +700,16 @@ use std::{marker::PhantomData, sync::Arc};
 /// let test_config = test_config.arcify();
 /// ```
 ///
+/// When the config struct derives [`Default`], so does its compact form (producing the same
+/// values), so the above can also start from `CompactAppConfig::default()`.
+///
+/// ## Test Utilities
+///
+/// The `test-util` feature enables the `conspiracy::config::testing` module, which includes a
+/// scripted `StepFetcher` for simulating config updates, assertions for checking sub-config
+/// fetchers against their parent, and `stress_fetcher` for checking custom [`ConfigFetcher`]
+/// implementations under concurrent reads and writes.
+///
 /// # Automatically Derived Traits
 ///
 /// The generated types will also get automatic implementations for:
@@ -371,6 +717,8 @@ use std::{marker::PhantomData, sync::Arc};
 /// - Traits necessary to be compatible with the [`conspiracy::config`][crate::config] ecosystem:
 ///     - [`AsField`] conversions into all nested config structs (applies recursively)
 ///     - [`RestartRequired`]
+///     - [`Reconcile`], for reloads that keep the unchanged branches of the previous snapshot (not
+///       generated with `#[conspiracy(no_restart)]`)
 /// - [`Clone`]
 /// - [`PartialEq`]
 pub use conspiracy_macros::config_struct;
@@ -380,6 +728,9 @@ pub use conspiracy_macros::config_struct;
 /// #[derive(serde::Serialize, serde::Deserialize)]
 /// pub struct Foo {}
 /// ```
+///
+/// Requires the `serde` feature (enabled by default).
+#[cfg(feature = "serde")]
 pub use conspiracy_macros::full_serde;
 /// An alias for deriving serde + serde_as, meant to replace the common config struct boilerplate:
 ///
@@ -388,13 +739,343 @@ pub use conspiracy_macros::full_serde;
 /// #[derive(serde::Serialize, serde::Deserialize)]
 /// pub struct Foo {}
 /// ```
+///
+/// Requires the `serde` feature (enabled by default).
+#[cfg(feature = "serde")]
 pub use conspiracy_macros::full_serde_as;
-pub use conspiracy_theories::config::{AsField, ConfigFetcher, RestartRequired};
+/// Derives the [`config_struct!`] machinery for an existing struct, for adopting conspiracy without
+/// rewriting config structs in the nested macro syntax.
+///
+/// Sub-configs are marked `#[conspiracy(nested)]`, must already be stored behind an [`Arc`], and
+/// must derive `ConfigStruct` themselves:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{as_shared_fetcher, shared_fetcher_from_static, ConfigStruct, SharedConfigFetcher};
+///
+/// #[derive(Clone, PartialEq, ConfigStruct)]
+/// pub struct DatabaseConfig {
+///     pub name: String,
+/// }
+///
+/// #[derive(Clone, PartialEq, ConfigStruct)]
+/// pub struct AppConfig {
+///     #[conspiracy(restart)]
+///     pub port: u16,
+///     #[conspiracy(nested)]
+///     pub database: Arc<DatabaseConfig>,
+/// }
+///
+/// let fetcher = shared_fetcher_from_static(Arc::new(AppConfig {
+///     port: 8080,
+///     database: Arc::new(DatabaseConfig { name: "prod".to_string() }),
+/// }));
+/// let database: SharedConfigFetcher<DatabaseConfig> = as_shared_fetcher(&fetcher);
+/// ```
+///
+/// This generates the same impls and methods as [`config_struct!`], including [`RestartRequired`],
+/// [`AsField`] and the compact form, and accepts the same field attributes. Since a derive can't
+/// modify the struct:
+///
+/// - The struct must implement [`Clone`] itself, and `#[conspiracy(shared)]` fields must already be
+///   declared as `Arc<T>`.
+/// - `#[conspiracy(strict)]`, `#[conspiracy(schema)]`, `#[conspiracy(arbitrary)]` and
+///   `#[conspiracy(hashable)]` are rejected, apply the serde attributes or derives they stand for
+///   directly.
+/// - Like an [external sub-config](config_struct#external-sub-configs), [`AsField`] is only
+///   generated for the direct sub-configs, not for theirs.
+///
+/// A derive can't see the other derives of its struct, so the compact form only (de)serializes when
+/// the struct uses [`full_serde`] / [`full_serde_as`] rather than deriving serde directly, and it
+/// never implements [`Default`].
+pub use conspiracy_macros::ConfigStruct;
+pub use conspiracy_theories::config::{
+    AsField, AsVariant, ConfigFetcher, Reconcile, RestartRequired,
+};
+
+use crate::config::fetcher::FromFn;
+
+pub mod ambient;
+#[cfg(feature = "json")]
+pub mod deprecated;
+pub mod fetcher;
+#[cfg(feature = "json")]
+pub mod fields;
+#[cfg(feature = "json")]
+pub mod interpolate;
+#[cfg(feature = "json")]
+pub mod patch;
+pub mod pin;
+pub mod restart;
+#[cfg(feature = "test-util")]
+pub mod testing;
+
+/// These functions are not intended to be used directly. Instead, they're used by the code
+/// generated by [`config_struct!`].
+pub mod macro_targets {
+    use std::{
+        collections::HashMap,
+        env,
+        fmt::{Debug, Display},
+        hash::Hash,
+        iter::zip,
+        str::FromStr,
+    };
+
+    use crate::config::{EnvOverrideError, RestartRequired};
+
+    /// Implemented for every config generated by [`config_struct!`][crate::config::config_struct]
+    /// or [`ConfigStruct`][crate::config::ConfigStruct], so extension traits like `ConfigFields`
+    /// and `ApplyPatch` are only implemented for configs rather than every serializable type.
+    pub trait GeneratedConfig {}
+
+    /// Reconciles a sub-config that may not implement [`Reconcile`][crate::config::Reconcile],
+    /// e.g. an external one, sharing it only when it's unchanged as a whole.
+    #[inline]
+    pub fn reconcile_by_eq<T: PartialEq>(
+        previous: &std::sync::Arc<T>,
+        next: std::sync::Arc<T>,
+    ) -> std::sync::Arc<T> {
+        if std::sync::Arc::ptr_eq(previous, &next) || **previous == *next {
+            previous.clone()
+        } else {
+            next
+        }
+    }
+
+    /// Compares two collections as maps keyed by `key`. A restart is required if an entry was added
+    /// or removed, or if an entry with the same key requires one. The order of the entries is
+    /// irrelevant, except between entries that share a key, which are compared in order.
+    pub fn keyed_restart_required<'a, T, K, I>(
+        current: I,
+        other: I,
+        key: impl Fn(&'a T) -> &'a K,
+    ) -> bool
+    where
+        T: RestartRequired + 'a,
+        K: Eq + Hash + 'a,
+        I: IntoIterator<Item = &'a T>,
+    {
+        let current = group_by_key(current, &key);
+        let other = group_by_key(other, &key);
+
+        current.len() != other.len()
+            || current.iter().any(|(key, entries)| match other.get(key) {
+                Some(others) => {
+                    entries.len() != others.len()
+                        || zip(entries, others).any(|(entry, other)| entry.restart_required(other))
+                }
+                None => true,
+            })
+    }
+
+    fn group_by_key<'a, T, K: Eq + Hash>(
+        entries: impl IntoIterator<Item = &'a T>,
+        key: impl Fn(&'a T) -> &'a K,
+    ) -> HashMap<&'a K, Vec<&'a T>> {
+        let mut groups = HashMap::<_, Vec<_>>::new();
+        for entry in entries {
+            groups.entry(key(entry)).or_default().push(entry);
+        }
+        groups
+    }
+
+    /// Reads and parses the environment variable `var`, or [`None`] if it isn't set.
+    pub fn env_override<T: FromStr>(var: &str) -> Result<Option<T>, EnvOverrideError>
+    where
+        T::Err: Display,
+    {
+        let value = match env::var(var) {
+            Ok(value) => value,
+            Err(env::VarError::NotPresent) => return Ok(None),
+            Err(error) => return Err(EnvOverrideError::new(var, error)),
+        };
+
+        value
+            .parse()
+            .map(Some)
+            .map_err(|error| EnvOverrideError::new(var, error))
+    }
+
+    /// Appends `field` to the dotted `path` of its parent, the root's path being empty.
+    pub fn dotted_path(path: &str, field: &str) -> String {
+        if path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{path}.{field}")
+        }
+    }
+
+    /// Writes a config's fields as `path = value` lines, implemented for every config generated by
+    /// [`config_struct!`][crate::config::config_struct].
+    pub trait WriteDotted {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>);
+    }
+
+    /// Wraps a field to select how it's written by autoref specialization, in order of preference:
+    /// as a config ([`WriteDotted`]), with [`Display`], with [`Debug`], or as `<unprintable>`.
+    /// The field's type is always concrete in the generated code, so the most specific applicable
+    /// impl is picked by method resolution: `(&&&&Dotted(&field)).write_dotted(path, lines)`.
+    pub struct Dotted<'a, T: ?Sized>(pub &'a T);
+
+    pub trait DottedConfig {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>);
+    }
+
+    impl<T: WriteDotted + ?Sized> DottedConfig for &&&Dotted<'_, T> {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+            self.0.write_dotted(path, lines);
+        }
+    }
+
+    pub trait DottedDisplay {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>);
+    }
+
+    impl<T: Display + ?Sized> DottedDisplay for &&Dotted<'_, T> {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+            lines.push(format!("{path} = {}", self.0));
+        }
+    }
+
+    pub trait DottedDebug {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>);
+    }
+
+    impl<T: Debug + ?Sized> DottedDebug for &Dotted<'_, T> {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+            lines.push(format!("{path} = {:?}", self.0));
+        }
+    }
+
+    pub trait DottedUnprintable {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>);
+    }
+
+    impl<T: ?Sized> DottedUnprintable for Dotted<'_, T> {
+        fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+            lines.push(format!("{path} = <unprintable>"));
+        }
+    }
+}
+
+/// Error returned when a field marked `#[conspiracy(env = "...")]` couldn't be overridden by its
+/// environment variable.
+#[derive(thiserror::Error, Debug)]
+#[error("Failed to override config with environment variable `{var}`: {reason}")]
+pub struct EnvOverrideError {
+    var: String,
+    reason: String,
+}
+
+impl EnvOverrideError {
+    fn new(var: &str, reason: impl Display) -> Self {
+        Self {
+            var: var.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Lists the fields marked `#[conspiracy(deprecated = "...")]`, implemented for every config
+/// generated by [`config_struct!`] or [`ConfigStruct`]. With the `json` feature, the
+/// [`deprecated`] module checks a loaded document for them.
+pub trait DeprecatedFields {
+    /// The deprecated fields of this config and its nested struct sub-configs. A field that can be
+    /// loaded from several keys (i.e. has aliases) is listed once per key.
+    fn deprecated_fields() -> Vec<DeprecatedField>;
+}
+
+/// A field marked `#[conspiracy(deprecated = "...")]`, see [`DeprecatedFields`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedField {
+    /// The keys leading to the field from the root of the document, i.e. the serialized (renamed)
+    /// field names.
+    pub path: Vec<&'static str>,
+    /// The message given to the attribute, e.g. `use foo instead`.
+    pub message: &'static str,
+}
+
+impl DeprecatedField {
+    /// The path joined with `.`, e.g. `database.pool`.
+    pub fn dotted_path(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+/// Error returned when building a config with a `#[conspiracy(builder)]` builder without setting
+/// every field that isn't an `Option`. Holds the names of the missing fields.
+#[derive(thiserror::Error, Debug)]
+#[error("Config fields were not set: {}", .0.join(", "))]
+pub struct MissingConfigFieldsError(pub Vec<&'static str>);
 
 /// A shared instance of a `ConfigFetcher` that can be converted in sub-config fetchers and shared
 /// across threads.
 pub type SharedConfigFetcher<T> = Arc<dyn ConfigFetcher<T> + Send + Sync>;
 
+/// A [`SharedConfigFetcher`] that can also be downcast back to the concrete fetcher, see
+/// [`AnyConfigFetcher`]. Create one with [`into_any_shared_fetcher`].
+pub type AnySharedConfigFetcher<T> = Arc<dyn AnyConfigFetcher<T>>;
+
+/// The trait object behind [`AnySharedConfigFetcher`], implemented for every [`ConfigFetcher`] that
+/// can be shared across threads. On top of fetching, it allows the type erased fetcher to be
+/// downcast back to its concrete type, e.g. to call [`store`][fetcher::SwapFetcher::store] on a
+/// [`SwapFetcher`][fetcher::SwapFetcher]:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{
+///     fetcher::SwapFetcher, into_any_shared_fetcher, AnySharedConfigFetcher, ConfigFetcher,
+/// };
+///
+/// let shared: AnySharedConfigFetcher<u16> =
+///     into_any_shared_fetcher(SwapFetcher::new(Arc::new(8080)));
+///
+/// let swap = shared.as_any().downcast_ref::<SwapFetcher<u16>>().unwrap();
+/// swap.store(Arc::new(9090));
+/// assert_eq!(9090, *shared.latest_snapshot());
+///
+/// // The concrete type, including its generics, must match exactly
+/// assert!(shared.as_any().downcast_ref::<SwapFetcher<u32>>().is_none());
+///
+/// // Code that only fetches takes the plain alias
+/// let fetcher = shared.clone().into_shared();
+/// assert_eq!(9090, *fetcher.latest_snapshot());
+/// ```
+///
+/// Downcasting only sees the outermost fetcher. The fetchers created by [`as_shared_fetcher`],
+/// [`shared_fetcher_from_fn`] and the like are closures over their source, so they can't be
+/// downcast to it; hold on to the concrete fetcher instead when it's needed later. Reaching for a
+/// concrete fetcher also couples the code back to that implementation, which is what sharing the
+/// fetcher as a trait object avoids, so prefer passing the concrete type where that's practical.
+pub trait AnyConfigFetcher<T>: ConfigFetcher<T> + Any + Send + Sync {
+    /// The fetcher as [`Any`], for `downcast_ref`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// The fetcher as [`Any`], for [`Arc::downcast`].
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    /// The fetcher as a [`SharedConfigFetcher`], for code that only fetches.
+    fn into_shared(self: Arc<Self>) -> SharedConfigFetcher<T>;
+}
+
+impl<T, F: ConfigFetcher<T> + Any + Send + Sync> AnyConfigFetcher<T> for F {
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    #[inline]
+    fn into_shared(self: Arc<Self>) -> SharedConfigFetcher<T> {
+        self
+    }
+}
+
 /// Creates a [`SharedConfigFetcher`] for the sub-config of the given fetcher.
 ///
 /// More formally, this generates a [`SharedConfigFetcher<T2>`] from a [`SharedConfigFetcher<T>`]
@@ -413,6 +1094,98 @@ where
     })
 }
 
+/// Creates a [`SharedConfigFetcher`] for the sub-config of an owned fetcher. This is
+/// [`as_shared_fetcher`] for when the parent fetcher itself isn't needed afterwards, so it doesn't
+/// have to be wrapped in an [`Arc`] first:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{as_shared_fetcher_owned, config_struct, ConfigFetcher, SharedConfigFetcher};
+///
+/// config_struct!(
+///     pub struct Config {
+///         sub_config: pub struct SubConfig {
+///             foo: u32,
+///         }
+///     }
+/// );
+///
+/// struct FileFetcher {
+///     config: Arc<Config>,
+/// }
+///
+/// impl ConfigFetcher<Config> for FileFetcher {
+///     fn latest_snapshot(&self) -> Arc<Config> {
+///         self.config.clone()
+///     }
+/// }
+///
+/// let fetcher = FileFetcher {
+///     config: Arc::new(Config { sub_config: Arc::new(SubConfig { foo: 1 }) }),
+/// };
+/// let sub_config: SharedConfigFetcher<SubConfig> = as_shared_fetcher_owned(fetcher);
+/// assert_eq!(1, sub_config.latest_snapshot().foo);
+/// ```
+pub fn as_shared_fetcher_owned<T, T2, F>(fetcher: F) -> SharedConfigFetcher<T2>
+where
+    F: ConfigFetcher<T> + Send + Sync + 'static,
+    T: AsField<T2>,
+    T2: Send + Sync + 'static,
+{
+    as_shared_fetcher(&Arc::new(fetcher))
+}
+
+/// Creates a [`SharedConfigFetcher`] for a sub-config that may be absent, i.e. a variant of an
+/// enum sub-config or an `Option<@Type>` sub-config (see [`AsVariant`]).
+///
+/// The sub-config is projected from every snapshot of the parent fetcher, so the fetcher yields
+/// [`None`] while the sub-config is absent and [`Some`] whenever a later snapshot has it again.
+/// Callers should treat a transition between the two like any other config change, e.g. tearing
+/// down or starting the component the sub-config belongs to.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{
+///     as_optional_shared_fetcher, config_struct, fetcher::SwapFetcher, SharedConfigFetcher,
+/// };
+///
+/// config_struct!(
+///     pub struct StorageConfig {
+///         backend: pub enum Backend {
+///             Postgres(pub struct PostgresConfig { url: String }),
+///             InMemory(pub struct InMemoryConfig { capacity: usize }),
+///         }
+///     }
+/// );
+///
+/// let postgres = Arc::new(PostgresConfig { url: "pg://".to_string() });
+/// let fetcher = Arc::new(SwapFetcher::new(Arc::new(StorageConfig {
+///     backend: Arc::new(Backend::Postgres(postgres)),
+/// })));
+/// let postgres: SharedConfigFetcher<Option<Arc<PostgresConfig>>> =
+///     as_optional_shared_fetcher(&fetcher);
+/// assert!(postgres.latest_snapshot().is_some());
+///
+/// fetcher.store(Arc::new(StorageConfig {
+///     backend: Arc::new(Backend::InMemory(Arc::new(InMemoryConfig { capacity: 10 }))),
+/// }));
+/// assert!(postgres.latest_snapshot().is_none());
+/// ```
+pub fn as_optional_shared_fetcher<T, T2, F>(
+    fetcher: &Arc<F>,
+) -> SharedConfigFetcher<Option<Arc<T2>>>
+where
+    F: ConfigFetcher<T> + ?Sized + Send + Sync + 'static,
+    T: AsVariant<T2>,
+    T2: Send + Sync + 'static,
+{
+    let clone = fetcher.clone();
+    shared_fetcher_from_fn(move || {
+        let snapshot: Arc<T> = clone.latest_snapshot();
+        Arc::new(snapshot.share_variant())
+    })
+}
+
 /// Constructs a [`SharedConfigFetcher`] from a closure that returns a new snapshot.
 pub fn shared_fetcher_from_fn<
     T: Send + Sync + 'static,
@@ -420,10 +1193,7 @@ pub fn shared_fetcher_from_fn<
 >(
     fetcher: F,
 ) -> SharedConfigFetcher<T> {
-    Arc::new(WrappedFetcher {
-        inner: fetcher,
-        phantom: PhantomData {},
-    })
+    Arc::new(FromFn::new(fetcher))
 }
 
 /// Constructs a [`SharedConfigFetcher`] from a static value.
@@ -452,20 +1222,13 @@ pub fn into_shared_fetcher<T: Send + Sync + 'static>(
     fetcher: impl ConfigFetcher<T> + Send + Sync + 'static,
 ) -> SharedConfigFetcher<T> {
     let fetcher = Arc::new(fetcher);
-    Arc::new(WrappedFetcher {
-        inner: move || fetcher.latest_snapshot(),
-        phantom: PhantomData {},
-    })
+    Arc::new(FromFn::new(move || fetcher.latest_snapshot()))
 }
 
-#[derive(Clone)]
-pub(crate) struct WrappedFetcher<T, F: Fn() -> Arc<T>> {
-    pub(crate) inner: F,
-    pub(crate) phantom: PhantomData<T>,
-}
-
-impl<T, F: Fn() -> Arc<T>> ConfigFetcher<T> for WrappedFetcher<T, F> {
-    fn latest_snapshot(&self) -> Arc<T> {
-        (self.inner)()
-    }
+/// Converts an owned [`ConfigFetcher`] into an [`AnySharedConfigFetcher`], which can be downcast
+/// back to the fetcher's type.
+pub fn into_any_shared_fetcher<T: Send + Sync + 'static>(
+    fetcher: impl ConfigFetcher<T> + Send + Sync + 'static,
+) -> AnySharedConfigFetcher<T> {
+    Arc::new(fetcher)
 }
@@ -0,0 +1,79 @@
+//! An escape hatch for reading config without it being passed in.
+//!
+//! **Prefer injection.** As described in [the config module][crate::config#injection-usage],
+//! config should be a part of your signatures, so every dependency on it is visible and tests can
+//! supply their own. Ambient config hides those dependencies, and since it can only be set once per
+//! process, tests that read it can't vary it. If a `static` is genuinely needed, a
+//! [`GlobalConfigFetcher`][crate::config::fetcher::GlobalConfigFetcher] scoped to the module that
+//! needs it is still more explicit than this.
+//!
+//! This module exists for the places injection can't reach, such as callbacks registered with a
+//! third party library that don't accept any context. The application sets its root config once
+//! during startup, and those callbacks read it:
+//!
+//! ```rust
+//! # use std::sync::Arc;
+//! use conspiracy::config::{
+//!     ambient::{ambient_config, set_ambient_config},
+//!     config_struct, fetcher::SwapFetcher,
+//! };
+//!
+//! config_struct!(
+//!     pub struct AppConfig {
+//!         pub verbose: bool,
+//!     }
+//! );
+//!
+//! let fetcher = Arc::new(SwapFetcher::new(Arc::new(AppConfig { verbose: false })));
+//! set_ambient_config(fetcher.clone()).unwrap();
+//!
+//! fn log_callback() -> bool {
+//!     ambient_config::<AppConfig>().is_some_and(|config| config.verbose)
+//! }
+//!
+//! assert!(!log_callback());
+//! fetcher.store(Arc::new(AppConfig { verbose: true }));
+//! assert!(log_callback());
+//! ```
+//!
+//! There's a single ambient config per process, meant for the application's root config. Libraries
+//! must never set it, they should accept config from their caller instead.
+
+use std::{
+    any::Any,
+    sync::{Arc, OnceLock},
+};
+
+use crate::config::SharedConfigFetcher;
+
+static AMBIENT_CONFIG: OnceLock<Box<dyn Any + Send + Sync>> = OnceLock::new();
+
+/// Set the process wide ambient config. Reads go through `fetcher`, so updates to it are visible
+/// to [`ambient_config`]. This can only be called once, subsequent calls will be rejected.
+///
+/// See the [module docs][self] before reaching for this.
+pub fn set_ambient_config<T: Send + Sync + 'static>(
+    fetcher: SharedConfigFetcher<T>,
+) -> Result<(), AmbientConfigAlreadySetError> {
+    AMBIENT_CONFIG
+        .set(Box::new(fetcher))
+        .map_err(|_| AmbientConfigAlreadySetError)
+}
+
+/// The latest snapshot of the ambient config, or [`None`] if [`set_ambient_config`] hasn't been
+/// called yet or was called with a config type other than `T`.
+///
+/// See the [module docs][self] before reaching for this.
+pub fn ambient_config<T: Send + Sync + 'static>() -> Option<Arc<T>> {
+    AMBIENT_CONFIG
+        .get()?
+        .downcast_ref::<SharedConfigFetcher<T>>()
+        .map(|fetcher| fetcher.latest_snapshot())
+}
+
+/// Error returned when [`set_ambient_config`] is called more than once.
+#[derive(thiserror::Error, Debug)]
+#[error(
+    "The ambient config has already been set. `set_ambient_config` cannot be called multiple times"
+)]
+pub struct AmbientConfigAlreadySetError;
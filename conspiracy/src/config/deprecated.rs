@@ -0,0 +1,77 @@
+//! Reporting deprecated config fields that are still present in a loaded document, so a field can be
+//! phased out without rejecting the configs that still set it. Requires the `json` feature.
+//!
+//! Fields are marked with `#[conspiracy(deprecated = "...")]`. Since a missing field and one set to
+//! its default deserialize the same way, the check runs against the raw document, before it's
+//! deserialized:
+//!
+//! ```rust
+//! use conspiracy::config::{config_struct, deprecated, full_serde};
+//! use serde_json::json;
+//!
+//! config_struct!(
+//!     #[full_serde]
+//!     pub struct AppConfig {
+//!         pub port: u16,
+//!         #[conspiracy(deprecated = "use `port` instead")]
+//!         pub listen_port: Option<u16>,
+//!     }
+//! );
+//!
+//! let document = json!({ "port": 8080, "listen_port": 8080 });
+//! let present = deprecated::present_fields::<AppConfig>(&document);
+//! assert_eq!("listen_port", present[0].dotted_path());
+//!
+//! // Or report them (with the `tracing` feature) while loading
+//! let config: AppConfig = deprecated::from_value(document).unwrap();
+//! assert_eq!(Some(8080), config.listen_port);
+//! ```
+//!
+//! Fields beneath enum and external (`field: @Type`) sub-configs aren't checked, since the keys
+//! beneath them depend on how they (de)serialize.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::{DeprecatedField, DeprecatedFields};
+
+/// The deprecated fields of `T` that are present in `document`.
+pub fn present_fields<T: DeprecatedFields>(document: &Value) -> Vec<DeprecatedField> {
+    T::deprecated_fields()
+        .into_iter()
+        .filter(|field| {
+            field
+                .path
+                .iter()
+                .try_fold(document, |value, key| value.get(key))
+                .is_some()
+        })
+        .collect()
+}
+
+/// Reports the deprecated fields of `T` that are present in `document`, returning them. With the
+/// `tracing` feature, each emits a `warn` event, otherwise they're only returned.
+pub fn warn_present_fields<T: DeprecatedFields>(document: &Value) -> Vec<DeprecatedField> {
+    let present = present_fields::<T>(document);
+
+    #[cfg(feature = "tracing")]
+    for field in &present {
+        tracing::warn!(
+            config = std::any::type_name::<T>(),
+            field = field.dotted_path(),
+            message = field.message,
+            "Loaded config sets a deprecated field"
+        );
+    }
+
+    present
+}
+
+/// Deserialize `T` from `document`, first reporting any deprecated fields it sets with
+/// [`warn_present_fields`]. Intended as a drop-in for [`serde_json::from_value`] in loaders.
+pub fn from_value<T: DeserializeOwned + DeprecatedFields>(
+    document: Value,
+) -> Result<T, serde_json::Error> {
+    warn_present_fields::<T>(&document);
+    serde_json::from_value(document)
+}
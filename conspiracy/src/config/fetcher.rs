@@ -0,0 +1,1268 @@
+//! Included [`ConfigFetcher`] implementations and adapters.
+
+#[cfg(feature = "tokio")]
+mod channel;
+#[cfg(feature = "json")]
+mod convention;
+#[cfg(feature = "env")]
+mod env;
+#[cfg(feature = "json-schema")]
+mod schema;
+#[cfg(feature = "json")]
+mod value;
+
+#[cfg(feature = "metrics")]
+use std::sync::atomic::AtomicU64;
+use std::{
+    fmt::Display,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock, RwLock, Weak,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use arc_swap::ArcSwap;
+pub use arc_swap::Guard;
+#[cfg(feature = "tokio")]
+pub use channel::ChannelFetcher;
+use conspiracy_theories::{
+    config::{AsField, ConfigFetcher, Reconcile, RestartRequired},
+    feature::FeatureSet,
+};
+#[cfg(feature = "json")]
+pub use convention::{load_by_convention, ConventionConfigError};
+#[cfg(feature = "env")]
+pub use env::{EnvConfigError, EnvConfigFetcher};
+#[cfg(feature = "json-schema")]
+pub use schema::{schema_validated, SchemaValidationError};
+#[cfg(feature = "json")]
+pub use value::ValueFetcher;
+
+use crate::config::{shared_fetcher_from_fn, SharedConfigFetcher};
+
+/// A [`ConfigFetcher`] backed by a closure that returns a new snapshot. This is the owned
+/// counterpart of [`shared_fetcher_from_fn`], for when the fetcher doesn't need to be type erased
+/// behind a [`SharedConfigFetcher`]:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{fetcher::FromFn, ConfigFetcher};
+///
+/// fn port(fetcher: &impl ConfigFetcher<u16>) -> u16 {
+///     *fetcher.latest_snapshot()
+/// }
+///
+/// let config = Arc::new(8080u16);
+/// let fetcher = FromFn::new(move || config.clone());
+///
+/// assert_eq!(8080, port(&fetcher));
+/// ```
+pub struct FromFn<T, F: Fn() -> Arc<T>> {
+    inner: F,
+    phantom: PhantomData<T>,
+}
+
+// Not derived, which would also require `T: Clone` even though only the closure is cloned
+impl<T, F: Fn() -> Arc<T> + Clone> Clone for FromFn<T, F> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, F: Fn() -> Arc<T>> FromFn<T, F> {
+    /// Wrap `fetcher`, calling it for every snapshot.
+    pub fn new(fetcher: F) -> Self {
+        Self {
+            inner: fetcher,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, F: Fn() -> Arc<T>> ConfigFetcher<T> for FromFn<T, F> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        (self.inner)()
+    }
+}
+
+/// A [`ConfigFetcher`] holding a snapshot that can be atomically replaced, backed by
+/// [`ArcSwap`]. Reads are lock-free, and readers keep whichever snapshot they already hold until
+/// they request a new one.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{fetcher::SwapFetcher, ConfigFetcher};
+///
+/// let fetcher = SwapFetcher::new(Arc::new(1u32));
+/// let before = fetcher.latest_snapshot();
+///
+/// fetcher.store(Arc::new(2));
+///
+/// assert_eq!(1, *before);
+/// assert_eq!(2, *fetcher.latest_snapshot());
+/// ```
+pub struct SwapFetcher<T> {
+    current: ArcSwap<T>,
+}
+
+impl<T> SwapFetcher<T> {
+    /// Start with `initial` as the current snapshot.
+    pub fn new(initial: Arc<T>) -> Self {
+        Self {
+            current: ArcSwap::new(initial),
+        }
+    }
+
+    /// Replace the current snapshot. Snapshots (and guards) taken before this call are unaffected.
+    pub fn store(&self, snapshot: Arc<T>) {
+        self.current.store(snapshot);
+    }
+
+    /// Like [`store`][SwapFetcher::store], but the branches of `snapshot` that are unchanged keep
+    /// the [`Arc`] they have in the current snapshot, see [`Reconcile`].
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// use conspiracy::config::{config_struct, fetcher::SwapFetcher, ConfigFetcher};
+    ///
+    /// config_struct!(
+    ///     pub struct Config {
+    ///         port: u16,
+    ///         sub_config: pub struct SubConfig {
+    ///             foo: u32,
+    ///         }
+    ///     }
+    /// );
+    ///
+    /// let config = |port| Arc::new(Config { port, sub_config: Arc::new(SubConfig { foo: 1 }) });
+    /// let fetcher = SwapFetcher::new(config(80));
+    /// let before = fetcher.latest_snapshot();
+    ///
+    /// fetcher.store_reconciled(config(8080));
+    /// assert!(Arc::ptr_eq(&before.sub_config, &fetcher.latest_snapshot().sub_config));
+    /// ```
+    pub fn store_reconciled(&self, snapshot: Arc<T>)
+    where
+        T: Reconcile,
+    {
+        self.current
+            .rcu(|current| T::reconcile(current, snapshot.clone()));
+    }
+
+    /// Borrow the current snapshot without incrementing its reference count, for hot paths that
+    /// read config at a high rate.
+    ///
+    /// The guard is meant for short-lived reads, such as checking a value within a single function.
+    /// It keeps the snapshot alive like an [`Arc`] would, but each thread can only hold a few
+    /// guards cheaply; holding many at once, or holding one for a long time (e.g. across an
+    /// `.await` or for a whole request), falls back to a slower path. For those cases, use
+    /// [`latest_snapshot`][ConfigFetcher::latest_snapshot] instead, or convert the guard with
+    /// [`Guard::into_inner`].
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// use conspiracy::config::fetcher::SwapFetcher;
+    ///
+    /// let fetcher = SwapFetcher::new(Arc::new(42u32));
+    /// assert_eq!(42, **fetcher.load_guard());
+    /// ```
+    #[inline]
+    pub fn load_guard(&self) -> Guard<Arc<T>> {
+        self.current.load()
+    }
+
+    /// A fetcher for the sub-config `T2`, projected straight from the current snapshot. This is the
+    /// hot path counterpart of [`as_shared_fetcher`][crate::config::as_shared_fetcher]: each read
+    /// borrows the snapshot with a single [`load_guard`][SwapFetcher::load_guard] and clones only
+    /// the sub-config's [`Arc`], instead of cloning the parent snapshot's [`Arc`] and calling
+    /// through a boxed closure.
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// use conspiracy::config::{config_struct, fetcher::SwapFetcher, ConfigFetcher, SharedConfigFetcher};
+    ///
+    /// config_struct!(
+    ///     pub struct Config {
+    ///         sub_config: pub struct SubConfig {
+    ///             foo: u32,
+    ///         }
+    ///     }
+    /// );
+    ///
+    /// let fetcher = Arc::new(SwapFetcher::new(Arc::new(Config {
+    ///     sub_config: Arc::new(SubConfig { foo: 1 }),
+    /// })));
+    /// let sub_config = fetcher.project::<SubConfig>();
+    /// assert_eq!(1, sub_config.latest_snapshot().foo);
+    ///
+    /// // It can still be type erased where needed
+    /// let shared: SharedConfigFetcher<SubConfig> = Arc::new(sub_config);
+    /// ```
+    pub fn project<T2>(self: &Arc<Self>) -> ProjectedFetcher<T, T2>
+    where
+        T: AsField<T2>,
+    {
+        ProjectedFetcher {
+            source: self.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> ConfigFetcher<T> for SwapFetcher<T> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+}
+
+/// A sub-config fetcher projected from a [`SwapFetcher`], see [`SwapFetcher::project`].
+pub struct ProjectedFetcher<T, T2> {
+    source: Arc<SwapFetcher<T>>,
+    phantom: PhantomData<fn() -> T2>,
+}
+
+impl<T: AsField<T2>, T2> ConfigFetcher<T2> for ProjectedFetcher<T, T2> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T2> {
+        self.source.load_guard().share()
+    }
+}
+
+/// A [`ConfigFetcher`] that (re)loads its config with a fallible loader, e.g. reading and parsing a
+/// file or calling a remote config service. Reads always return the last successfully loaded
+/// snapshot; a failed reload keeps it and is recorded in the fetcher's [`FetcherHealth`] instead.
+///
+/// ```rust
+/// # use std::{fs, sync::Arc};
+/// use conspiracy::config::{config_struct, fetcher::PollingFetcher, full_serde, ConfigFetcher};
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         port: u16,
+///     }
+/// );
+///
+/// let path = std::env::temp_dir().join("polling_fetcher_doc.json");
+/// fs::write(&path, r#"{ "port": 8080 }"#).unwrap();
+///
+/// let load_path = path.clone();
+/// let fetcher = PollingFetcher::new(move || {
+///     serde_json::from_str::<AppConfig>(&fs::read_to_string(&load_path)?).map_err(std::io::Error::from)
+/// })
+/// .unwrap();
+/// assert_eq!(8080, fetcher.latest_snapshot().port);
+///
+/// // A broken file doesn't replace the last good config
+/// fs::write(&path, "{").unwrap();
+/// assert!(fetcher.reload().is_err());
+/// assert_eq!(8080, fetcher.latest_snapshot().port);
+/// assert_eq!(1, fetcher.health().consecutive_failures);
+/// # fs::remove_file(&path).unwrap();
+/// ```
+///
+/// Reloads happen when [`reload`][PollingFetcher::reload] is called, or periodically on a background
+/// thread with [`poll_every`][PollingFetcher::poll_every]. With the `tracing` feature, each reload
+/// emits an `info` event when the snapshot is replaced (including whether it requires a restart,
+/// see [`report_restarts`][PollingFetcher::report_restarts]) or a `warn` event with the error when
+/// it isn't.
+pub struct PollingFetcher<T, L> {
+    load: L,
+    current: ArcSwap<T>,
+    health: Mutex<FetcherHealth>,
+    reconcile: Option<ReconcileFn<T>>,
+    // Only reported by the reload event
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    restarts: Option<RestartsFn<T>>,
+}
+
+type ReconcileFn<T> = fn(&Arc<T>, Arc<T>) -> Arc<T>;
+type RestartsFn<T> = fn(&T, &T) -> (bool, Vec<&'static str>);
+
+/// The outcome of a fetcher's recent load attempts, e.g. for a readiness probe.
+#[derive(Debug, Clone)]
+pub struct FetcherHealth {
+    /// When the config currently being served was loaded.
+    pub last_success: Instant,
+    /// The error of the most recent load attempt, if it failed.
+    pub last_error: Option<String>,
+    /// The number of load attempts that have failed since the last success.
+    pub consecutive_failures: u32,
+}
+
+impl<T, L: TryConfigFetcher<T>> PollingFetcher<T, L>
+where
+    L::Error: Display,
+{
+    /// Perform the initial load, which must succeed since there's no previous config to fall back
+    /// to.
+    pub fn new(load: L) -> Result<Self, L::Error> {
+        let initial = load.try_load()?;
+
+        Ok(Self {
+            load,
+            current: ArcSwap::from_pointee(initial),
+            health: Mutex::new(FetcherHealth {
+                last_success: Instant::now(),
+                last_error: None,
+                consecutive_failures: 0,
+            }),
+            reconcile: None,
+            restarts: None,
+        })
+    }
+
+    /// Load the config again, replacing the current snapshot on success. On failure the current
+    /// snapshot is kept and the error is recorded in [`health`][PollingFetcher::health].
+    pub fn reload(&self) -> Result<(), L::Error> {
+        let loaded = self.load.try_load();
+
+        let mut health = self.health.lock().unwrap();
+        match loaded {
+            Ok(config) => {
+                let config = match self.reconcile {
+                    Some(reconcile) => reconcile(&self.current.load(), Arc::new(config)),
+                    None => Arc::new(config),
+                };
+                #[cfg(feature = "tracing")]
+                match self.restarts {
+                    Some(restarts) => {
+                        let (restart_required, restart_reasons) =
+                            restarts(&self.current.load(), &config);
+                        tracing::info!(
+                            config = std::any::type_name::<T>(),
+                            restart_required,
+                            restart_reasons = ?restart_reasons,
+                            "Applied new config snapshot"
+                        );
+                    }
+                    None => tracing::info!(
+                        config = std::any::type_name::<T>(),
+                        "Applied new config snapshot"
+                    ),
+                }
+                self.current.store(config);
+                *health = FetcherHealth {
+                    last_success: Instant::now(),
+                    last_error: None,
+                    consecutive_failures: 0,
+                };
+                Ok(())
+            }
+            Err(error) => {
+                health.last_error = Some(error.to_string());
+                health.consecutive_failures += 1;
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    config = std::any::type_name::<T>(),
+                    error = %error,
+                    consecutive_failures = health.consecutive_failures,
+                    "Failed to reload config, keeping the previous snapshot"
+                );
+                Err(error)
+            }
+        }
+    }
+
+    /// The outcome of the recent load attempts.
+    pub fn health(&self) -> FetcherHealth {
+        self.health.lock().unwrap().clone()
+    }
+
+    /// [`Reconcile`] each reloaded config with the current snapshot, so that only the branches that
+    /// changed are replaced. Sub-config fetchers of unchanged branches then keep returning the same
+    /// [`Arc`] across reloads.
+    pub fn reconciled(mut self) -> Self
+    where
+        T: Reconcile,
+    {
+        self.reconcile = Some(T::reconcile);
+        self
+    }
+
+    /// Compare each reloaded config with the current snapshot, so that with the `tracing` feature
+    /// the reload's `info` event also reports `restart_required` and the `restart_reasons`, i.e.
+    /// which restart fields changed.
+    pub fn report_restarts(mut self) -> Self
+    where
+        T: RestartRequired,
+    {
+        self.restarts = Some(|current, next| {
+            (
+                current.restart_required(next),
+                current.restart_reasons(next),
+            )
+        });
+        self
+    }
+}
+
+impl<T, L> PollingFetcher<T, L>
+where
+    T: Send + Sync + 'static,
+    L: TryConfigFetcher<T> + Send + Sync + 'static,
+    L::Error: Display,
+{
+    /// Spawn a background thread that reloads every `interval`. The thread stops once every other
+    /// reference to the fetcher has been dropped. Failures are only reported through
+    /// [`health`][PollingFetcher::health].
+    pub fn poll_every(self: &Arc<Self>, interval: Duration) -> thread::JoinHandle<()> {
+        let fetcher = Arc::downgrade(self);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            match fetcher.upgrade() {
+                Some(fetcher) => {
+                    let _ = fetcher.reload();
+                }
+                None => return,
+            }
+        })
+    }
+}
+
+impl<T, L> ConfigFetcher<T> for PollingFetcher<T, L> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+}
+
+/// A fallible source of configs, e.g. a file or remote endpoint that may be unreachable. Unlike a
+/// [`ConfigFetcher`], which always has a snapshot to serve, each load may fail. Every closure
+/// `Fn() -> Result<T, E>` is one, so a loader can be given to [`PollingFetcher::new`] directly.
+///
+/// The combinators attach context to the errors, so a failure within a chain of wrapped or layered
+/// loaders names the source that failed:
+///
+/// ```rust
+/// use conspiracy::config::fetcher::TryConfigFetcher;
+///
+/// let file = (|| Err::<u32, _>("permission denied")).with_context("config.json");
+/// let base = file.with_context("base layer");
+///
+/// let error = base.try_load().unwrap_err();
+/// assert_eq!("base layer: config.json: permission denied", error.to_string());
+/// ```
+pub trait TryConfigFetcher<T> {
+    /// The error of a failed load.
+    type Error;
+
+    /// Load the config.
+    fn try_load(&self) -> Result<T, Self::Error>;
+
+    /// Prefix errors with `context`, e.g. the file or layer being loaded. Wrapping again adds
+    /// another breadcrumb.
+    fn with_context(self, context: impl Into<String>) -> WithContext<Self>
+    where
+        Self: Sized,
+    {
+        WithContext {
+            load: self,
+            context: context.into(),
+        }
+    }
+
+    /// Convert errors with `map`, e.g. so layers with different error types can be combined by a
+    /// [`LayeredConfigFetcher`].
+    fn map_err<E, M: Fn(Self::Error) -> E>(self, map: M) -> MapErr<Self, M>
+    where
+        Self: Sized,
+    {
+        MapErr { load: self, map }
+    }
+}
+
+impl<T, E, L: Fn() -> Result<T, E>> TryConfigFetcher<T> for L {
+    type Error = E;
+
+    #[inline]
+    fn try_load(&self) -> Result<T, E> {
+        self()
+    }
+}
+
+/// Wraps a fallible loader so its errors name the source that failed, the free function form of
+/// [`TryConfigFetcher::with_context`]:
+///
+/// ```rust
+/// use conspiracy::config::fetcher::{with_context, PollingFetcher};
+///
+/// let file = with_context("config.json", || Err::<u32, _>("permission denied"));
+/// let base = with_context("base layer", file);
+///
+/// let error = PollingFetcher::new(base).err().unwrap();
+/// assert_eq!("base layer: config.json: permission denied", error.to_string());
+/// ```
+pub fn with_context<T, L: TryConfigFetcher<T>>(
+    context: impl Into<String>,
+    load: L,
+) -> WithContext<L> {
+    load.with_context(context)
+}
+
+/// A loader whose errors are prefixed with context, see [`TryConfigFetcher::with_context`].
+pub struct WithContext<L> {
+    load: L,
+    context: String,
+}
+
+impl<T, L: TryConfigFetcher<T>> TryConfigFetcher<T> for WithContext<L> {
+    type Error = ContextError<L::Error>;
+
+    fn try_load(&self) -> Result<T, Self::Error> {
+        self.load.try_load().map_err(|error| ContextError {
+            context: self.context.clone(),
+            error,
+        })
+    }
+}
+
+/// Error returned by a loader wrapped with [`with_context`].
+#[derive(thiserror::Error, Debug)]
+#[error("{context}: {error}")]
+pub struct ContextError<E> {
+    /// The context given to [`with_context`], e.g. the file or layer being loaded.
+    pub context: String,
+    /// The loader's own error.
+    pub error: E,
+}
+
+/// A loader whose errors are converted, see [`TryConfigFetcher::map_err`].
+pub struct MapErr<L, M> {
+    load: L,
+    map: M,
+}
+
+impl<T, E, L: TryConfigFetcher<T>, M: Fn(L::Error) -> E> TryConfigFetcher<T> for MapErr<L, M> {
+    type Error = E;
+
+    #[inline]
+    fn try_load(&self) -> Result<T, E> {
+        self.load.try_load().map_err(&self.map)
+    }
+}
+
+/// A [`TryConfigFetcher`] that falls back through layers of sources, e.g. a remote endpoint backed
+/// by a local file. Layers are consulted in the order they were added and the first one that loads
+/// provides the config. If every layer fails, the error lists each layer's error in order, so
+/// wrapping the layers [`with_context`] shows which source failed and why:
+///
+/// ```rust
+/// use conspiracy::config::{
+///     fetcher::{LayeredConfigFetcher, PollingFetcher, TryConfigFetcher},
+///     ConfigFetcher,
+/// };
+///
+/// let remote = (|| Err::<u32, _>("connection refused")).with_context("remote");
+/// let file = (|| Ok(8080)).with_context("config.json");
+///
+/// let fetcher = PollingFetcher::new(LayeredConfigFetcher::new().with_layer(remote).with_layer(file));
+/// assert_eq!(8080, *fetcher.unwrap().latest_snapshot());
+///
+/// let cache = (|| Err("not found")).with_context("cache");
+/// let error = LayeredConfigFetcher::<u32, _>::new()
+///     .with_layer((|| Err("connection refused")).with_context("remote"))
+///     .with_layer(cache)
+///     .try_load()
+///     .unwrap_err();
+/// assert_eq!(
+///     "every layer failed: remote: connection refused; cache: not found",
+///     error.to_string()
+/// );
+/// ```
+///
+/// The layers share an error type, use [`TryConfigFetcher::map_err`] to convert layers whose error
+/// types differ.
+pub struct LayeredConfigFetcher<T, E> {
+    layers: Vec<Box<dyn TryConfigFetcher<T, Error = E> + Send + Sync>>,
+}
+
+impl<T, E> LayeredConfigFetcher<T, E> {
+    /// Initialize with no layers, which fails every load until a layer is added.
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Add a layer. This layer is only consulted if every previously added layer fails to load.
+    pub fn with_layer(
+        mut self,
+        layer: impl TryConfigFetcher<T, Error = E> + Send + Sync + 'static,
+    ) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+}
+
+impl<T, E> Default for LayeredConfigFetcher<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E> TryConfigFetcher<T> for LayeredConfigFetcher<T, E> {
+    type Error = LayeredConfigError<E>;
+
+    fn try_load(&self) -> Result<T, Self::Error> {
+        let mut errors = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            match layer.try_load() {
+                Ok(config) => return Ok(config),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        Err(LayeredConfigError(errors))
+    }
+}
+
+/// Error returned by a [`LayeredConfigFetcher`] when every layer failed, holding each layer's error
+/// in the order the layers were added.
+#[derive(Debug)]
+pub struct LayeredConfigError<E>(pub Vec<E>);
+
+impl<E: Display> Display for LayeredConfigError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "every layer failed")?;
+        for (i, error) in self.0.iter().enumerate() {
+            write!(f, "{}{error}", if i == 0 { ": " } else { "; " })?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for LayeredConfigError<E> {}
+
+/// A [`ConfigFetcher`] for a process-global config that is set once at boot, e.g. from `main` after
+/// loading the config file. Declare it as a `static` and [`init`][GlobalConfigFetcher::init] it
+/// before any reads:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{config_struct, fetcher::GlobalConfigFetcher, ConfigFetcher};
+///
+/// config_struct!(
+///     pub struct AppConfig {
+///         port: u16,
+///     }
+/// );
+///
+/// static CONFIG: GlobalConfigFetcher<AppConfig> = GlobalConfigFetcher::new();
+///
+/// CONFIG.init(Arc::new(AppConfig { port: 8080 })).unwrap();
+/// assert_eq!(8080, CONFIG.latest_snapshot().port);
+/// ```
+///
+/// The snapshot never changes once set. Like the feature control macros, reading before
+/// initialization panics rather than falling back to a default, so a missing `init` is caught at
+/// startup instead of silently running with an unintended config. Use
+/// [`try_latest_snapshot`][GlobalConfigFetcher::try_latest_snapshot] where that isn't desired.
+pub struct GlobalConfigFetcher<T> {
+    config: OnceLock<Arc<T>>,
+}
+
+impl<T> GlobalConfigFetcher<T> {
+    /// Create an uninitialized fetcher, usable in a `static`.
+    pub const fn new() -> Self {
+        Self {
+            config: OnceLock::new(),
+        }
+    }
+
+    /// Set the config. This can only be called once, subsequent calls will be rejected.
+    pub fn init(&self, config: Arc<T>) -> Result<(), GlobalConfigAlreadySetError> {
+        self.config
+            .set(config)
+            .map_err(|_| GlobalConfigAlreadySetError)
+    }
+
+    /// The config, or [`None`] if [`init`][GlobalConfigFetcher::init] hasn't been called yet.
+    pub fn try_latest_snapshot(&self) -> Option<Arc<T>> {
+        self.config.get().cloned()
+    }
+}
+
+impl<T> Default for GlobalConfigFetcher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConfigFetcher<T> for GlobalConfigFetcher<T> {
+    /// # Panics
+    ///
+    /// If [`init`][GlobalConfigFetcher::init] hasn't been called yet.
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.config
+            .get()
+            .expect("GlobalConfigFetcher read before `init` was called")
+            .clone()
+    }
+}
+
+/// Error returned when [`GlobalConfigFetcher::init`] is called more than once.
+#[derive(thiserror::Error, Debug)]
+#[error("The global config has already been set. `init` cannot be called multiple times")]
+pub struct GlobalConfigAlreadySetError;
+
+/// A [`ConfigFetcher`] adapter that memoizes the last snapshot of an inner fetcher for a fixed
+/// time-to-live. Within the TTL, reads return the cached [`Arc`] without calling the inner fetcher.
+///
+/// This is intended to throttle fetchers whose `latest_snapshot` is expensive, e.g. a
+/// [`shared_fetcher_from_fn`] closure that rebuilds the
+/// config on every call:
+///
+/// ```rust
+/// # use std::{sync::Arc, time::Duration};
+/// use conspiracy::config::{fetcher::CachingFetcher, shared_fetcher_from_fn, ConfigFetcher};
+///
+/// let expensive = shared_fetcher_from_fn(|| Arc::new(42u32));
+/// let fetcher = CachingFetcher::new(expensive, Duration::from_secs(5));
+///
+/// assert_eq!(42, *fetcher.latest_snapshot());
+/// ```
+///
+/// Updates from the inner fetcher become visible at most one TTL late.
+pub struct CachingFetcher<T, F: ConfigFetcher<T>> {
+    inner: F,
+    ttl: Duration,
+    cache: RwLock<Option<(Instant, Arc<T>)>>,
+    phantom: PhantomData<T>,
+}
+
+impl<T, F: ConfigFetcher<T>> CachingFetcher<T, F> {
+    /// Wrap `inner`, re-fetching from it at most once per `ttl`.
+    pub fn new(inner: F, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: RwLock::new(None),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, F: ConfigFetcher<T>> ConfigFetcher<T> for CachingFetcher<T, F> {
+    fn latest_snapshot(&self) -> Arc<T> {
+        if let Some((fetched_at, snapshot)) = &*self.cache.read().unwrap() {
+            if fetched_at.elapsed() < self.ttl {
+                return snapshot.clone();
+            }
+        }
+
+        let mut cache = self.cache.write().unwrap();
+        // Another thread may have refreshed while we were waiting on the write lock
+        if let Some((fetched_at, snapshot)) = &*cache {
+            if fetched_at.elapsed() < self.ttl {
+                return snapshot.clone();
+            }
+        }
+
+        let snapshot = self.inner.latest_snapshot();
+        *cache = Some((Instant::now(), snapshot.clone()));
+        snapshot
+    }
+}
+
+/// A [`ConfigFetcher`] adapter that protects an expensive inner fetcher (e.g. one calling a remote
+/// config service) from being called more than once per `min_interval`, no matter how many readers
+/// there are.
+///
+/// ```rust
+/// # use std::{sync::Arc, time::Duration};
+/// use conspiracy::config::{fetcher::ThrottledFetcher, shared_fetcher_from_fn, ConfigFetcher};
+///
+/// let remote = shared_fetcher_from_fn(|| Arc::new(42u32));
+/// let fetcher = ThrottledFetcher::new(remote, Duration::from_secs(1));
+///
+/// assert_eq!(42, *fetcher.latest_snapshot());
+/// ```
+///
+/// Unlike [`CachingFetcher`], readers never wait on the inner fetcher. Once the interval has passed,
+/// the first reader refreshes the snapshot while every concurrent reader is served the previous one,
+/// so a burst of reads against a stale snapshot results in a single refresh. The initial snapshot
+/// is fetched on construction.
+pub struct ThrottledFetcher<T, F: ConfigFetcher<T>> {
+    inner: F,
+    min_interval: Duration,
+    clock: Box<dyn Fn() -> Instant + Send + Sync>,
+    current: Mutex<(Instant, Arc<T>)>,
+    refreshing: AtomicBool,
+}
+
+impl<T, F: ConfigFetcher<T>> ThrottledFetcher<T, F> {
+    /// Wrap `inner`, calling it at most once per `min_interval`.
+    pub fn new(inner: F, min_interval: Duration) -> Self {
+        Self::with_clock(inner, min_interval, Instant::now)
+    }
+
+    /// Like [`new`][ThrottledFetcher::new], but reading the time from `clock`, e.g. so tests can
+    /// advance it rather than sleeping.
+    pub fn with_clock(
+        inner: F,
+        min_interval: Duration,
+        clock: impl Fn() -> Instant + Send + Sync + 'static,
+    ) -> Self {
+        let snapshot = inner.latest_snapshot();
+        Self {
+            inner,
+            min_interval,
+            current: Mutex::new((clock(), snapshot)),
+            clock: Box::new(clock),
+            refreshing: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<T, F: ConfigFetcher<T>> ConfigFetcher<T> for ThrottledFetcher<T, F> {
+    fn latest_snapshot(&self) -> Arc<T> {
+        let stale = {
+            let (fetched_at, snapshot) = &*self.current.lock().unwrap();
+            if (self.clock)().saturating_duration_since(*fetched_at) < self.min_interval {
+                return snapshot.clone();
+            }
+            snapshot.clone()
+        };
+
+        // Only the reader that wins the flag refreshes, the rest make do with the stale snapshot
+        if self
+            .refreshing
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return stale;
+        }
+
+        // Released even if the inner fetcher panics, otherwise no reader would refresh again
+        struct Refreshing<'a>(&'a AtomicBool);
+        impl Drop for Refreshing<'_> {
+            fn drop(&mut self) {
+                self.0.store(false, Ordering::Release);
+            }
+        }
+        let _refreshing = Refreshing(&self.refreshing);
+
+        // Another reader may have refreshed and released the flag since the staleness check above
+        {
+            let (fetched_at, snapshot) = &*self.current.lock().unwrap();
+            if (self.clock)().saturating_duration_since(*fetched_at) < self.min_interval {
+                return snapshot.clone();
+            }
+        }
+
+        let snapshot = self.inner.latest_snapshot();
+        *self.current.lock().unwrap() = ((self.clock)(), snapshot.clone());
+        snapshot
+    }
+}
+
+/// A [`ConfigFetcher`] adapter that derives a new config from each snapshot of an inner fetcher.
+/// This is like [`as_shared_fetcher`][crate::config::as_shared_fetcher], but for arbitrary
+/// derivations rather than sub-configs, e.g. parsing a connection string into its components.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{fetcher::MapFetcher, shared_fetcher_from_static, ConfigFetcher};
+///
+/// let fetcher = MapFetcher::new(
+///     shared_fetcher_from_static(Arc::new("localhost:8080".to_string())),
+///     |addr: &Arc<String>| Arc::new(addr.rsplit_once(':').unwrap().1.parse::<u16>().unwrap()),
+/// );
+///
+/// assert_eq!(8080, *fetcher.latest_snapshot());
+/// ```
+///
+/// The mapping only re-runs when the inner fetcher returns a different snapshot, detected by
+/// pointer identity of the snapshot [`Arc`]. Until then, the previously derived snapshot is shared.
+pub struct MapFetcher<T, U, F: ConfigFetcher<T>, M: Fn(&Arc<T>) -> Arc<U>> {
+    inner: F,
+    map: M,
+    // Weak keeps the allocation (but not the config) alive, so the address can't be reused by a
+    // newer snapshot and mistaken for the old one.
+    cache: RwLock<Option<(Weak<T>, Arc<U>)>>,
+}
+
+impl<T, U, F: ConfigFetcher<T>, M: Fn(&Arc<T>) -> Arc<U>> MapFetcher<T, U, F, M> {
+    /// Wrap `inner`, deriving each snapshot with `map`.
+    pub fn new(inner: F, map: M) -> Self {
+        Self {
+            inner,
+            map,
+            cache: RwLock::new(None),
+        }
+    }
+}
+
+impl<T, U, F: ConfigFetcher<T>, M: Fn(&Arc<T>) -> Arc<U>> ConfigFetcher<U>
+    for MapFetcher<T, U, F, M>
+{
+    fn latest_snapshot(&self) -> Arc<U> {
+        let source = self.inner.latest_snapshot();
+
+        if let Some((derived_from, derived)) = &*self.cache.read().unwrap() {
+            if derived_from.as_ptr() == Arc::as_ptr(&source) {
+                return derived.clone();
+            }
+        }
+
+        let derived = (self.map)(&source);
+        *self.cache.write().unwrap() = Some((Arc::downgrade(&source), derived.clone()));
+        derived
+    }
+}
+
+/// A [`ConfigFetcher`] combinator that pairs the snapshots of two independently sourced configs,
+/// for subsystems that need both together:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{fetcher::ZipFetcher, shared_fetcher_from_static, ConfigFetcher};
+///
+/// let fetcher = ZipFetcher::new(
+///     shared_fetcher_from_static(Arc::new(8080u16)),
+///     shared_fetcher_from_static(Arc::new("localhost".to_string())),
+/// );
+///
+/// let (port, host) = &*fetcher.latest_snapshot();
+/// assert_eq!("localhost:8080", format!("{host}:{port}"));
+/// ```
+///
+/// Each snapshot is a consistent pair in the sense that both halves are held for as long as the
+/// snapshot is, but atomicity is only per call: the two sources are read one after the other, so if
+/// they're updated independently the pair may combine configs that were loaded at slightly different
+/// times. While neither source changes, the same pair [`Arc`] is returned.
+pub struct ZipFetcher<A, B, FA: ConfigFetcher<A>, FB: ConfigFetcher<B>> {
+    first: FA,
+    second: FB,
+    cache: RwLock<Option<Arc<Zipped<A, B>>>>,
+}
+
+/// The snapshot type of a [`ZipFetcher`].
+pub type Zipped<A, B> = (Arc<A>, Arc<B>);
+
+impl<A, B, FA: ConfigFetcher<A>, FB: ConfigFetcher<B>> ZipFetcher<A, B, FA, FB> {
+    /// Pair the snapshots of `first` and `second`.
+    pub fn new(first: FA, second: FB) -> Self {
+        Self {
+            first,
+            second,
+            cache: RwLock::new(None),
+        }
+    }
+}
+
+impl<A, B, FA: ConfigFetcher<A>, FB: ConfigFetcher<B>> ConfigFetcher<Zipped<A, B>>
+    for ZipFetcher<A, B, FA, FB>
+{
+    fn latest_snapshot(&self) -> Arc<Zipped<A, B>> {
+        let first = self.first.latest_snapshot();
+        let second = self.second.latest_snapshot();
+
+        if let Some(pair) = &*self.cache.read().unwrap() {
+            if Arc::ptr_eq(&pair.0, &first) && Arc::ptr_eq(&pair.1, &second) {
+                return pair.clone();
+            }
+        }
+
+        let pair = Arc::new((first, second));
+        *self.cache.write().unwrap() = Some(pair.clone());
+        pair
+    }
+}
+
+/// A [`ConfigFetcher`] pairing an app config with the state of a [`FeatureSet`], so a request
+/// handler that takes one snapshot at its start reads both consistently. Without it, a handler that
+/// reads config and then checks a feature could see config from before an update and feature state
+/// from after it.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{config_struct, fetcher::CombinedFetcher, ConfigFetcher};
+/// use conspiracy::feature_control::define_features;
+///
+/// config_struct!(
+///     pub struct AppConfig {
+///         port: u16,
+///     }
+/// );
+/// define_features!(pub enum Features { UseQuic => true });
+///
+/// let fetcher = CombinedFetcher::<AppConfig, Features>::new(
+///     Arc::new(AppConfig { port: 8080 }),
+///     Arc::new(FeaturesState::default()),
+/// );
+///
+/// // At the start of a request
+/// let (config, features) = &*fetcher.latest_snapshot();
+/// assert_eq!(8080, config.port);
+/// assert!(features.use_quic);
+/// ```
+///
+/// Both halves are published together with [`store`][CombinedFetcher::store], so every snapshot
+/// pairs a config with the feature state it was stored with. Components that only need one half can
+/// be handed [`config_fetcher`][CombinedFetcher::config_fetcher] or
+/// [`feature_state_fetcher`][CombinedFetcher::feature_state_fetcher].
+pub struct CombinedFetcher<C, F: FeatureSet> {
+    current: ArcSwap<Zipped<C, F::State>>,
+}
+
+impl<C, F: FeatureSet> CombinedFetcher<C, F> {
+    /// Initialize with the given config and feature state.
+    pub fn new(config: Arc<C>, feature_state: Arc<F::State>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee((config, feature_state)),
+        }
+    }
+
+    /// Atomically replace both halves, readers see either the previous pair or this one.
+    pub fn store(&self, config: Arc<C>, feature_state: Arc<F::State>) {
+        self.current.store(Arc::new((config, feature_state)));
+    }
+}
+
+impl<C: Send + Sync + 'static, F: FeatureSet + 'static> CombinedFetcher<C, F> {
+    /// A fetcher projecting the config half of each combined snapshot.
+    pub fn config_fetcher(self: &Arc<Self>) -> SharedConfigFetcher<C> {
+        let combined = self.clone();
+        shared_fetcher_from_fn(move || combined.latest_snapshot().0.clone())
+    }
+
+    /// A fetcher projecting the feature state half of each combined snapshot.
+    pub fn feature_state_fetcher(self: &Arc<Self>) -> SharedConfigFetcher<F::State> {
+        let combined = self.clone();
+        shared_fetcher_from_fn(move || combined.latest_snapshot().1.clone())
+    }
+}
+
+impl<C, F: FeatureSet> ConfigFetcher<Zipped<C, F::State>> for CombinedFetcher<C, F> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<Zipped<C, F::State>> {
+        self.current.load_full()
+    }
+}
+
+/// A [`ConfigFetcher`] decorator that watches the snapshots passing through it for changes that
+/// require a restart, according to the config's [`RestartRequired`] implementation (i.e. its
+/// `#[conspiracy(restart)]` fields). When one is observed, `on_restart` is called with the new
+/// snapshot.
+///
+/// ```rust
+/// # use std::sync::{atomic::{AtomicU32, Ordering}, Arc};
+/// use conspiracy::config::{config_struct, fetcher::{RestartAwareFetcher, SwapFetcher}, ConfigFetcher};
+///
+/// config_struct!(
+///     pub struct Config {
+///         #[conspiracy(restart)]
+///         port: u16,
+///     }
+/// );
+///
+/// let source = Arc::new(SwapFetcher::new(Arc::new(Config { port: 80 })));
+/// let restarts = Arc::new(AtomicU32::new(0));
+/// let counter = restarts.clone();
+/// let fetcher = RestartAwareFetcher::new(source.clone(), move |_: Arc<Config>| {
+///     counter.fetch_add(1, Ordering::SeqCst);
+/// });
+///
+/// fetcher.latest_snapshot();
+/// source.store(Arc::new(Config { port: 8080 }));
+/// fetcher.latest_snapshot();
+///
+/// assert_eq!(1, restarts.load(Ordering::SeqCst));
+/// ```
+///
+/// Changes are only observed when a snapshot is read through this fetcher, and the first snapshot
+/// read is the baseline. To avoid restarting repeatedly when the config source flaps, pair this with
+/// a [`RestartCoordinator`][crate::config::restart::RestartCoordinator].
+///
+/// With the `tracing` feature, every new snapshot emits an `info` event recording whether it
+/// requires a restart and the [`restart_reasons`][RestartRequired::restart_reasons].
+pub struct RestartAwareFetcher<T, F: ConfigFetcher<T>, H: Fn(Arc<T>)> {
+    inner: F,
+    on_restart: H,
+    last_snapshot: Mutex<Option<Arc<T>>>,
+}
+
+impl<T, F: ConfigFetcher<T>, H: Fn(Arc<T>)> RestartAwareFetcher<T, F, H> {
+    /// Wrap `inner`, calling `on_restart` whenever a snapshot requires a restart compared to the
+    /// previous snapshot.
+    pub fn new(inner: F, on_restart: H) -> Self {
+        Self {
+            inner,
+            on_restart,
+            last_snapshot: Mutex::new(None),
+        }
+    }
+}
+
+impl<T: RestartRequired, F: ConfigFetcher<T>, H: Fn(Arc<T>)> ConfigFetcher<T>
+    for RestartAwareFetcher<T, F, H>
+{
+    fn latest_snapshot(&self) -> Arc<T> {
+        let snapshot = self.inner.latest_snapshot();
+
+        let mut last_snapshot = self.last_snapshot.lock().unwrap();
+        let restart = match &*last_snapshot {
+            Some(last) if Arc::ptr_eq(last, &snapshot) => return snapshot,
+            Some(last) => {
+                let restart = last.restart_required(&snapshot);
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    config = std::any::type_name::<T>(),
+                    restart_required = restart,
+                    restart_reasons = ?last.restart_reasons(&snapshot),
+                    "Observed new config snapshot"
+                );
+                restart
+            }
+            None => false,
+        };
+        *last_snapshot = Some(snapshot.clone());
+        drop(last_snapshot);
+
+        if restart {
+            (self.on_restart)(snapshot.clone());
+        }
+
+        snapshot
+    }
+}
+
+/// A [`ConfigFetcher`] decorator that counts how often snapshots are requested and how often the
+/// returned snapshot actually changed. Requires the `metrics` feature.
+///
+/// A change is detected by pointer identity of the snapshot [`Arc`], not by value, so a fetcher that
+/// rebuilds an equal config on every call will count every call as a change.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{fetcher::MeteredFetcher, shared_fetcher_from_static, ConfigFetcher};
+///
+/// let fetcher = MeteredFetcher::new(shared_fetcher_from_static(Arc::new(42u32)));
+/// fetcher.latest_snapshot();
+/// fetcher.latest_snapshot();
+///
+/// assert_eq!(2, fetcher.snapshot_count());
+/// assert_eq!(1, fetcher.change_count());
+/// ```
+#[cfg(feature = "metrics")]
+pub struct MeteredFetcher<T, F: ConfigFetcher<T>> {
+    inner: F,
+    snapshots: AtomicU64,
+    changes: AtomicU64,
+    // Weak keeps the allocation (but not the config) alive, so the address can't be reused by a
+    // newer snapshot and mistaken for the old one.
+    last_snapshot: Mutex<Weak<T>>,
+}
+
+#[cfg(feature = "metrics")]
+impl<T, F: ConfigFetcher<T>> MeteredFetcher<T, F> {
+    /// Wrap `inner`, counting every snapshot taken through this fetcher.
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            snapshots: AtomicU64::new(0),
+            changes: AtomicU64::new(0),
+            last_snapshot: Mutex::new(Weak::new()),
+        }
+    }
+
+    /// The number of times [`latest_snapshot`][ConfigFetcher::latest_snapshot] has been called.
+    pub fn snapshot_count(&self) -> u64 {
+        self.snapshots.load(Ordering::Relaxed)
+    }
+
+    /// The number of times [`latest_snapshot`][ConfigFetcher::latest_snapshot] returned a different
+    /// snapshot than the call before it. The first snapshot counts as a change.
+    pub fn change_count(&self) -> u64 {
+        self.changes.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl<T, F: ConfigFetcher<T>> ConfigFetcher<T> for MeteredFetcher<T, F> {
+    fn latest_snapshot(&self) -> Arc<T> {
+        let snapshot = self.inner.latest_snapshot();
+        self.snapshots.fetch_add(1, Ordering::Relaxed);
+
+        let mut last_snapshot = self.last_snapshot.lock().unwrap();
+        if !Weak::ptr_eq(&last_snapshot, &Arc::downgrade(&snapshot)) {
+            self.changes.fetch_add(1, Ordering::Relaxed);
+            *last_snapshot = Arc::downgrade(&snapshot);
+        }
+
+        snapshot
+    }
+}
+
+/// A [`ConfigFetcher`] adapter that numbers the snapshots of an inner fetcher with a generation,
+/// e.g. for caches of state derived from the config that need to know when to invalidate.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::fetcher::{SwapFetcher, VersionedFetcher};
+///
+/// let source = Arc::new(SwapFetcher::new(Arc::new(1u32)));
+/// let fetcher = VersionedFetcher::new(source.clone());
+/// assert_eq!(0, fetcher.latest_versioned().0);
+///
+/// // An equal snapshot isn't a change
+/// source.store(Arc::new(1));
+/// assert_eq!(0, fetcher.latest_versioned().0);
+///
+/// source.store(Arc::new(2));
+/// assert_eq!((1, Arc::new(2)), fetcher.latest_versioned());
+/// ```
+///
+/// The generation starts at `0` for the first snapshot read and increments whenever a snapshot
+/// differs from the previous one by [`PartialEq`]. Like [`RestartAwareFetcher`], changes are only
+/// observed when a snapshot is read through this fetcher.
+pub struct VersionedFetcher<T, F: ConfigFetcher<T>> {
+    inner: F,
+    last_snapshot: Mutex<Option<(u64, Arc<T>)>>,
+}
+
+impl<T, F: ConfigFetcher<T>> VersionedFetcher<T, F> {
+    /// Wrap `inner`, versioning the snapshots taken through this fetcher.
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            last_snapshot: Mutex::new(None),
+        }
+    }
+}
+
+impl<T: PartialEq, F: ConfigFetcher<T>> VersionedFetcher<T, F> {
+    /// Get the latest snapshot along with its generation.
+    pub fn latest_versioned(&self) -> (u64, Arc<T>) {
+        let snapshot = self.inner.latest_snapshot();
+
+        let mut last_snapshot = self.last_snapshot.lock().unwrap();
+        let generation = match &*last_snapshot {
+            Some((generation, last)) if Arc::ptr_eq(last, &snapshot) || **last == *snapshot => {
+                *generation
+            }
+            Some((generation, _)) => generation + 1,
+            None => 0,
+        };
+        // Keep the newest snapshot even when it's equal, so the next comparison can short circuit
+        // on pointer identity
+        *last_snapshot = Some((generation, snapshot.clone()));
+
+        (generation, snapshot)
+    }
+}
+
+impl<T: PartialEq, F: ConfigFetcher<T>> ConfigFetcher<T> for VersionedFetcher<T, F> {
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.latest_versioned().1
+    }
+}
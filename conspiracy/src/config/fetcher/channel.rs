@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use conspiracy_theories::config::ConfigFetcher;
+use tokio::{sync::mpsc::Receiver, task::JoinHandle};
+
+/// A [`ConfigFetcher`] for push based delivery, e.g. a control plane that sends new snapshots over
+/// a channel. A background task drains the channel, swapping in each snapshot as it's received, so
+/// reads are lock-free and never wait on the channel. Requires the `tokio` feature.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{fetcher::ChannelFetcher, ConfigFetcher};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let (sender, receiver) = tokio::sync::mpsc::channel(8);
+/// let fetcher = Arc::new(ChannelFetcher::new(Arc::new(1)));
+/// let listener = fetcher.listen(receiver);
+///
+/// sender.send(Arc::new(2)).await.unwrap();
+///
+/// // Dropping the sender shuts the task down once the pending snapshots are applied
+/// drop(sender);
+/// listener.await.unwrap();
+/// assert_eq!(2, *fetcher.latest_snapshot());
+/// # });
+/// ```
+pub struct ChannelFetcher<T> {
+    current: ArcSwap<T>,
+}
+
+impl<T> ChannelFetcher<T> {
+    /// Create the fetcher with the snapshot to serve until the first update is received.
+    pub fn new(initial: Arc<T>) -> Self {
+        Self {
+            current: ArcSwap::new(initial),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> ChannelFetcher<T> {
+    /// Spawn a task on the current tokio runtime that applies every snapshot received from
+    /// `updates`. The task stops when every sender has been dropped, keeping the last received
+    /// snapshot, or when it receives a snapshot after every other reference to the fetcher has been
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// When called outside of a tokio runtime.
+    pub fn listen(self: &Arc<Self>, mut updates: Receiver<Arc<T>>) -> JoinHandle<()> {
+        let fetcher = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(snapshot) = updates.recv().await {
+                let Some(fetcher) = fetcher.upgrade() else {
+                    return;
+                };
+                fetcher.current.store(snapshot);
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    config = std::any::type_name::<T>(),
+                    "Applied new config snapshot"
+                );
+            }
+        })
+    }
+}
+
+impl<T> ConfigFetcher<T> for ChannelFetcher<T> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::patch::merge_patch;
+
+/// A loader for [`PollingFetcher::new`][super::PollingFetcher::new] that selects config files by
+/// the common per-environment convention. From the base directory, `config.json` is loaded, then
+/// overlaid with `config.<environment>.json`, then with `config.local.json`, so later files take
+/// precedence. Requires the `json` feature.
+///
+/// Only `config.json` is required, the overlays are skipped when they don't exist. Overlays are
+/// applied as JSON merge patches (see [`merge_patch`]), so they only need to contain the fields they
+/// change. When the environment is `local` itself, `config.local.json` is only applied once.
+///
+/// ```rust
+/// use conspiracy::config::{
+///     config_struct,
+///     fetcher::{load_by_convention, PollingFetcher},
+///     full_serde, ConfigFetcher,
+/// };
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub port: u16,
+///         pub verbose: bool,
+///     }
+/// );
+///
+/// let dir = std::env::temp_dir().join("conspiracy_convention_fetcher_doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("config.json"), r#"{ "port": 8080, "verbose": false }"#).unwrap();
+/// std::fs::write(dir.join("config.staging.json"), r#"{ "verbose": true }"#).unwrap();
+///
+/// let fetcher = PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "staging")).unwrap();
+/// assert_eq!(8080, fetcher.latest_snapshot().port);
+/// assert!(fetcher.latest_snapshot().verbose);
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+///
+/// The files are read again on every load, so reloading the [`PollingFetcher`][super::PollingFetcher]
+/// picks up edits, and overlays created after the initial load.
+pub fn load_by_convention<T: DeserializeOwned>(
+    base_dir: impl Into<PathBuf>,
+    environment: impl Into<String>,
+) -> impl Fn() -> Result<T, ConventionConfigError> {
+    let base_dir = base_dir.into();
+    let environment = environment.into();
+    move || load(&base_dir, &environment)
+}
+
+/// The optional overlays read for `environment`, lowest precedence first.
+fn overlays(base_dir: &Path, environment: &str) -> Vec<PathBuf> {
+    let mut overlays = Vec::with_capacity(2);
+    if environment != "local" {
+        overlays.push(base_dir.join(format!("config.{environment}.json")));
+    }
+    overlays.push(base_dir.join("config.local.json"));
+    overlays
+}
+
+fn load<T: DeserializeOwned>(
+    base_dir: &Path,
+    environment: &str,
+) -> Result<T, ConventionConfigError> {
+    let base = base_dir.join("config.json");
+    let mut document = read(&base)?.ok_or(ConventionConfigError::MissingBase(base))?;
+
+    for overlay in overlays(base_dir, environment) {
+        if let Some(patch) = read(&overlay)? {
+            merge_patch(&mut document, &patch);
+        }
+    }
+
+    Ok(serde_json::from_value(document)?)
+}
+
+/// Read and parse `path`, or `None` if it doesn't exist.
+fn read(path: &Path) -> Result<Option<Value>, ConventionConfigError> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(ConventionConfigError::Io {
+                path: path.to_path_buf(),
+                error,
+            })
+        }
+    };
+
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|error| ConventionConfigError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// Error returned by a [`load_by_convention`] loader when the files couldn't be loaded.
+#[derive(thiserror::Error, Debug)]
+pub enum ConventionConfigError {
+    #[error("base config file `{0}` doesn't exist")]
+    MissingBase(PathBuf),
+    #[error("failed to read config file `{path}`: {error}")]
+    Io { path: PathBuf, error: io::Error },
+    #[error("config file `{path}` isn't valid JSON: {error}")]
+    Parse {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    #[error("failed to deserialize the merged config files: {0}")]
+    Deserialize(#[from] serde_json::Error),
+}
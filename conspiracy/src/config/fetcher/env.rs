@@ -0,0 +1,251 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use conspiracy_theories::config::ConfigFetcher;
+use serde::{
+    de::{
+        value::{Error, MapDeserializer},
+        DeserializeOwned, Error as _, IntoDeserializer, Unexpected, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+
+/// A [`ConfigFetcher`] that deserializes the whole config from environment variables, intended for
+/// [twelve-factor](https://12factor.net/config) apps that don't need a config file. Requires the
+/// `env` feature.
+///
+/// Variables are mapped using the `PREFIX_FIELD__NESTED` convention: the prefix and a single `_`
+/// are stripped, `__` separates nested sub-configs, and each segment is lowercased to match the
+/// (snake case) field name. Variables that don't start with the prefix are ignored.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{config_struct, fetcher::EnvConfigFetcher, full_serde, ConfigFetcher};
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub max_connections: u32,
+///         pub web_server: #[full_serde] pub struct WebServerConfig {
+///             pub port: u16,
+///         }
+///     }
+/// );
+///
+/// let fetcher = EnvConfigFetcher::<AppConfig>::from_vars(
+///     "MY_APP",
+///     [
+///         ("MY_APP_MAX_CONNECTIONS".to_string(), "16".to_string()),
+///         ("MY_APP_WEB_SERVER__PORT".to_string(), "8080".to_string()),
+///     ],
+/// )
+/// .unwrap();
+///
+/// assert_eq!(16, fetcher.latest_snapshot().max_connections);
+/// assert_eq!(8080, fetcher.latest_snapshot().web_server.port);
+/// ```
+///
+/// Values are parsed into whatever type the field asks for, so `"8080"` can populate a `u16` or a
+/// `String` alike. The environment is read once on construction, every snapshot after that is the
+/// same [`Arc`].
+///
+/// Sequences (e.g. `Vec` or tuple fields) have no representation in this convention, a variable
+/// for one is rejected with an error naming it rather than guessing at a separator. Leave such
+/// fields out of the environment (e.g. with `#[serde(default)]`), or parse the string with
+/// `#[serde(deserialize_with = "...")]`.
+pub struct EnvConfigFetcher<T> {
+    snapshot: Arc<T>,
+}
+
+impl<T: DeserializeOwned> EnvConfigFetcher<T> {
+    /// Deserialize `T` from the variables of the current process environment that start with
+    /// `prefix`. Variables that aren't valid unicode are skipped.
+    pub fn new(prefix: &str) -> Result<Self, EnvConfigError> {
+        Self::from_vars(
+            prefix,
+            std::env::vars_os().filter_map(|(key, value)| {
+                Some((key.into_string().ok()?, value.into_string().ok()?))
+            }),
+        )
+    }
+
+    /// Deserialize `T` from the given variables rather than the process environment.
+    pub fn from_vars(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, EnvConfigError> {
+        let prefix = format!("{prefix}_");
+        let mut root = EnvNode::Map(BTreeMap::new());
+
+        for (key, value) in vars {
+            if let Some(path) = key.strip_prefix(&prefix) {
+                let path = path.split("__").map(str::to_lowercase).collect::<Vec<_>>();
+                root.insert(&key, &path, value)?;
+            }
+        }
+
+        Ok(Self {
+            snapshot: Arc::new(T::deserialize(root)?),
+        })
+    }
+}
+
+impl<T> ConfigFetcher<T> for EnvConfigFetcher<T> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.snapshot.clone()
+    }
+}
+
+/// Error returned when the environment couldn't be deserialized into the config.
+#[derive(thiserror::Error, Debug)]
+#[error("failed to read config from the environment: {0}")]
+pub struct EnvConfigError(#[from] Error);
+
+/// The environment variables regrouped into a tree, split on `__`.
+enum EnvNode {
+    /// The variable's value, along with its name for errors.
+    Value {
+        key: String,
+        value: String,
+    },
+    Map(BTreeMap<String, EnvNode>),
+}
+
+impl EnvNode {
+    fn insert(&mut self, key: &str, path: &[String], value: String) -> Result<(), Error> {
+        let EnvNode::Map(map) = self else {
+            return Err(Error::custom(format!(
+                "`{key}` is nested beneath a variable that already has a value"
+            )));
+        };
+
+        match path {
+            [] => Err(Error::custom(format!("`{key}` doesn't name a field"))),
+            [field] => match map.insert(
+                field.clone(),
+                EnvNode::Value {
+                    key: key.to_string(),
+                    value,
+                },
+            ) {
+                Some(EnvNode::Map(_)) => Err(Error::custom(format!(
+                    "`{key}` has a value but also has nested variables"
+                ))),
+                _ => Ok(()),
+            },
+            [field, rest @ ..] => map
+                .entry(field.clone())
+                .or_insert_with(|| EnvNode::Map(BTreeMap::new()))
+                .insert(key, rest, value),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for EnvNode {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Environment values are always strings, so parse them into the requested primitive.
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),* $(,)?) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self {
+                EnvNode::Value { value, .. } => match value.parse() {
+                    Ok(parsed) => visitor.$visit(parsed),
+                    Err(_) => Err(Error::invalid_value(Unexpected::Str(&value), &visitor)),
+                },
+                node => node.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for EnvNode {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            EnvNode::Value { value, .. } => visitor.visit_string(value),
+            EnvNode::Map(map) => visitor.visit_map(MapDeserializer::new(map.into_iter())),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            EnvNode::Value { value, .. } => value
+                .into_deserializer()
+                .deserialize_enum(name, variants, visitor),
+            node => node.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        let source = match self {
+            EnvNode::Value { key, .. } => format!("`{key}`"),
+            EnvNode::Map(_) => "nested variables".to_string(),
+        };
+        Err(Error::custom(format!(
+            "{source} can't populate a sequence, which has no representation as environment \
+            variables"
+        )))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Wraps a loader that produces JSON documents, like one given to
+/// [`PollingFetcher::new`][super::PollingFetcher::new], so each document is validated against a
+/// JSON Schema before it's deserialized. A document that's well-formed JSON but violates the schema
+/// then fails the reload, and the [`PollingFetcher`][super::PollingFetcher] keeps serving the last
+/// good config. Requires the `json-schema` feature.
+///
+/// The schema can be written by hand or generated with `#[conspiracy(schema)]`, in which case
+/// `AppConfig::schema().as_value()` is passed in. It's compiled once, up front.
+///
+/// ```rust
+/// use conspiracy::config::{
+///     config_struct,
+///     fetcher::{schema_validated, PollingFetcher, SchemaValidationError},
+///     full_serde, ConfigFetcher,
+/// };
+/// use serde_json::{json, Value};
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub port: u16,
+///     }
+/// );
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": { "port": { "type": "integer", "minimum": 1024 } },
+///     "required": ["port"],
+/// });
+///
+/// let document = std::sync::Mutex::new(json!({ "port": 8080 }));
+/// let load = schema_validated::<AppConfig, _, _>(&schema, || {
+///     Ok::<Value, std::convert::Infallible>(document.lock().unwrap().clone())
+/// })
+/// .unwrap();
+/// let fetcher = PollingFetcher::new(load).unwrap();
+/// assert_eq!(8080, fetcher.latest_snapshot().port);
+///
+/// // A privileged port fits in a u16, but the schema rejects it
+/// *document.lock().unwrap() = json!({ "port": 80 });
+/// assert!(matches!(
+///     fetcher.reload(),
+///     Err(SchemaValidationError::Invalid { .. })
+/// ));
+/// assert_eq!(8080, fetcher.latest_snapshot().port);
+/// ```
+pub fn schema_validated<T, E, S>(
+    schema: &Value,
+    source: S,
+) -> Result<impl Fn() -> Result<T, SchemaValidationError>, SchemaValidationError>
+where
+    T: DeserializeOwned,
+    E: Display,
+    S: Fn() -> Result<Value, E>,
+{
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|error| SchemaValidationError::InvalidSchema(error.to_string()))?;
+
+    Ok(move || {
+        let document =
+            source().map_err(|error| SchemaValidationError::Source(error.to_string()))?;
+
+        validator
+            .validate(&document)
+            .map_err(|error| SchemaValidationError::Invalid {
+                instance_path: error.instance_path().to_string(),
+                schema_path: error.schema_path().to_string(),
+                message: error.to_string(),
+            })?;
+
+        Ok(serde_json::from_value(document)?)
+    })
+}
+
+/// Error returned by a loader wrapped with [`schema_validated`].
+#[derive(thiserror::Error, Debug)]
+pub enum SchemaValidationError {
+    #[error("invalid JSON schema: {0}")]
+    InvalidSchema(String),
+    #[error("failed to fetch config document: {0}")]
+    Source(String),
+    /// The document violates the schema. Both paths are JSON pointers, e.g. `/port` and
+    /// `/properties/port/minimum`.
+    #[error("config document is invalid at `{instance_path}` (schema `{schema_path}`): {message}")]
+    Invalid {
+        instance_path: String,
+        schema_path: String,
+        message: String,
+    },
+    #[error("failed to deserialize validated config document: {0}")]
+    Deserialize(#[from] serde_json::Error),
+}
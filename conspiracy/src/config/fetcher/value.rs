@@ -0,0 +1,70 @@
+use conspiracy_theories::config::ConfigFetcher;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Wraps a [`ConfigFetcher`] to erase its config type, exposing each snapshot as a
+/// [`serde_json::Value`]. Intended for admin / debug endpoints that dump whatever config a service
+/// has without knowing its type. Requires the `json` feature.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{
+///     config_struct, fetcher::{SwapFetcher, ValueFetcher}, full_serde,
+/// };
+/// use serde_json::json;
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub port: u16,
+///     }
+/// );
+///
+/// fn dump(fetchers: &[ValueFetcher]) -> Vec<serde_json::Value> {
+///     fetchers.iter().map(ValueFetcher::as_value).collect()
+/// }
+///
+/// let fetchers = [
+///     ValueFetcher::new(SwapFetcher::new(Arc::new(AppConfig { port: 8080 }))),
+///     ValueFetcher::new(SwapFetcher::new(Arc::new("other".to_string()))),
+/// ];
+///
+/// assert_eq!(vec![json!({ "port": 8080 }), json!("other")], dump(&fetchers));
+/// ```
+///
+/// Every call serializes the inner fetcher's latest snapshot, so it isn't meant for hot paths.
+///
+/// The value is the config's full serialized form, so fields marked `#[conspiracy(secret)]` are
+/// included as-is. Don't expose it where secrets mustn't leak, `to_dotted_string` redacts them.
+pub struct ValueFetcher {
+    to_value: Box<dyn Fn() -> Result<Value, serde_json::Error> + Send + Sync>,
+}
+
+impl ValueFetcher {
+    /// Wrap `fetcher`, serializing its snapshots on demand.
+    pub fn new<T, F>(fetcher: F) -> Self
+    where
+        T: Serialize,
+        F: ConfigFetcher<T> + Send + Sync + 'static,
+    {
+        Self {
+            to_value: Box::new(move || serde_json::to_value(&*fetcher.latest_snapshot())),
+        }
+    }
+
+    /// Serialize the latest snapshot of the wrapped fetcher.
+    ///
+    /// # Panics
+    ///
+    /// When the config can't be represented as JSON, e.g. it contains a map with non-string keys.
+    /// Use [`try_as_value`][ValueFetcher::try_as_value] to handle that case.
+    pub fn as_value(&self) -> Value {
+        self.try_as_value()
+            .expect("Config couldn't be serialized to a JSON value")
+    }
+
+    /// Serialize the latest snapshot of the wrapped fetcher.
+    pub fn try_as_value(&self) -> Result<Value, serde_json::Error> {
+        (self.to_value)()
+    }
+}
@@ -0,0 +1,74 @@
+//! Generic access to a config's leaf fields, e.g. for diffing or auditing configs without writing
+//! per-struct code. Requires the `json` feature.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::macro_targets::GeneratedConfig;
+
+/// Lists a config's leaf fields as `(dotted path, value)` pairs. Implemented for every config
+/// generated by [`config_struct!`][crate::config::config_struct] or
+/// [`ConfigStruct`][crate::config::ConfigStruct] that can be serialized, e.g. using
+/// [`full_serde`][crate::config::full_serde]. Other serializable types don't get it, so `fields()`
+/// doesn't show up on every value in scope.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{config_struct, fields::ConfigFields, full_serde};
+/// use serde_json::json;
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub port: u16,
+///         pub database: #[full_serde] pub struct DatabaseConfig {
+///             pub url: String,
+///         }
+///     }
+/// );
+///
+/// let config = AppConfig {
+///     port: 8080,
+///     database: Arc::new(DatabaseConfig { url: "db:5432".to_string() }),
+/// };
+///
+/// assert_eq!(
+///     vec![
+///         ("database.url".to_string(), json!("db:5432")),
+///         ("port".to_string(), json!(8080)),
+///     ],
+///     config.fields().unwrap(),
+/// );
+/// ```
+///
+/// Fields are visited in the serialized form, so paths use the serialized (i.e. renamed) field
+/// names and are sorted by them. Only objects are recursed into: arrays, and objects without any
+/// fields, are reported as a single value.
+pub trait ConfigFields: Serialize {
+    /// Serialize this config and flatten it into its leaf fields.
+    fn fields(&self) -> Result<Vec<(String, Value)>, serde_json::Error> {
+        let mut fields = Vec::new();
+        flatten(String::new(), serde_json::to_value(self)?, &mut fields);
+        // Objects are only ordered when serde_json's `preserve_order` feature is off
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(fields)
+    }
+}
+
+impl<T: Serialize + GeneratedConfig> ConfigFields for T {}
+
+fn flatten(path: String, value: Value, fields: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let path = if path.is_empty() {
+                    key
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten(path, value, fields);
+            }
+        }
+        leaf => fields.push((path, leaf)),
+    }
+}
@@ -0,0 +1,139 @@
+//! Substituting environment variables into config files before they're deserialized. Requires the
+//! `json` feature.
+
+use serde_json::Value;
+
+/// Replaces `${VAR}` placeholders in every string of `value` with the environment variable `VAR`.
+/// A fallback for when the variable isn't set can be given as `${VAR:-fallback}`, and `$${` is a
+/// literal `${`. Only string values are touched: keys, numbers, and booleans are left as they are.
+///
+/// This is intended to run between parsing and deserializing a config file, e.g. in the loader of a
+/// [`PollingFetcher`][crate::config::fetcher::PollingFetcher]:
+///
+/// ```rust
+/// use conspiracy::config::{config_struct, full_serde, interpolate::interpolate_env};
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub connection_string: String,
+///     }
+/// );
+///
+/// let mut raw = serde_json::json!({
+///     "connection_string": "postgres://${INTERPOLATE_DOC_DB_HOST:-localhost}/app",
+/// });
+/// interpolate_env(&mut raw).unwrap();
+///
+/// // `INTERPOLATE_DOC_DB_HOST` isn't set, so the fallback was used
+/// let config: AppConfig = serde_json::from_value(raw).unwrap();
+/// assert_eq!("postgres://localhost/app", config.connection_string);
+/// ```
+///
+/// A placeholder without a fallback whose variable isn't set is an error, rather than silently
+/// becoming an empty string. Substituted values aren't interpolated again.
+pub fn interpolate_env(value: &mut Value) -> Result<(), InterpolationError> {
+    interpolate_with(value, |var| std::env::var(var).ok())
+}
+
+/// [`interpolate_env`], but with the values of the placeholders supplied by `lookup` instead of the
+/// environment, e.g. so tests don't have to mutate the environment shared by every thread.
+pub fn interpolate_with(
+    value: &mut Value,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), InterpolationError> {
+    interpolate_value(value, &lookup, &mut String::new())
+}
+
+fn interpolate_value(
+    value: &mut Value,
+    lookup: &impl Fn(&str) -> Option<String>,
+    path: &mut String,
+) -> Result<(), InterpolationError> {
+    let len = path.len();
+
+    match value {
+        Value::String(string) => {
+            if string.contains('$') {
+                *string = interpolate_str(string, lookup, path)?;
+            }
+        }
+        Value::Array(values) => {
+            for (index, value) in values.iter_mut().enumerate() {
+                path.push_str(&format!("[{index}]"));
+                interpolate_value(value, lookup, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                interpolate_value(value, lookup, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+
+    Ok(())
+}
+
+fn interpolate_str(
+    input: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    path: &str,
+) -> Result<String, InterpolationError> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            output.push_str("${");
+            rest = escaped;
+        } else if let Some(placeholder) = rest.strip_prefix("${") {
+            let end = placeholder
+                .find('}')
+                .ok_or_else(|| InterpolationError::Unterminated {
+                    path: path.to_string(),
+                })?;
+            let (var, fallback) = match placeholder[..end].split_once(":-") {
+                Some((var, fallback)) => (var, Some(fallback)),
+                None => (&placeholder[..end], None),
+            };
+
+            match (lookup(var), fallback) {
+                (Some(value), _) => output.push_str(&value),
+                (None, Some(fallback)) => output.push_str(fallback),
+                (None, None) => {
+                    return Err(InterpolationError::MissingVar {
+                        var: var.to_string(),
+                        path: path.to_string(),
+                    })
+                }
+            }
+            rest = &placeholder[end + 1..];
+        } else {
+            output.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Error returned by [`interpolate_env`] / [`interpolate_with`]. `path` is the location of the
+/// offending string, e.g. `database.hosts[0]`.
+#[derive(thiserror::Error, Debug)]
+pub enum InterpolationError {
+    #[error("Environment variable `{var}` referenced by `{path}` isn't set and has no fallback")]
+    MissingVar { var: String, path: String },
+    #[error("Unterminated `${{` placeholder in `{path}`")]
+    Unterminated { path: String },
+}
@@ -0,0 +1,88 @@
+//! Applying partial updates to a config, e.g. deltas pushed by a control plane. Requires the `json`
+//! feature.
+
+use std::sync::Arc;
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::config::macro_targets::GeneratedConfig;
+
+/// Derives a new config from an existing one and a JSON merge patch
+/// ([RFC 7386](https://www.rfc-editor.org/rfc/rfc7386)). Implemented for every config generated by
+/// [`config_struct!`][crate::config::config_struct] or [`ConfigStruct`][crate::config::ConfigStruct]
+/// that can be (de)serialized, e.g. using [`full_serde`][crate::config::full_serde]. Other
+/// serializable types don't get it, use [`merge_patch`] on their serialized form instead.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{config_struct, full_serde, patch::ApplyPatch};
+///
+/// config_struct!(
+///     #[full_serde]
+///     pub struct AppConfig {
+///         pub port: u16,
+///         pub database: #[full_serde] pub struct DatabaseConfig {
+///             pub url: String,
+///             pub pool_size: u32,
+///         }
+///     }
+/// );
+///
+/// let config = AppConfig {
+///     port: 8080,
+///     database: Arc::new(DatabaseConfig { url: "db:5432".to_string(), pool_size: 4 }),
+/// };
+///
+/// let patched = config.apply_patch(&serde_json::json!({ "database": { "pool_size": 16 } })).unwrap();
+/// assert_eq!(16, patched.database.pool_size);
+/// assert_eq!("db:5432", patched.database.url);
+/// ```
+///
+/// The patch is applied to the serialized form, so its keys are the serialized (i.e. renamed) field
+/// names, and the result goes through the same validation as any other deserialized config. Per the
+/// RFC, a `null` in the patch removes the key, which then deserializes as a missing field.
+pub trait ApplyPatch: Serialize + DeserializeOwned {
+    /// Apply `patch` to a copy of this config, leaving this config untouched.
+    fn apply_patch(&self, patch: &Value) -> Result<Arc<Self>, PatchError> {
+        let mut value = serde_json::to_value(self).map_err(PatchError::Serialize)?;
+        merge_patch(&mut value, patch);
+        serde_json::from_value(value)
+            .map(Arc::new)
+            .map_err(PatchError::Deserialize)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + GeneratedConfig> ApplyPatch for T {}
+
+/// Applies a JSON merge patch ([RFC 7386](https://www.rfc-editor.org/rfc/rfc7386)) to `target` in
+/// place. Objects are merged recursively, `null` removes a key, and any other value replaces the
+/// target wholesale (including arrays).
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let target = target.as_object_mut().expect("Replaced by an object above");
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Error returned by [`ApplyPatch::apply_patch`].
+#[derive(thiserror::Error, Debug)]
+pub enum PatchError {
+    #[error("Failed to serialize the config to apply the patch to: {0}")]
+    Serialize(serde_json::Error),
+    #[error("The patched config is invalid: {0}")]
+    Deserialize(serde_json::Error),
+}
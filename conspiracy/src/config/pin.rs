@@ -0,0 +1,139 @@
+//! Pinning a config snapshot for the duration of a long-running operation, e.g. a batch job that
+//! must see a single consistent config from start to finish.
+
+use std::{
+    collections::BTreeMap,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use conspiracy_theories::config::ConfigFetcher;
+
+/// Extension methods available on every [`ConfigFetcher`].
+pub trait ConfigFetcherExt<T>: ConfigFetcher<T> {
+    /// Take the latest snapshot and hold it as a [`PinnedSnapshot`]. This is equivalent to keeping
+    /// the [`Arc`] from [`latest_snapshot`][ConfigFetcher::latest_snapshot], but documents that
+    /// later updates are intentionally ignored.
+    fn pin(&self) -> PinnedSnapshot<T> {
+        PinnedSnapshot {
+            snapshot: self.latest_snapshot(),
+            pinned_at: Instant::now(),
+            registration: None,
+        }
+    }
+
+    /// Like [`pin`][ConfigFetcherExt::pin], but the pin is recorded in `tracker` until it's
+    /// dropped.
+    fn pin_tracked(&self, tracker: &PinTracker) -> PinnedSnapshot<T> {
+        let mut pinned = self.pin();
+        pinned.registration = Some(tracker.register(pinned.pinned_at));
+        pinned
+    }
+}
+
+impl<T, F: ConfigFetcher<T> + ?Sized> ConfigFetcherExt<T> for F {}
+
+/// A snapshot deliberately held across config updates, see [`ConfigFetcherExt::pin`]. Derefs to the
+/// config.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{
+///     fetcher::SwapFetcher,
+///     pin::{ConfigFetcherExt, PinTracker},
+/// };
+///
+/// let fetcher = SwapFetcher::new(Arc::new(1));
+/// let tracker = PinTracker::default();
+///
+/// let pinned = fetcher.pin_tracked(&tracker);
+/// fetcher.store(Arc::new(2));
+/// assert_eq!(1, *pinned);
+/// assert_eq!(1, tracker.outstanding());
+///
+/// drop(pinned);
+/// assert_eq!(0, tracker.outstanding());
+/// ```
+pub struct PinnedSnapshot<T> {
+    snapshot: Arc<T>,
+    pinned_at: Instant,
+    registration: Option<PinRegistration>,
+}
+
+impl<T> PinnedSnapshot<T> {
+    /// The pinned snapshot.
+    pub fn snapshot(&self) -> &Arc<T> {
+        &self.snapshot
+    }
+
+    /// How long the snapshot has been pinned.
+    pub fn age(&self) -> Duration {
+        self.pinned_at.elapsed()
+    }
+}
+
+impl<T> Deref for PinnedSnapshot<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.snapshot
+    }
+}
+
+/// Records the outstanding [`PinnedSnapshot`]s created with
+/// [`pin_tracked`][ConfigFetcherExt::pin_tracked], for diagnosing jobs that hold on to stale
+/// config. Clones share the same records.
+#[derive(Clone, Default)]
+pub struct PinTracker {
+    pins: Arc<Mutex<PinRecords>>,
+}
+
+#[derive(Default)]
+struct PinRecords {
+    next_id: u64,
+    // Ids are handed out in order, so the first entry is always the oldest pin
+    pinned_at: BTreeMap<u64, Instant>,
+}
+
+impl PinTracker {
+    /// The number of tracked pins that haven't been dropped yet.
+    pub fn outstanding(&self) -> usize {
+        self.pins.lock().unwrap().pinned_at.len()
+    }
+
+    /// How long the oldest outstanding pin has been held, if there are any.
+    pub fn oldest_pin_age(&self) -> Option<Duration> {
+        self.pins
+            .lock()
+            .unwrap()
+            .pinned_at
+            .first_key_value()
+            .map(|(_, pinned_at)| pinned_at.elapsed())
+    }
+
+    fn register(&self, pinned_at: Instant) -> PinRegistration {
+        let mut pins = self.pins.lock().unwrap();
+        let id = pins.next_id;
+        pins.next_id += 1;
+        pins.pinned_at.insert(id, pinned_at);
+
+        PinRegistration {
+            pins: self.pins.clone(),
+            id,
+        }
+    }
+}
+
+/// Removes a pin from its tracker when the [`PinnedSnapshot`] is dropped.
+struct PinRegistration {
+    pins: Arc<Mutex<PinRecords>>,
+    id: u64,
+}
+
+impl Drop for PinRegistration {
+    fn drop(&mut self) {
+        self.pins.lock().unwrap().pinned_at.remove(&self.id);
+    }
+}
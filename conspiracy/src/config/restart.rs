@@ -0,0 +1,101 @@
+//! Coordinating graceful restarts triggered by config changes.
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Debounces restart notifications, so a config source that flaps (e.g. a file rewritten twice in
+/// quick succession) results in a single restart rather than one per change.
+///
+/// The first [`notify`][RestartCoordinator::notify] opens a window. Further notifications within
+/// the window only replace the pending snapshot. When the window closes, the restart action runs
+/// once with the latest snapshot. The action therefore runs at most once per window, on a
+/// background thread.
+///
+/// ```rust
+/// # use std::{sync::{Arc, Mutex}, time::Duration};
+/// use conspiracy::config::{
+///     config_struct,
+///     fetcher::{RestartAwareFetcher, SwapFetcher},
+///     restart::RestartCoordinator,
+///     ConfigFetcher,
+/// };
+///
+/// config_struct!(
+///     pub struct Config {
+///         #[conspiracy(restart)]
+///         port: u16,
+///     }
+/// );
+///
+/// let restarted_with = Arc::new(Mutex::new(Vec::new()));
+/// let restarts = restarted_with.clone();
+/// let coordinator = RestartCoordinator::new(Duration::from_millis(50), move |config: Arc<Config>| {
+///     restarts.lock().unwrap().push(config.port);
+/// });
+///
+/// let source = Arc::new(SwapFetcher::new(Arc::new(Config { port: 80 })));
+/// let fetcher = RestartAwareFetcher::new(source.clone(), move |config| coordinator.notify(config));
+///
+/// fetcher.latest_snapshot();
+/// for port in [8080, 8081] {
+///     source.store(Arc::new(Config { port }));
+///     fetcher.latest_snapshot();
+/// }
+///
+/// std::thread::sleep(Duration::from_millis(200));
+/// assert_eq!(vec![8081], *restarted_with.lock().unwrap());
+/// ```
+pub struct RestartCoordinator<T> {
+    inner: Arc<CoordinatorInner<T>>,
+}
+
+struct CoordinatorInner<T> {
+    window: Duration,
+    // Some while a window is open
+    pending: Mutex<Option<Arc<T>>>,
+    action: Box<dyn Fn(Arc<T>) + Send + Sync>,
+}
+
+impl<T: Send + Sync + 'static> RestartCoordinator<T> {
+    /// Run `action` at most once per `window`, with the latest snapshot that required a restart.
+    pub fn new(window: Duration, action: impl Fn(Arc<T>) + Send + Sync + 'static) -> Self {
+        Self {
+            inner: Arc::new(CoordinatorInner {
+                window,
+                pending: Mutex::new(None),
+                action: Box::new(action),
+            }),
+        }
+    }
+
+    /// Signal that `snapshot` requires a restart. The restart action runs once the current window
+    /// closes, opening a new window if none is open.
+    pub fn notify(&self, snapshot: Arc<T>) {
+        let mut pending = self.inner.pending.lock().unwrap();
+        let window_open = pending.replace(snapshot).is_some();
+        drop(pending);
+
+        if !window_open {
+            let inner = self.inner.clone();
+            thread::spawn(move || {
+                thread::sleep(inner.window);
+                // Take before running the action, so notifications during the restart open a new window
+                let snapshot = inner.pending.lock().unwrap().take();
+                if let Some(snapshot) = snapshot {
+                    (inner.action)(snapshot);
+                }
+            });
+        }
+    }
+}
+
+impl<T> Clone for RestartCoordinator<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Helpers for testing code that consumes config through a [`ConfigFetcher`]. Requires the
+//! `test-util` feature, which is intended to be enabled only for `dev-dependencies`.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use conspiracy_theories::config::{AsField, ConfigFetcher};
+
+/// A [`ConfigFetcher`] that plays back a scripted sequence of snapshots, for tests that need the
+/// config to change between reads.
+///
+/// Each snapshot is returned for `reads_per_step` reads (one by default) before moving on to the
+/// next. Once the script runs out, the last snapshot is returned indefinitely.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{testing::StepFetcher, ConfigFetcher};
+///
+/// let fetcher = StepFetcher::new([1u32, 2, 3].map(Arc::new));
+///
+/// assert_eq!(1, *fetcher.latest_snapshot());
+/// assert_eq!(2, *fetcher.latest_snapshot());
+/// assert_eq!(3, *fetcher.latest_snapshot());
+/// assert_eq!(3, *fetcher.latest_snapshot());
+/// assert_eq!(4, fetcher.reads());
+/// ```
+pub struct StepFetcher<T> {
+    steps: Vec<Arc<T>>,
+    reads_per_step: usize,
+    reads: AtomicUsize,
+}
+
+impl<T> StepFetcher<T> {
+    /// Script the snapshots to return, in order.
+    ///
+    /// # Panics
+    ///
+    /// If `steps` is empty.
+    pub fn new(steps: impl IntoIterator<Item = Arc<T>>) -> Self {
+        let steps: Vec<_> = steps.into_iter().collect();
+        assert!(!steps.is_empty(), "StepFetcher requires at least one step");
+
+        Self {
+            steps,
+            reads_per_step: 1,
+            reads: AtomicUsize::new(0),
+        }
+    }
+
+    /// Hold each snapshot for `reads_per_step` reads, e.g. so that a parent fetcher and a sub-config
+    /// fetcher projected from it observe the same step.
+    ///
+    /// # Panics
+    ///
+    /// If `reads_per_step` is zero.
+    pub fn with_reads_per_step(mut self, reads_per_step: usize) -> Self {
+        assert!(reads_per_step > 0, "reads_per_step must be at least one");
+        self.reads_per_step = reads_per_step;
+        self
+    }
+
+    /// The number of times [`latest_snapshot`][ConfigFetcher::latest_snapshot] has been called.
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> ConfigFetcher<T> for StepFetcher<T> {
+    fn latest_snapshot(&self) -> Arc<T> {
+        let read = self.reads.fetch_add(1, Ordering::SeqCst);
+        let step = (read / self.reads_per_step).min(self.steps.len() - 1);
+        self.steps[step].clone()
+    }
+}
+
+/// A [`ConfigFetcher`] decorator that records every snapshot returned by the inner fetcher, e.g. to
+/// capture the sequence of configs behind a flaky failure and reproduce it with a
+/// [`ReplayFetcher`]. Repeated reads of the same snapshot (i.e. the same [`Arc`]) are recorded
+/// once, so a hot read path doesn't grow the recording while the config is unchanged.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{
+///     fetcher::SwapFetcher,
+///     testing::{RecordingFetcher, ReplayFetcher},
+///     ConfigFetcher,
+/// };
+///
+/// let fetcher = RecordingFetcher::new(SwapFetcher::new(Arc::new(1u32)));
+/// fetcher.latest_snapshot();
+/// fetcher.inner().store(Arc::new(2));
+/// fetcher.latest_snapshot();
+///
+/// let replay = ReplayFetcher::new(fetcher.recording());
+/// assert_eq!(1, *replay.latest_snapshot());
+/// assert_eq!(2, *replay.latest_snapshot());
+/// assert!(replay.finished());
+/// ```
+///
+/// With the `json` feature, the recording can be saved with
+/// [`recording_to_json`][RecordingFetcher::recording_to_json] and loaded in a test with
+/// [`ReplayFetcher::from_json`].
+pub struct RecordingFetcher<T, F: ConfigFetcher<T>> {
+    inner: F,
+    recording: Mutex<Vec<Arc<T>>>,
+}
+
+impl<T, F: ConfigFetcher<T>> RecordingFetcher<T, F> {
+    /// Record the snapshots returned by `inner`.
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            recording: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The wrapped fetcher.
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+
+    /// Every snapshot returned so far, in the order they were returned, without consecutive
+    /// repeats.
+    pub fn recording(&self) -> Vec<Arc<T>> {
+        self.recording.lock().unwrap().clone()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: serde::Serialize, F: ConfigFetcher<T>> RecordingFetcher<T, F> {
+    /// The [`recording`][RecordingFetcher::recording] as a JSON array of snapshots.
+    pub fn recording_to_json(&self) -> Result<String, serde_json::Error> {
+        let recording = self.recording.lock().unwrap();
+        serde_json::to_string_pretty(
+            &recording
+                .iter()
+                .map(|snapshot| &**snapshot)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl<T, F: ConfigFetcher<T>> ConfigFetcher<T> for RecordingFetcher<T, F> {
+    fn latest_snapshot(&self) -> Arc<T> {
+        let snapshot = self.inner.latest_snapshot();
+        let mut recording = self.recording.lock().unwrap();
+        if !recording
+            .last()
+            .is_some_and(|last| Arc::ptr_eq(last, &snapshot))
+        {
+            recording.push(snapshot.clone());
+        }
+        snapshot
+    }
+}
+
+/// A [`ConfigFetcher`] that replays a sequence captured by a [`RecordingFetcher`], one snapshot per
+/// read. Once the recording runs out, the last snapshot is returned indefinitely; check
+/// [`finished`][ReplayFetcher::finished] to assert the code under test read as many snapshots as
+/// were recorded.
+pub struct ReplayFetcher<T> {
+    steps: StepFetcher<T>,
+}
+
+impl<T> ReplayFetcher<T> {
+    /// Replay `recording`, in order.
+    ///
+    /// # Panics
+    ///
+    /// If `recording` is empty.
+    pub fn new(recording: impl IntoIterator<Item = Arc<T>>) -> Self {
+        Self {
+            steps: StepFetcher::new(recording),
+        }
+    }
+
+    /// If every recorded snapshot has been returned.
+    pub fn finished(&self) -> bool {
+        self.steps.reads() >= self.steps.steps.len()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: serde::de::DeserializeOwned> ReplayFetcher<T> {
+    /// Replay a recording saved with [`RecordingFetcher::recording_to_json`].
+    ///
+    /// # Panics
+    ///
+    /// If the recording is empty.
+    pub fn from_json(recording: &str) -> Result<Self, serde_json::Error> {
+        let recording: Vec<T> = serde_json::from_str(recording)?;
+        Ok(Self::new(recording.into_iter().map(Arc::new)))
+    }
+}
+
+impl<T> ConfigFetcher<T> for ReplayFetcher<T> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        self.steps.latest_snapshot()
+    }
+}
+
+/// Asserts that `child` observes the same sub-config as the corresponding field of the snapshot
+/// observed by `parent`. Each fetcher is read exactly once, parent first.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::{
+///     as_shared_fetcher, config_struct, shared_fetcher_from_static, testing::assert_fetchers_agree,
+///     SharedConfigFetcher,
+/// };
+///
+/// config_struct!(
+///     pub struct AppConfig {
+///         database: pub struct DatabaseConfig {
+///             name: String,
+///         }
+///     }
+/// );
+///
+/// let fetcher = shared_fetcher_from_static(Arc::new(AppConfig {
+///     database: Arc::new(DatabaseConfig { name: "primary".to_string() }),
+/// }));
+/// let database: SharedConfigFetcher<DatabaseConfig> = as_shared_fetcher(&fetcher);
+///
+/// assert_fetchers_agree(&fetcher, &database);
+/// ```
+///
+/// # Panics
+///
+/// If the snapshots aren't equal.
+#[track_caller]
+pub fn assert_fetchers_agree<T, T2, P, C>(parent: &P, child: &C)
+where
+    T: AsField<T2>,
+    T2: PartialEq,
+    P: ConfigFetcher<T> + ?Sized,
+    C: ConfigFetcher<T2> + ?Sized,
+{
+    let expected = parent.latest_snapshot().share();
+    let actual = child.latest_snapshot();
+
+    assert!(
+        expected == actual,
+        "child fetcher snapshot doesn't match the projection of the parent fetcher snapshot"
+    );
+}
+
+/// The snapshot type used by [`stress_fetcher`]. Every snapshot holds several copies of its
+/// generation, so a snapshot observed while only partially written (a torn read) is detectable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressSnapshot {
+    generation: u64,
+    copies: [u64; 16],
+}
+
+impl StressSnapshot {
+    /// A consistent snapshot for `generation`.
+    pub fn new(generation: u64) -> Self {
+        Self {
+            generation,
+            copies: [generation; 16],
+        }
+    }
+
+    /// The generation the snapshot was created for.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn is_consistent(&self) -> bool {
+        self.copies.iter().all(|copy| *copy == self.generation)
+    }
+}
+
+/// The number of operations performed by [`stress_fetcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StressReport {
+    /// The total number of [`latest_snapshot`][ConfigFetcher::latest_snapshot] calls across all
+    /// reader threads, each of which checked the snapshot it read.
+    pub reads: u64,
+    /// The total number of snapshots stored across all writer threads.
+    pub writes: u64,
+}
+
+/// Stress tests a [`ConfigFetcher`] implementation: `readers` threads call
+/// [`latest_snapshot`][ConfigFetcher::latest_snapshot] in a loop while `writers` threads push new
+/// snapshots with `store`, all for `duration`. [`ConfigFetcher`] has no write operation, so `store`
+/// is how the fetcher under test is updated.
+///
+/// ```rust
+/// # use std::{sync::Arc, time::Duration};
+/// use conspiracy::config::{
+///     fetcher::SwapFetcher,
+///     testing::{stress_fetcher, StressSnapshot},
+/// };
+///
+/// let fetcher = SwapFetcher::new(Arc::new(StressSnapshot::new(0)));
+/// let report = stress_fetcher(&fetcher, 4, 2, Duration::from_millis(10), SwapFetcher::store);
+/// assert!(report.reads > 0);
+/// ```
+///
+/// Every thread stops at the deadline on its own, so a slow fetcher lengthens the test rather than
+/// hanging it (as long as its calls return).
+///
+/// # Panics
+///
+/// If any snapshot read is torn, i.e. not exactly as it was stored, if a snapshot from a generation
+/// that was never stored is read, or if the fetcher or `store` panics on any thread.
+pub fn stress_fetcher<F, S>(
+    fetcher: &F,
+    readers: usize,
+    writers: usize,
+    duration: Duration,
+    store: S,
+) -> StressReport
+where
+    F: ConfigFetcher<StressSnapshot> + Sync + ?Sized,
+    S: Fn(&F, Arc<StressSnapshot>) + Sync,
+{
+    let deadline = Instant::now() + duration;
+    // Generations are handed out before they're stored, so any generation read must be below this
+    let next_generation = AtomicU64::new(fetcher.latest_snapshot().generation() + 1);
+    let reads = AtomicU64::new(0);
+    let writes = AtomicU64::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..readers {
+            scope.spawn(|| {
+                while Instant::now() < deadline {
+                    let snapshot = fetcher.latest_snapshot();
+                    assert!(
+                        snapshot.is_consistent(),
+                        "torn read of generation {}",
+                        snapshot.generation()
+                    );
+                    assert!(
+                        snapshot.generation() < next_generation.load(Ordering::SeqCst),
+                        "read generation {} before it was stored",
+                        snapshot.generation()
+                    );
+                    reads.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+
+        for _ in 0..writers {
+            scope.spawn(|| {
+                while Instant::now() < deadline {
+                    let generation = next_generation.fetch_add(1, Ordering::SeqCst);
+                    store(fetcher, Arc::new(StressSnapshot::new(generation)));
+                    writes.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    StressReport {
+        reads: reads.into_inner(),
+        writes: writes.into_inner(),
+    }
+}
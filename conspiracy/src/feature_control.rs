@@ -6,15 +6,15 @@
 //! benefits:
 //!
 //! - Strong static typing of features and all associated benefits. The compiler checks for errors,
-//!    your IDE can give type + doc annotations, etc.
+//!   your IDE can give type + doc annotations, etc.
 //! - Feature control ergonomics with static functions (more on this below).
 //! - Reuses the config features offered by this crate enabling you to define performant, safe,
-//!     dynamic determination of state at runtime without having to introduce a second set of
-//!     semantics for these portions. The full feature set of the [`config`][crate::config] module
-//!     is available including the ability to mix and match / customize implementations.
+//!   dynamic determination of state at runtime without having to introduce a second set of
+//!   semantics for these portions. The full feature set of the [`config`][crate::config] module
+//!   is available including the ability to mix and match / customize implementations.
 //! - Abstracts out the implementation of the global tracker state and asserting the tracker state
-//!     generically. This means the same interface can be backed by hard-coded values, dynamic
-//!     configuration, or any other custom implementation.
+//!   generically. This means the same interface can be backed by hard-coded values, dynamic
+//!   configuration, or any other custom implementation.
 //!
 //! # Defaults and Unit Testing
 //!
@@ -24,7 +24,8 @@
 //! defaults that could be silently picked up, which isn't desirable in workloads at scale. The
 //! macros allow having a different behavior under test, use the default, vs in the non-test build.
 //! You can also still explicitly opt into a default being used in the non-test build by opting to
-//! use [`feature_enabled_or_default`].
+//! use [`feature_enabled_or_default`], or for a whole program (e.g. a one-shot CLI) by installing
+//! the defaults with [`use_defaults_as_global_tracker`].
 //!
 //! # Usage
 //!
@@ -135,13 +136,60 @@
 //! At the end of the day ask yourself "should this be static?" and let that guide the decision.
 
 use std::{
-    any::Any,
+    any::{Any, TypeId},
+    collections::HashMap,
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, LazyLock, OnceLock, RwLock,
     },
 };
 
+/// Combine the feature sets of several [`define_features!`] invocations into one, so each team can
+/// own its own set (and feature file) while a single global tracker serves all of them. Each part
+/// is a variant wrapping its feature enum:
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use conspiracy::feature_control::{combine_features, feature_enabled, tracker::ConspiracyFeatureTracker};
+///
+/// mod payments {
+///     conspiracy::feature_control::define_features!(pub enum Features { NewCheckout => false });
+/// }
+///
+/// mod search {
+///     conspiracy::feature_control::define_features!(pub enum Features { FuzzyMatching => true });
+/// }
+///
+/// combine_features!(
+///     pub enum AllFeatures {
+///         Payments(payments::Features),
+///         Search(search::Features),
+///     }
+/// );
+///
+/// let state = AllFeaturesState {
+///     payments: Arc::new(payments::Features::builder().new_checkout(true).build().unwrap()),
+///     ..Default::default()
+/// };
+/// ConspiracyFeatureTracker::<AllFeatures, _>::from_static(state)
+///     .set_as_global_tracker()
+///     .unwrap();
+///
+/// // Each part is checked as usual
+/// assert!(feature_enabled!(payments::Features::NewCheckout));
+/// assert!(feature_enabled!(search::Features::FuzzyMatching));
+/// ```
+///
+/// For an enum `AllFeatures`, this generates the [`FeatureSet`] `AllFeatures`, the state
+/// `AllFeaturesState` with a `snake_case` field per part, and `AllFeaturesOverrides` likewise. The
+/// state serializes with each part nested under its field name, e.g. `{ "payments": { .. } }`. A
+/// restart required by any part is a restart of the whole, reported by the part's field name.
+///
+/// Feature checks of a part are resolved through [`FeatureTracker::static_feature_state_part`],
+/// which the included trackers implement. [`FeatureSet::name`] is the name within the part, so
+/// feature names should be unique across parts where names are used as keys, e.g. with
+/// [`tracker::EnvFeatureTracker`].
+pub use conspiracy_macros::combine_features;
 /// Define the features of your application as a quasi-enum of feature name + default value pairs.
 /// This will generate a corresponding enum and other associated types that enable you to use
 /// statically typed features and check their current state from static assertions.
@@ -159,6 +207,86 @@ use std::{
 /// );
 /// ```
 ///
+/// # Const Defaults
+///
+/// Defaults may be any constant expression evaluating to [`bool`], not just a literal. They are
+/// exposed as `const fn`s on the generated state struct, so they can also be used in const contexts:
+///
+/// ```rust
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         UseQuic => cfg!(target_os = "linux"),
+///         VerboseLogging => cfg!(debug_assertions) && !cfg!(miri),
+///     }
+/// );
+///
+/// const QUIC_BY_DEFAULT: bool = FeaturesState::default_use_quic();
+/// ```
+///
+/// Since the expression becomes the body of a `const fn`, it may call other `const fn`s, and an
+/// expression that isn't a [`bool`] is a compile error.
+///
+/// # Metadata
+///
+/// Doc comments on variants are kept on the generated enum and are also available at runtime as
+/// the feature's `description()`, alongside an optional `owner()`. This is intended for surfacing
+/// features in tooling such as an admin dashboard:
+///
+/// ```rust
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         /// Serve HTTP/3 traffic over QUIC.
+///         #[conspiracy(owner = "networking")]
+///         UseQuic => false,
+///     }
+/// );
+///
+/// assert_eq!("Serve HTTP/3 traffic over QUIC.", Features::UseQuic.description());
+/// assert_eq!(Some("networking"), Features::UseQuic.owner());
+/// ```
+///
+/// Every feature is listed, in declaration order, by `Features::ALL` and `Features::iter()`, e.g. to
+/// render the features along with their `name()`:
+///
+/// ```rust
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         UseQuic => false,
+///         VerboseLogging => false,
+///     }
+/// );
+///
+/// let names = Features::iter().map(|feature| feature.name()).collect::<Vec<_>>();
+/// assert_eq!(vec!["use_quic", "verbose_logging"], names);
+/// ```
+///
+/// The enum derives `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash`, so
+/// features can be sorted (also in declaration order) or used as map keys. Any of these already
+/// derived on the enum by the caller are left to the caller's derive.
+///
+/// # Required Features
+///
+/// Every feature has a default, so the state builder never lacks a value. To force a feature to be
+/// chosen explicitly instead of silently falling back to its default, mark it
+/// `#[conspiracy(required)]`. The builder's `build()` then fails with a
+/// [`MissingRequiredFeaturesError`] naming every required feature that wasn't set:
+///
+/// ```rust
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         #[conspiracy(required)]
+///         UseQuic => false,
+///         VerboseLogging => false,
+///     }
+/// );
+///
+/// assert!(FeaturesState::builder().verbose_logging(true).build().is_err());
+/// assert!(FeaturesState::builder().use_quic(false).build().is_ok());
+/// ```
+///
+/// The default is still used by `FeaturesState::default()` and in place of a missing tracker under
+/// `#[cfg(test)]`.
+///
 /// # Automatic Restarts
 ///
 /// If your [`FeatureTracker`] is backed by a [`ConfigFetcher`][crate::config::ConfigFetcher](which
@@ -178,6 +306,87 @@ use std::{
 /// );
 /// ```
 ///
+/// Related features can be grouped with `#[conspiracy(group = "...")]`. Naming a group with the
+/// enum level `#[conspiracy(restart_group = "...")]` marks every feature in it as requiring a
+/// restart, e.g. when any networking change needs one:
+///
+/// ```rust
+/// use conspiracy_macros::define_features;
+/// define_features!(
+///     #[conspiracy(restart_group = "networking")]
+///     pub enum Features {
+///         #[conspiracy(group = "networking")]
+///         UseQuic => false,
+///         #[conspiracy(group = "networking")]
+///         UseHttp2 => true,
+///         VerboseLogging => false,
+///     }
+/// );
+/// ```
+///
+/// # Persisted State
+///
+/// With the `serde` feature (enabled by default), the generated state and overrides structs
+/// implement `Serialize` and `Deserialize`, so they can be persisted and reloaded. The round-trip
+/// is exact, and a feature missing from the persisted state (e.g. one added after the state was
+/// written) loads with its default:
+///
+/// ```rust
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         Foo => false,
+///         Bar => true,
+///     }
+/// );
+///
+/// let state = FeaturesState::builder().foo(true).build().unwrap();
+/// let json = serde_json::to_string(&state).unwrap();
+/// assert_eq!(state, serde_json::from_str(&json).unwrap());
+///
+/// let persisted_before_bar: FeaturesState = serde_json::from_str(r#"{ "foo": true }"#).unwrap();
+/// assert_eq!(state, persisted_before_bar);
+/// ```
+///
+/// The reverse also holds: a feature in the persisted state that has since been removed is ignored,
+/// so old state still loads after a feature is cleaned up. With the `tracing` feature, the ignored
+/// keys are reported in a `warn` event.
+///
+/// ```rust
+/// # conspiracy_macros::define_features!(pub enum Features { Foo => false, Bar => true });
+/// let persisted_with_removed: FeaturesState =
+///     serde_json::from_str(r#"{ "foo": true, "bar": true, "removed": true }"#).unwrap();
+/// assert_eq!(FeaturesState::builder().foo(true).build().unwrap(), persisted_with_removed);
+/// ```
+///
+/// # Merging Partial State
+///
+/// `FeaturesOverrides` holds a value for only some of the features. It can be deserialized from a
+/// partial document, or built from `(name, value)` pairs (e.g. from an ops tool) where unknown names
+/// are either ignored or rejected. Merging it onto a state only changes the features it sets:
+///
+/// ```rust
+/// use conspiracy::feature_control::UnknownFeatures;
+///
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         Foo => false,
+///         Bar => true,
+///     }
+/// );
+///
+/// let overrides = FeaturesOverrides::from_map([("foo", true)], UnknownFeatures::Error).unwrap();
+/// let mut state = FeaturesState::default();
+/// state.merge(&overrides);
+///
+/// assert_eq!(FeaturesState::builder().foo(true).bar(true).build().unwrap(), state);
+/// ```
+///
+/// For a command line flag or environment variable, `FeaturesState::from_overrides_str` parses a
+/// comma separated `name=bool` list (e.g. `foo=true,bar=false`) onto the defaults, rejecting
+/// unknown names and values other than `true` / `false` with an [`OverridesParseError`]. When the
+/// values are already keyed by the enum, `FeaturesState::from_map` takes a
+/// `HashMap<Features, bool>` and fills in the defaults for any feature missing from it.
+///
 /// # Best Practices
 ///
 /// Other than the enum itself, don't attempt to work with the generated types directly. The other
@@ -199,12 +408,35 @@ use std::{
 /// use generated::Features;
 /// ```
 ///
+/// # Generated Names
+///
+/// For an enum `Features`, the macro generates `FeaturesState`, `FeaturesStateBuilder`, and
+/// `FeaturesOverrides`. Each variant becomes a `snake_case` field of the state struct and a method
+/// of the builder. Variants that convert to the same name within one invocation are rejected at
+/// compile time:
+///
+/// ```compile_fail
+/// conspiracy_macros::define_features!(
+///     pub enum Features {
+///         FooBar => false,
+///         Foo_Bar => true,
+///     }
+/// );
+/// ```
+///
+/// Collisions across invocations (e.g. two `Features` enums, or an enum named `FeaturesState`
+/// next to `Features`) can't be detected by the macro. They surface as duplicate definitions,
+/// which is another reason to give each invocation its own module.
+///
 /// # See Also
 ///
 /// - [`feature_enabled!`]
+/// - [`feature_enabled_all!`]
+/// - [`feature_enabled_any!`]
 /// - [`feature_enabled_or!`]
 /// - [`feature_enabled_or_default!`]
 /// - [`try_feature_enabled!`]
+/// - [`combine_features!`]
 pub use conspiracy_macros::define_features;
 /// Assert the state of a feature that has been defined by [`define_features!`] from the registered
 /// global tracker. If no global tracker was registered, a panic is raised.
@@ -237,7 +469,65 @@ pub use conspiracy_macros::define_features;
 /// will prevent errors from being checked in or deployed) than having a backup behavior suddenly
 /// applied. If you're not in such a situation, you can use [`feature_enabled_or_default!`] which
 /// self documents that this behavior can occur in your code.
+///
+/// ```rust,should_panic
+/// conspiracy::feature_control::define_features!(pub enum Features { Foo => false });
+///
+/// // No global tracker set up, so this panics
+/// conspiracy::feature_control::feature_enabled!(Features::Foo);
+/// ```
+///
+/// # Opting Out of the Panic per Call Site
+///
+/// Individual call sites can opt into the default value with `panic = false`. For that call only,
+/// this behaves exactly like [`feature_enabled_or_default!`] in every build:
+///
+/// ```rust
+/// conspiracy::feature_control::define_features!(pub enum Features { Foo => true });
+///
+/// // No global tracker set up, so yields the default of `true`
+/// assert!(conspiracy::feature_control::feature_enabled!(Features::Foo, panic = false));
+/// ```
 pub use conspiracy_macros::feature_enabled;
+/// Check that every one of several features (from the same [`define_features!`] enum) is enabled.
+/// The feature state is fetched from the global tracker once and shared by all the checks, rather
+/// than once per feature as with `feature_enabled!(A) && feature_enabled!(B)`.
+///
+/// Behaves like [`feature_enabled!`] when no global tracker was registered: it panics, except
+/// under `#[cfg(test)]` where the defaults are used instead.
+///
+/// ```rust
+/// use conspiracy::feature_control::{define_features, feature_enabled_all};
+/// use conspiracy::feature_control::tracker::{ConspiracyFeatureTracker, StaticFetcher};
+///
+/// define_features!(pub enum Features { Foo => true, Bar => true, Baz => false });
+///
+/// ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+///     .set_as_global_tracker()
+///     .unwrap();
+///
+/// assert!(feature_enabled_all!(Features::Foo, Features::Bar));
+/// assert!(!feature_enabled_all!(Features::Foo, Features::Baz));
+/// ```
+pub use conspiracy_macros::feature_enabled_all;
+/// Check that at least one of several features (from the same [`define_features!`] enum) is
+/// enabled. This is the [`feature_enabled_all!`] counterpart of `||`, with the same single fetch of
+/// the feature state and the same behavior when no global tracker was registered.
+///
+/// ```rust
+/// use conspiracy::feature_control::{define_features, feature_enabled_any};
+/// use conspiracy::feature_control::tracker::{ConspiracyFeatureTracker, StaticFetcher};
+///
+/// define_features!(pub enum Features { Foo => true, Bar => false, Baz => false });
+///
+/// ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+///     .set_as_global_tracker()
+///     .unwrap();
+///
+/// assert!(feature_enabled_any!(Features::Foo, Features::Bar));
+/// assert!(!feature_enabled_any!(Features::Bar, Features::Baz));
+/// ```
+pub use conspiracy_macros::feature_enabled_any;
 /// Assert the state of a feature that has been defined by [`define_features!`] from the registered
 /// global tracker. If no global tracker was registered, provided value is returned.
 ///
@@ -283,7 +573,10 @@ pub use conspiracy_macros::feature_enabled_or_default;
 /// try_feature_enabled!(Features::Foo);
 /// ```
 pub use conspiracy_macros::try_feature_enabled;
-pub use conspiracy_theories::feature::{AsFeature, FeatureSet, FeatureTracker};
+pub use conspiracy_theories::feature::{
+    AsFeature, ExplainFeature, FeatureExplanation, FeatureOverrides, FeatureSet, FeatureSource,
+    FeatureTracker,
+};
 
 pub mod tracker;
 
@@ -292,38 +585,205 @@ pub mod tracker;
 static GLOBAL_TRACKER_INIT: AtomicUsize = AtomicUsize::new(UNINITIALIZED);
 static mut GLOBAL_TRACKER: &'static dyn FeatureTracker = &NO_TRACKER;
 static NO_TRACKER: tracker::NoTracker = tracker::NoTracker;
+// Only used to diagnose a mismatched state type, so it's set after (and separately from) the tracker
+static GLOBAL_TRACKER_TYPE: OnceLock<&'static str> = OnceLock::new();
+// Trackers of further feature sets, registered once `GLOBAL_TRACKER` is set, keyed by their state
+// type. They're only consulted when `GLOBAL_TRACKER` doesn't serve the requested state, so the first
+// tracker keeps its lock-free reads.
+static ADDITIONAL_TRACKERS: LazyLock<RwLock<HashMap<TypeId, AdditionalTracker>>> =
+    LazyLock::new(Default::default);
+
+struct AdditionalTracker {
+    tracker: &'static (dyn FeatureTracker + Send + Sync),
+    // The tracker's type name, to diagnose a mismatched state type
+    name: &'static str,
+}
 
 const UNINITIALIZED: usize = 0;
 const INITIALIZING: usize = 1;
 const INITIALIZED: usize = 2;
 
 /// Registers a [`FeatureTracker`] as the global tracker used to statically assert feature state.
-/// This can only be called once, subsequent calls will be rejected.
-pub fn set_global_tracker<T: 'static, C: FeatureTracker + 'static>(
+/// With the `tracing` feature, a successful install emits an `info` event.
+///
+/// Each feature set `T` can have one global tracker, so independent [`define_features!`] enums can
+/// each register their own. Registering a second tracker for a feature set that's already served
+/// is rejected, including by a [`combine_features!`] tracker that has `T` as one of its parts.
+///
+/// ```rust
+/// use conspiracy::feature_control::{define_features, feature_enabled, tracker::ConspiracyFeatureTracker};
+/// use conspiracy::feature_control::tracker::StaticFetcher;
+///
+/// mod payments {
+///     conspiracy::feature_control::define_features!(pub enum Features { NewCheckout => true });
+/// }
+///
+/// mod search {
+///     conspiracy::feature_control::define_features!(pub enum Features { FuzzyMatching => false });
+/// }
+///
+/// ConspiracyFeatureTracker::<payments::Features, StaticFetcher<_>>::from_default()
+///     .set_as_global_tracker()
+///     .unwrap();
+/// ConspiracyFeatureTracker::<search::Features, StaticFetcher<_>>::from_default()
+///     .set_as_global_tracker()
+///     .unwrap();
+///
+/// assert!(feature_enabled!(payments::Features::NewCheckout));
+/// assert!(!feature_enabled!(search::Features::FuzzyMatching));
+/// ```
+///
+/// The first tracker registered is read without any locking, the others are looked up by type. When
+/// feature checks are hot, prefer a single [`combine_features!`] tracker.
+///
+/// The tracker is shared by every thread that checks features, so it must be `Send + Sync`.
+pub fn set_global_tracker<T: 'static, C: FeatureTracker + Send + Sync + 'static>(
     tracker: C,
 ) -> Result<(), SetGlobalTrackerError> {
-    let tracker = Box::new(tracker);
+    let tracker = Box::into_raw(Box::new(tracker));
 
     unsafe {
         // SAFETY: No data-race, this is indirectly locked via the atomic GLOBAL_TRACKER_INIT
-        // SAFETY: No memory issue, this is leaked onto heap satisfying 'static. Calling this
-        // function multiple times isn't allowed, so this will never be "truly" leaked.
-        set_global_tracker_from_ref(Box::into_raw(tracker))?;
+        // SAFETY: No memory issue, this is leaked onto heap satisfying 'static. A tracker is only
+        // installed once per feature set, so this will never be "truly" leaked.
+        if set_global_tracker_from_ref(tracker).is_err() {
+            // SAFETY: The pointer wasn't installed, so it's still uniquely owned here
+            return set_additional_tracker::<T, C>(Box::from_raw(tracker));
+        }
+        let _ = GLOBAL_TRACKER_TYPE.set(std::any::type_name::<C>());
 
         // Try validating the type. We expect a single type behind the opaque value. Checking here means
         // we're far more likely to catch at startup, which in turn makes it viable for the unwrap based
         // feature checks to be used safely.
         #[allow(static_mut_refs)] // Never mutated without guard via GLOBAL_TRACKER_INIT
         if GLOBAL_TRACKER.static_feature_state().is::<T>() {
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                state = std::any::type_name::<T>(),
+                tracker = std::any::type_name::<C>(),
+                "Installed global feature tracker"
+            );
             Ok(())
         } else {
-            Err(SetGlobalTrackerError::BadCast(BadCastError(
-                std::any::type_name::<T>().to_string(),
-            )))
+            Err(SetGlobalTrackerError::BadCast(BadCastError {
+                expected: std::any::type_name::<T>(),
+                trackers: vec![std::any::type_name::<C>()],
+            }))
         }
     }
 }
 
+/// Registers a tracker for a further feature set once the first global tracker is set. Unlike the
+/// first tracker, a mismatched state type is rejected without installing the tracker.
+fn set_additional_tracker<T: 'static, C: FeatureTracker + Send + Sync + 'static>(
+    tracker: Box<C>,
+) -> Result<(), SetGlobalTrackerError> {
+    // The first tracker may still be being installed by another thread
+    while GLOBAL_TRACKER_INIT.load(Ordering::SeqCst) != INITIALIZED {
+        std::hint::spin_loop();
+    }
+
+    // Every state the incoming tracker would serve, including the parts of a combined feature set
+    let mut incoming = tracker.static_feature_state_parts();
+    incoming.push(TypeId::of::<T>());
+
+    // SAFETY: GLOBAL_TRACKER is INITIALIZED, so it's never mutated again
+    #[allow(static_mut_refs)]
+    let served_by_first = unsafe {
+        let state: &dyn Any = &*GLOBAL_TRACKER.static_feature_state();
+        serves_any(GLOBAL_TRACKER, state.type_id(), &incoming)
+    };
+
+    let mut trackers = ADDITIONAL_TRACKERS.write().unwrap();
+    let served_by_additional = trackers
+        .iter()
+        .any(|(state, additional)| serves_any(additional.tracker, *state, &incoming));
+    if served_by_first || served_by_additional {
+        return Err(SetGlobalTrackerError::GlobalTrackerAlreadySet);
+    }
+
+    if !tracker.static_feature_state().is::<T>() {
+        return Err(SetGlobalTrackerError::BadCast(BadCastError {
+            expected: std::any::type_name::<T>(),
+            trackers: vec![std::any::type_name::<C>()],
+        }));
+    }
+
+    trackers.insert(
+        TypeId::of::<T>(),
+        AdditionalTracker {
+            tracker: Box::leak(tracker),
+            name: std::any::type_name::<C>(),
+        },
+    );
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        state = std::any::type_name::<T>(),
+        tracker = std::any::type_name::<C>(),
+        "Installed global feature tracker"
+    );
+    Ok(())
+}
+
+/// Whether a registered `tracker`, whose state type is `state`, already serves any of `states`,
+/// either as its state or as one of its parts.
+fn serves_any(tracker: &dyn FeatureTracker, state: TypeId, states: &[TypeId]) -> bool {
+    states.contains(&state)
+        || tracker
+            .static_feature_state_parts()
+            .iter()
+            .any(|part| states.contains(part))
+}
+
+/// Registers a tracker serving the declared defaults of `T` as the global tracker, e.g. for one-shot
+/// CLIs that have no feature state to load. Unlike the fallback under `#[cfg(test)]`, this applies
+/// to every build, so plain [`feature_enabled!`] can be used without a per call site opt-in:
+///
+/// ```rust
+/// use conspiracy::feature_control::{define_features, feature_enabled, use_defaults_as_global_tracker};
+///
+/// define_features!(pub enum Features { Foo => true, Bar => false });
+///
+/// use_defaults_as_global_tracker::<Features>().unwrap();
+///
+/// assert!(feature_enabled!(Features::Foo));
+/// assert!(!feature_enabled!(Features::Bar));
+/// ```
+///
+/// Shorthand for [`ConspiracyFeatureTracker::from_default`][tracker::ConspiracyFeatureTracker::from_default]
+/// followed by [`set_as_global_tracker`][tracker::ConspiracyFeatureTracker::set_as_global_tracker],
+/// so it's rejected in the same way if `T` already has a global tracker.
+pub fn use_defaults_as_global_tracker<T: FeatureSet>() -> Result<(), SetGlobalTrackerError> {
+    tracker::ConspiracyFeatureTracker::<T, tracker::StaticFetcher<T>>::from_default()
+        .set_as_global_tracker()
+}
+
+/// Retrieve the complete state of the feature set `T` from the global tracker, e.g. for building a
+/// dashboard of every feature's state. Prefer the `feature_` prefixed macros for checking
+/// individual features.
+///
+/// ```rust
+/// use conspiracy::feature_control::{current_feature_state, define_features};
+/// use conspiracy::feature_control::tracker::{ConspiracyFeatureTracker, StaticFetcher};
+///
+/// define_features!(pub enum Features { Foo => true, Bar => false });
+///
+/// ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+///     .set_as_global_tracker()
+///     .unwrap();
+///
+/// let state = current_feature_state::<Features>().unwrap();
+/// assert!(state.foo);
+/// assert!(!state.bar);
+/// ```
+///
+/// Unlike the macros, this never falls back to the defaults, under `#[cfg(test)]` or otherwise.
+/// An error is returned if no global tracker was set or it tracks a different feature set.
+pub fn current_feature_state<T: FeatureSet>() -> Result<Arc<T::State>, FeatureEnabledError> {
+    macro_targets::try_feature_state::<T::State>()
+}
+
 /// Implementation details of [`set_global_tracker`]. The caller **MUST** pass a valid pointer with
 /// a `'static` lifetime.
 ///
@@ -384,7 +844,52 @@ pub mod macro_targets {
     /// mutable reference as an optimization. Interacting with that state safely requires using a
     /// separate static atomic properly.
     pub unsafe fn feature_state_unchecked<T: Any + Send + Sync>() -> Arc<T> {
-        feature_state_inner().expect("Bad cast")
+        feature_state_inner().unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Reports the keys of features that were present in deserialized state but are no longer
+    /// defined, i.e. they were removed since the state was persisted. With the `tracing` feature,
+    /// this emits a `warn` event, otherwise the keys are silently ignored.
+    #[allow(unused_variables)]
+    pub fn ignore_unknown_features<'a>(state: &'static str, keys: impl Iterator<Item = &'a str>) {
+        #[cfg(feature = "tracing")]
+        {
+            let keys = keys.collect::<Vec<_>>();
+            if !keys.is_empty() {
+                tracing::warn!(
+                    state,
+                    unknown_features = ?keys,
+                    "Ignoring unknown features in persisted feature state"
+                );
+            }
+        }
+    }
+
+    /// Splits a `name=bool` list such as `use_quic=true,verbose_logging=false` into its pairs.
+    /// Whitespace around names and values is ignored, as are empty entries.
+    pub fn parse_overrides(
+        overrides: &str,
+    ) -> Result<Vec<(&str, bool)>, crate::feature_control::OverridesParseError> {
+        use crate::feature_control::OverridesParseError;
+
+        overrides
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (name, value) = entry
+                    .split_once('=')
+                    .ok_or_else(|| OverridesParseError::Malformed(entry.to_string()))?;
+                let (name, value) = (name.trim(), value.trim());
+                let value = value
+                    .parse()
+                    .map_err(|_| OverridesParseError::InvalidValue {
+                        feature: name.to_string(),
+                        value: value.to_string(),
+                    })?;
+                Ok((name, value))
+            })
+            .collect()
     }
 
     /// Uses the global tracker previously set by [`set_global_tracker`][crate::feature_control::set_global_tracker]
@@ -401,9 +906,35 @@ pub mod macro_targets {
 unsafe fn feature_state_inner<T: Any + Send + Sync>() -> Result<Arc<T>, FeatureEnabledError> {
     #[allow(static_mut_refs)] // Never mutated without guard via GLOBAL_TRACKER_INIT
     let state = GLOBAL_TRACKER.static_feature_state();
-    Ok(state
-        .downcast::<T>()
-        .map_err(|_| BadCastError(std::any::type_name::<T>().to_string()))?)
+    match state.downcast::<T>() {
+        Ok(state) => Ok(state),
+        // The tracker may serve a combined feature set, of which `T` is one part
+        #[allow(static_mut_refs)]
+        Err(_) => GLOBAL_TRACKER
+            .static_feature_state_part(TypeId::of::<T>())
+            .and_then(|part| part.downcast::<T>().ok())
+            .or_else(additional_feature_state::<T>)
+            .ok_or_else(|| BadCastError::new::<T>().into()),
+    }
+}
+
+/// Looks up `T` among the trackers registered after the first, either directly or as a part of a
+/// combined feature set.
+fn additional_feature_state<T: Any + Send + Sync>() -> Option<Arc<T>> {
+    let trackers = ADDITIONAL_TRACKERS.read().unwrap();
+    let state = TypeId::of::<T>();
+
+    match trackers.get(&state) {
+        Some(additional) => additional
+            .tracker
+            .static_feature_state()
+            .downcast::<T>()
+            .ok(),
+        None => trackers
+            .values()
+            .find_map(|additional| additional.tracker.static_feature_state_part(state))
+            .and_then(|part| part.downcast::<T>().ok()),
+    }
 }
 
 /// Checks if [`set_global_tracker`] has already been called to determine if singleton should be
@@ -415,18 +946,51 @@ fn global_tracker_set() -> bool {
 /// Error returned when the type tracked by the global tracker doesn't match the type used asserting
 /// the state of a feature (i.e. when the [`FeatureSet`] types are mismatched).
 #[derive(thiserror::Error, Debug)]
-#[error("Expected global state type `{0}`. This should be unreachable! The tracker's state type shouldn't vary"
+#[error(
+    "Expected global feature state of type `{expected}`, but {} a different type. Check that the \
+    tracker was created for the same feature set",
+    describe_trackers(trackers)
 )]
-pub struct BadCastError(String);
+pub struct BadCastError {
+    expected: &'static str,
+    trackers: Vec<&'static str>,
+}
+
+impl BadCastError {
+    /// The error for a lookup of `T` that none of the registered trackers serve, naming all of them.
+    fn new<T>() -> Self {
+        let mut trackers = vec![GLOBAL_TRACKER_TYPE.get().copied().unwrap_or("<unknown>")];
+        trackers.extend(
+            ADDITIONAL_TRACKERS
+                .read()
+                .unwrap()
+                .values()
+                .map(|additional| additional.name),
+        );
+
+        Self {
+            expected: std::any::type_name::<T>(),
+            trackers,
+        }
+    }
+}
+
+fn describe_trackers(trackers: &[&str]) -> String {
+    match trackers {
+        [tracker] => format!("the global tracker `{tracker}` provides"),
+        trackers => format!("the global trackers `{}` provide", trackers.join("`, `")),
+    }
+}
 
 /// Error returned when setting the global tracker fails.
 #[derive(thiserror::Error, Debug)]
 pub enum SetGlobalTrackerError {
     #[error(
-        "A global tracker has already been set. `set_global_tracker` cannot be called multiple times"
+        "A global tracker has already been set for this feature set. `set_global_tracker` cannot \
+        be called multiple times for the same feature set"
     )]
     GlobalTrackerAlreadySet,
-    #[error("{0:?}")]
+    #[error("{0}")]
     BadCast(#[from] BadCastError),
 }
 
@@ -435,6 +999,39 @@ pub enum SetGlobalTrackerError {
 pub enum FeatureEnabledError {
     #[error("No global tracker was set. `set_global_tracker` must be called first")]
     NoGlobalTracker,
-    #[error("{0:?}")]
+    #[error("{0}")]
     BadCast(#[from] BadCastError),
 }
+
+/// Error returned when building a feature state without explicitly setting every feature marked
+/// `#[conspiracy(required)]`. Holds the names of the missing features.
+#[derive(thiserror::Error, Debug)]
+#[error("Required features were not set: {}", .0.join(", "))]
+pub struct MissingRequiredFeaturesError(pub Vec<&'static str>);
+
+/// How names that don't match any feature are handled when building overrides from a map, see the
+/// generated `from_map` of a [`FeatureSet::Overrides`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownFeatures {
+    /// Skip unknown names, e.g. when the source may still mention features that have been removed.
+    Ignore,
+    /// Fail with an [`UnknownFeatureError`].
+    Error,
+}
+
+/// Error returned when a feature name doesn't match any feature of the [`FeatureSet`].
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown feature `{0}`")]
+pub struct UnknownFeatureError(pub String);
+
+/// Error returned when parsing feature overrides from a `name=bool` list, see the generated
+/// `from_overrides_str` of a [`FeatureSet::State`].
+#[derive(thiserror::Error, Debug)]
+pub enum OverridesParseError {
+    #[error("Expected `name=true` or `name=false`, found `{0}`")]
+    Malformed(String),
+    #[error("Feature `{feature}` must be `true` or `false`, found `{value}`")]
+    InvalidValue { feature: String, value: String },
+    #[error("{0}")]
+    UnknownFeature(#[from] UnknownFeatureError),
+}
@@ -1,11 +1,23 @@
 //! Included [`FeatureTracker`] implementations.
 
-use std::{any::Any, marker::PhantomData, sync::Arc};
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+use std::path::{Path, PathBuf};
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    collections::HashMap,
+    marker::PhantomData,
+    sync::Arc,
+};
 
 use conspiracy_theories::config::ConfigFetcher;
 
-use crate::feature_control::{
-    set_global_tracker, FeatureSet, FeatureTracker, SetGlobalTrackerError,
+use crate::{
+    config::SharedConfigFetcher,
+    feature_control::{
+        set_global_tracker, AsFeature, ExplainFeature, FeatureExplanation, FeatureOverrides,
+        FeatureSet, FeatureSource, FeatureTracker, SetGlobalTrackerError,
+    },
 };
 
 /// A general purpose [`FeatureTracker`] with support for:
@@ -44,7 +56,7 @@ impl<T: FeatureSet> ConspiracyFeatureTracker<T, StaticFetcher<T>> {
     ///
     /// let state = Features::builder()
     ///     .foo(true)
-    ///     .build();
+    ///     .build().unwrap();
     ///
     /// let result = ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_static(state)
     ///     .set_as_global_tracker();
@@ -59,7 +71,90 @@ impl<T: FeatureSet> ConspiracyFeatureTracker<T, StaticFetcher<T>> {
     }
 }
 
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+impl<T: FeatureSet> ConspiracyFeatureTracker<T, StaticFetcher<T>>
+where
+    T::State: serde::de::DeserializeOwned,
+{
+    /// Read the state once from a file, e.g. one deployed alongside the application. The format is
+    /// chosen by the file's extension, each requiring its feature to be enabled:
+    ///
+    /// | Extension | Feature |
+    /// |--|--|
+    /// | `.json` | `json` |
+    /// | `.toml` | `toml` |
+    /// | `.yaml` / `.yml` | `yaml` |
+    ///
+    /// Features missing from the file keep their defaults.
+    ///
+    /// Requires at least one of the formats' features.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "json")] {
+    /// use conspiracy::feature_control::{define_features, feature_enabled, tracker::ConspiracyFeatureTracker};
+    ///
+    /// define_features!(pub enum Features { Foo => false });
+    ///
+    /// let path = std::env::temp_dir().join("feature_state_doc.json");
+    /// std::fs::write(&path, r#"{ "foo": true }"#).unwrap();
+    ///
+    /// ConspiracyFeatureTracker::<Features, _>::from_file(&path)
+    ///     .unwrap()
+    ///     .set_as_global_tracker()
+    ///     .unwrap();
+    /// assert!(feature_enabled!(Features::Foo));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// # }
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FeatureFileError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let state = match extension {
+            #[cfg(feature = "json")]
+            Some("json") => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            #[cfg(feature = "toml")]
+            Some("toml") => toml::from_str(&std::fs::read_to_string(path)?)?,
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => serde_yaml::from_str(&std::fs::read_to_string(path)?)?,
+            _ => return Err(FeatureFileError::UnsupportedFormat(path.to_path_buf())),
+        };
+
+        Ok(Self::from_static(state))
+    }
+}
+
 impl<T: FeatureSet, F: ConfigFetcher<T::State> + 'static> ConspiracyFeatureTracker<T, F> {
+    /// Track the state supplied by an arbitrary fetcher. Every feature check reads the latest
+    /// snapshot, so a fetcher that can be updated makes the features dynamic even though the global
+    /// tracker itself can only be set once. For example, with a [`SwapFetcher`] that ops can
+    /// [`store`][SwapFetcher::store] new state to:
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// use conspiracy::config::fetcher::SwapFetcher;
+    /// use conspiracy::feature_control::{define_features, feature_enabled, tracker::ConspiracyFeatureTracker};
+    ///
+    /// define_features!(pub enum Features { Foo => false });
+    ///
+    /// let state = Arc::new(SwapFetcher::new(Arc::new(FeaturesState::default())));
+    /// ConspiracyFeatureTracker::<Features, _>::new(state.clone())
+    ///     .set_as_global_tracker()
+    ///     .unwrap();
+    /// assert!(!feature_enabled!(Features::Foo));
+    ///
+    /// state.store(Arc::new(Features::builder().foo(true).build().unwrap()));
+    /// assert!(feature_enabled!(Features::Foo));
+    /// ```
+    ///
+    /// [`SwapFetcher`]: crate::config::fetcher::SwapFetcher
+    /// [SwapFetcher::store]: crate::config::fetcher::SwapFetcher::store
+    pub fn new(state_fetcher: F) -> Self {
+        Self {
+            state_fetcher,
+            phantom: PhantomData,
+        }
+    }
+
     /// Convenience function for applying the tracker as the global default rather than having to
     /// specify the generics matching generated types:
     ///
@@ -73,17 +168,619 @@ impl<T: FeatureSet, F: ConfigFetcher<T::State> + 'static> ConspiracyFeatureTrack
     ///     ConspiracyFeatureTracker::from_static(FeaturesState::default()),
     /// );
     /// ```
-    pub fn set_as_global_tracker(self) -> Result<(), SetGlobalTrackerError> {
+    pub fn set_as_global_tracker(self) -> Result<(), SetGlobalTrackerError>
+    where
+        F: Send + Sync,
+    {
         set_global_tracker::<T::State, Self>(self)
     }
 }
 
+#[cfg(feature = "tokio")]
+impl<T, F> ConspiracyFeatureTracker<T, F>
+where
+    T: FeatureSet,
+    T::State: PartialEq,
+    F: ConfigFetcher<T::State> + Clone + Send + Sync + 'static,
+{
+    /// Subscribe to changes of the feature state, for components that react to a feature flipping
+    /// rather than checking it on each use. Requires the `tokio` feature.
+    ///
+    /// The stream yields the current state immediately, then each state that differs from the last
+    /// one yielded. The state fetcher is polled every `poll_interval` by a task spawned on the
+    /// current tokio runtime, which stops at the first poll after the stream is dropped. Since the
+    /// task holds its own clone of the fetcher, the stream keeps working after the tracker is set
+    /// as the global tracker:
+    ///
+    /// ```rust
+    /// # use std::{sync::Arc, time::Duration};
+    /// use conspiracy::config::fetcher::SwapFetcher;
+    /// use conspiracy::feature_control::{define_features, tracker::ConspiracyFeatureTracker};
+    /// use tokio_stream::StreamExt;
+    ///
+    /// define_features!(pub enum Features { Foo => false });
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let state = Arc::new(SwapFetcher::new(Arc::new(FeaturesState::default())));
+    /// let tracker = ConspiracyFeatureTracker::<Features, _>::new(state.clone());
+    /// let mut changes = tracker.feature_changes(Duration::from_millis(10));
+    /// assert!(!changes.next().await.unwrap().foo);
+    ///
+    /// state.store(Arc::new(Features::builder().foo(true).build().unwrap()));
+    /// assert!(changes.next().await.unwrap().foo);
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// When called outside of a tokio runtime.
+    pub fn feature_changes(
+        &self,
+        poll_interval: std::time::Duration,
+    ) -> impl tokio_stream::Stream<Item = Arc<T::State>> + Send + 'static {
+        let fetcher = self.state_fetcher.clone();
+        let (sender, receiver) = tokio::sync::watch::channel(fetcher.latest_snapshot());
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                interval.tick().await;
+                if sender.is_closed() {
+                    return;
+                }
+
+                let latest = fetcher.latest_snapshot();
+                sender.send_if_modified(|current| {
+                    let modified = **current != *latest;
+                    if modified {
+                        *current = latest;
+                    }
+                    modified
+                });
+            }
+        });
+
+        tokio_stream::wrappers::WatchStream::new(receiver)
+    }
+}
+
 impl<T: FeatureSet, F: ConfigFetcher<T::State> + 'static> FeatureTracker
     for ConspiracyFeatureTracker<T, F>
 {
     fn static_feature_state(&self) -> Arc<dyn Any + Send + Sync> {
         self.state_fetcher.latest_snapshot()
     }
+
+    fn static_feature_state_part(&self, part: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        T::state_part(&self.state_fetcher.latest_snapshot(), part)
+    }
+
+    fn static_feature_state_parts(&self) -> Vec<TypeId> {
+        T::state_parts()
+    }
+}
+
+impl<T: FeatureSet, F: ConfigFetcher<T::State> + 'static> ExplainFeature<T>
+    for ConspiracyFeatureTracker<T, F>
+{
+}
+
+/// A [`FeatureTracker`] that layers [`FeatureSet::Overrides`] on top of a base state. For each
+/// feature, the value comes from the first layer that has an opinion on it (i.e. the feature is set
+/// in that layer's overrides), falling back to the base state when no layer does.
+///
+/// Each layer is a [`ConfigFetcher`], so an override source such as an ops kill-switch file can be
+/// updated at runtime without replacing the tracker:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::shared_fetcher_from_static;
+/// use conspiracy::feature_control::tracker::{LayeredFeatureTracker, StaticFetcher};
+///
+/// conspiracy::feature_control::define_features!(pub enum Features { Foo => false, Bar => false });
+///
+/// let kill_switch = FeaturesOverrides::default().foo(true);
+///
+/// let result = LayeredFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+///     .with_layer(shared_fetcher_from_static(Arc::new(kill_switch)))
+///     .set_as_global_tracker();
+/// ```
+///
+/// [`ExplainFeature::explain`] reports which layer (if any) decided the value of a feature.
+pub struct LayeredFeatureTracker<T: FeatureSet, F: ConfigFetcher<T::State>> {
+    layers: Vec<SharedConfigFetcher<T::Overrides>>,
+    base: F,
+}
+
+impl<T: FeatureSet> LayeredFeatureTracker<T, StaticFetcher<T>> {
+    /// Initialize with no layers, using the default value of `T` as the base.
+    pub fn from_default() -> Self {
+        Self::from_static(T::State::default())
+    }
+
+    /// Initialize with no layers, using a static base state.
+    pub fn from_static(state: T::State) -> Self {
+        Self::new(StaticFetcher {
+            state: Arc::new(state),
+        })
+    }
+}
+
+impl<T: FeatureSet, F: ConfigFetcher<T::State>> LayeredFeatureTracker<T, F> {
+    /// Initialize with no layers, using the given fetcher for the base state.
+    pub fn new(base: F) -> Self {
+        Self {
+            layers: Vec::new(),
+            base,
+        }
+    }
+
+    /// Add a layer of overrides. Layers are consulted in the order they were added, so this layer
+    /// has lower precedence than any previously added layer, but higher than the base state.
+    pub fn with_layer(mut self, layer: SharedConfigFetcher<T::Overrides>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+}
+
+impl<T: FeatureSet, F: ConfigFetcher<T::State> + Send + Sync + 'static> LayeredFeatureTracker<T, F>
+where
+    T::State: Clone,
+{
+    /// Convenience function for applying the tracker as the global default rather than having to
+    /// specify the generics matching generated types. See [`ConspiracyFeatureTracker::set_as_global_tracker`].
+    pub fn set_as_global_tracker(self) -> Result<(), SetGlobalTrackerError> {
+        set_global_tracker::<T::State, Self>(self)
+    }
+}
+
+impl<T: FeatureSet, F: ConfigFetcher<T::State> + 'static> FeatureTracker
+    for LayeredFeatureTracker<T, F>
+where
+    T::State: Clone,
+{
+    fn static_feature_state(&self) -> Arc<dyn Any + Send + Sync> {
+        let mut state = (*self.base.latest_snapshot()).clone();
+
+        // Apply in reverse so the highest precedence layer is applied last
+        for layer in self.layers.iter().rev() {
+            layer.latest_snapshot().apply(&mut state);
+        }
+
+        Arc::new(state)
+    }
+
+    fn static_feature_state_part(&self, part: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        state_part::<T>(self.static_feature_state(), part)
+    }
+
+    fn static_feature_state_parts(&self) -> Vec<TypeId> {
+        T::state_parts()
+    }
+}
+
+impl<T: FeatureSet, F: ConfigFetcher<T::State> + 'static> ExplainFeature<T>
+    for LayeredFeatureTracker<T, F>
+where
+    T::State: Clone,
+{
+    /// Identifies the highest precedence layer with an opinion on `feature` as its source, or
+    /// [`FeatureSource::Base`] if no layer has one.
+    fn explain(&self, feature: T) -> FeatureExplanation {
+        for (index, layer) in self.layers.iter().enumerate() {
+            if let Some(enabled) = layer.latest_snapshot().get(&feature) {
+                return FeatureExplanation {
+                    enabled,
+                    source: FeatureSource::Layer(index),
+                };
+            }
+        }
+
+        FeatureExplanation {
+            enabled: self.base.latest_snapshot().as_feature(feature),
+            source: FeatureSource::Base,
+        }
+    }
+}
+
+/// A [`FeatureTracker`] that reads features from environment variables, for quick local
+/// experimentation without a config file. Each feature is read from `PREFIX_NAME`, the uppercase
+/// form of its [`name`][FeatureSet::name], and parsed as `true` or `false`. Features whose variable
+/// isn't set keep their default.
+///
+/// ```rust
+/// use conspiracy::feature_control::{define_features, feature_enabled, tracker::EnvFeatureTracker};
+///
+/// define_features!(pub enum Features { UseQuic => false });
+///
+/// // Run with `MY_APP_FEATURE_USE_QUIC=true` to enable the feature
+/// EnvFeatureTracker::<Features>::new("MY_APP_FEATURE")
+///     .unwrap()
+///     .set_as_global_tracker()
+///     .unwrap();
+///
+/// let use_quic = feature_enabled!(Features::UseQuic);
+/// ```
+///
+/// [`new`][EnvFeatureTracker::new] reads the environment once, rejecting invalid values up front.
+/// [`live`][EnvFeatureTracker::live] reads it on every feature check instead, so changes made by the
+/// process after the tracker is installed apply. Since a check can't fail, an invalid value is
+/// treated as if the variable wasn't set.
+///
+/// Both have a `_with_lookup` counterpart that reads variables through a function instead of the
+/// process environment, e.g. so tests don't have to mutate the environment shared by every thread:
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// use conspiracy::feature_control::{current_feature_state, define_features, tracker::EnvFeatureTracker};
+///
+/// define_features!(pub enum Features { UseQuic => false });
+///
+/// let env = HashMap::from([("MY_APP_FEATURE_USE_QUIC", "true")]);
+/// EnvFeatureTracker::<Features>::new_with_lookup("MY_APP_FEATURE", move |var| {
+///     env.get(var).map(|value| value.to_string())
+/// })
+/// .unwrap()
+/// .set_as_global_tracker()
+/// .unwrap();
+///
+/// assert!(current_feature_state::<Features>().unwrap().use_quic);
+/// ```
+pub struct EnvFeatureTracker<T: FeatureSet> {
+    prefix: String,
+    lookup: EnvLookup,
+    // Only set when the environment was read once, up front
+    cached: Option<Arc<T::State>>,
+}
+
+type EnvLookup = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+impl<T: FeatureSet> EnvFeatureTracker<T> {
+    /// Read the features from the environment once, every feature check shares that state.
+    pub fn new(prefix: impl Into<String>) -> Result<Self, EnvFeatureError> {
+        Self::new_with_lookup(prefix, process_env)
+    }
+
+    /// Like [`new`][EnvFeatureTracker::new], reading each variable with `lookup` rather than from
+    /// the process environment.
+    pub fn new_with_lookup(
+        prefix: impl Into<String>,
+        lookup: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Result<Self, EnvFeatureError> {
+        let mut tracker = Self::live_with_lookup(prefix, lookup);
+        tracker.cached = Some(Arc::new(tracker.read_state(true)?));
+        Ok(tracker)
+    }
+
+    /// Read the features from the environment on every feature check.
+    pub fn live(prefix: impl Into<String>) -> Self {
+        Self::live_with_lookup(prefix, process_env)
+    }
+
+    /// Like [`live`][EnvFeatureTracker::live], reading each variable with `lookup` rather than from
+    /// the process environment.
+    pub fn live_with_lookup(
+        prefix: impl Into<String>,
+        lookup: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            prefix: prefix.into(),
+            lookup: Box::new(lookup),
+            cached: None,
+        }
+    }
+
+    /// The environment variable that sets `feature`.
+    pub fn var_name(&self, feature: &T) -> String {
+        format!("{}_{}", self.prefix, feature.name().to_uppercase())
+    }
+
+    /// Convenience function for applying the tracker as the global default rather than having to
+    /// specify the generics matching generated types. See [`ConspiracyFeatureTracker::set_as_global_tracker`].
+    pub fn set_as_global_tracker(self) -> Result<(), SetGlobalTrackerError> {
+        set_global_tracker::<T::State, Self>(self)
+    }
+
+    fn read_state(&self, strict: bool) -> Result<T::State, EnvFeatureError> {
+        let mut overrides = T::Overrides::default();
+
+        for feature in T::all() {
+            let var = self.var_name(feature);
+            let Some(value) = (self.lookup)(&var) else {
+                continue;
+            };
+
+            match value.parse() {
+                Ok(enabled) => overrides.set(feature, enabled),
+                Err(_) if strict => return Err(EnvFeatureError { var, value }),
+                Err(_) => {}
+            }
+        }
+
+        let mut state = T::State::default();
+        overrides.apply(&mut state);
+        Ok(state)
+    }
+}
+
+/// Reads `var` from the process environment. A value that isn't valid unicode is kept (lossily), so
+/// it's reported as invalid rather than treated as unset.
+fn process_env(var: &str) -> Option<String> {
+    std::env::var_os(var).map(|value| value.to_string_lossy().into_owned())
+}
+
+impl<T: FeatureSet> FeatureTracker for EnvFeatureTracker<T> {
+    fn static_feature_state(&self) -> Arc<dyn Any + Send + Sync> {
+        match &self.cached {
+            Some(state) => state.clone(),
+            None => Arc::new(self.read_state(false).expect("Invalid values are skipped")),
+        }
+    }
+
+    fn static_feature_state_part(&self, part: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        state_part::<T>(self.static_feature_state(), part)
+    }
+
+    fn static_feature_state_parts(&self) -> Vec<TypeId> {
+        T::state_parts()
+    }
+}
+
+impl<T: FeatureSet> ExplainFeature<T> for EnvFeatureTracker<T> {}
+
+/// Error returned by [`EnvFeatureTracker::new`] when a feature's environment variable isn't a
+/// [`bool`].
+#[derive(thiserror::Error, Debug)]
+#[error("Environment variable `{var}` must be `true` or `false`, found `{value}`")]
+pub struct EnvFeatureError {
+    var: String,
+    value: String,
+}
+
+/// Error returned by [`ConspiracyFeatureTracker::from_file`].
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+#[derive(thiserror::Error, Debug)]
+pub enum FeatureFileError {
+    #[error("Failed to read the feature state file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(
+        "Unsupported feature state file `{}`, the extension must be one of the enabled formats",
+        .0.display()
+    )]
+    UnsupportedFormat(PathBuf),
+    #[cfg(feature = "json")]
+    #[error("Invalid JSON feature state: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "toml")]
+    #[error("Invalid TOML feature state: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[cfg(feature = "yaml")]
+    #[error("Invalid YAML feature state: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// Per-feature rollout percentages consumed by [`FlightingFeatureTracker`], keyed by the feature's
+/// [`name`][FeatureSet::name]. Percentages are in the range `0.0..=100.0`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RolloutConfig {
+    pub percentages: HashMap<String, f64>,
+}
+
+impl RolloutConfig {
+    /// Set the rollout percentage of a feature.
+    pub fn with(mut self, feature: impl Into<String>, percentage: f64) -> Self {
+        self.percentages.insert(feature.into(), percentage);
+        self
+    }
+}
+
+/// A context that [`FlightingFeatureTracker`] buckets, e.g. a user or tenant id. The bucket is a hash
+/// of [`rollout_bytes`][RolloutContext::rollout_bytes], so the encoding must be the same on every
+/// platform and release for contexts to keep their buckets.
+///
+/// Strings and byte slices are used as-is (strings as UTF-8) and integers are encoded as their
+/// little-endian bytes. `usize` and `isize` aren't supported since their width differs between
+/// platforms, convert them to a fixed width integer first. For a compound context, implement this
+/// with a stable encoding of its parts.
+pub trait RolloutContext {
+    /// The bytes identifying this context.
+    fn rollout_bytes(&self) -> Cow<'_, [u8]>;
+}
+
+impl RolloutContext for str {
+    fn rollout_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl RolloutContext for String {
+    fn rollout_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl RolloutContext for [u8] {
+    fn rollout_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl RolloutContext for Vec<u8> {
+    fn rollout_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<T: RolloutContext + ?Sized> RolloutContext for &T {
+    fn rollout_bytes(&self) -> Cow<'_, [u8]> {
+        (**self).rollout_bytes()
+    }
+}
+
+macro_rules! rollout_context_for_integers {
+    ($($ty:ty),*) => {
+        $(
+            impl RolloutContext for $ty {
+                fn rollout_bytes(&self) -> Cow<'_, [u8]> {
+                    Cow::Owned(self.to_le_bytes().to_vec())
+                }
+            }
+        )*
+    };
+}
+
+rollout_context_for_integers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// The resolution of rollout percentages, i.e. percentages are honored to two decimal places.
+const ROLLOUT_BUCKETS: u64 = 10_000;
+
+/// A [`FeatureTracker`] that enables features for a percentage of contexts (e.g. a user or tenant
+/// id). Each context is deterministically hashed into a bucket per feature, and the feature is
+/// enabled when the bucket falls below the feature's rollout percentage. Because the bucket of a
+/// context never changes, ramping a percentage up only ever adds contexts: anyone who already had
+/// the feature keeps it.
+///
+/// The rollout is read from a [`ConfigFetcher`] on every evaluation, so percentages can be ramped
+/// at runtime. Features without a percentage fall back to the base state.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// use conspiracy::config::shared_fetcher_from_static;
+/// use conspiracy::feature_control::tracker::{FlightingFeatureTracker, RolloutConfig, StaticFetcher};
+///
+/// conspiracy::feature_control::define_features!(pub enum Features { NewCheckout => false });
+///
+/// let rollout = RolloutConfig::default().with("new_checkout", 25.0);
+/// let tracker = FlightingFeatureTracker::<Features, _, _>::from_default(
+///     shared_fetcher_from_static(Arc::new(rollout)),
+/// );
+///
+/// let enabled_for_tenant = tracker.is_enabled(Features::NewCheckout, &"tenant-42");
+/// ```
+///
+/// Flighting needs a context, which the global feature macros don't have. When registered as the
+/// global tracker, [`feature_enabled`][crate::feature_control::feature_enabled] sees only the base
+/// state.
+pub struct FlightingFeatureTracker<T, F, R>
+where
+    T: FeatureSet,
+    F: ConfigFetcher<T::State>,
+    R: ConfigFetcher<RolloutConfig>,
+{
+    base: F,
+    rollout: R,
+    phantom: PhantomData<T>,
+}
+
+impl<T: FeatureSet, R: ConfigFetcher<RolloutConfig>>
+    FlightingFeatureTracker<T, StaticFetcher<T>, R>
+{
+    /// Initialize using the default value of `T` as the base state.
+    pub fn from_default(rollout: R) -> Self {
+        Self::new(
+            StaticFetcher {
+                state: Arc::new(T::State::default()),
+            },
+            rollout,
+        )
+    }
+}
+
+impl<T, F, R> FlightingFeatureTracker<T, F, R>
+where
+    T: FeatureSet,
+    F: ConfigFetcher<T::State>,
+    R: ConfigFetcher<RolloutConfig>,
+{
+    /// Initialize using the given fetchers for the base state and rollout percentages.
+    pub fn new(base: F, rollout: R) -> Self {
+        Self {
+            base,
+            rollout,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Whether `feature` is enabled for `context`. The same feature and context always land in the
+    /// same bucket, regardless of process, platform, or rollout percentage.
+    pub fn is_enabled(&self, feature: T, context: &(impl RolloutContext + ?Sized)) -> bool {
+        let name = feature.name();
+        match self.rollout.latest_snapshot().percentages.get(name) {
+            Some(percentage) => rollout_bucket(name, context) < percentage_threshold(*percentage),
+            None => self.base.latest_snapshot().as_feature(feature),
+        }
+    }
+
+    /// The bucket of `context` for `feature`, in the range `0..10_000`. The feature is enabled for
+    /// the context when the bucket is below the feature's percentage times 100, e.g. for debugging
+    /// why a context did or didn't get a feature.
+    pub fn bucket(&self, feature: T, context: &(impl RolloutContext + ?Sized)) -> u64 {
+        rollout_bucket(feature.name(), context)
+    }
+}
+
+impl<T, F, R> FlightingFeatureTracker<T, F, R>
+where
+    T: FeatureSet,
+    F: ConfigFetcher<T::State> + Send + Sync + 'static,
+    R: ConfigFetcher<RolloutConfig> + Send + Sync + 'static,
+{
+    /// Convenience function for applying the tracker as the global default rather than having to
+    /// specify the generics matching generated types. See [`ConspiracyFeatureTracker::set_as_global_tracker`].
+    pub fn set_as_global_tracker(self) -> Result<(), SetGlobalTrackerError> {
+        set_global_tracker::<T::State, Self>(self)
+    }
+}
+
+impl<T, F, R> FeatureTracker for FlightingFeatureTracker<T, F, R>
+where
+    T: FeatureSet,
+    F: ConfigFetcher<T::State> + 'static,
+    R: ConfigFetcher<RolloutConfig> + 'static,
+{
+    fn static_feature_state(&self) -> Arc<dyn Any + Send + Sync> {
+        self.base.latest_snapshot()
+    }
+
+    fn static_feature_state_part(&self, part: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        T::state_part(&self.base.latest_snapshot(), part)
+    }
+
+    fn static_feature_state_parts(&self) -> Vec<TypeId> {
+        T::state_parts()
+    }
+}
+
+/// [`FeatureSet::state_part`] of a type erased state, for trackers that build their state on the
+/// fly rather than reading it from a fetcher.
+fn state_part<T: FeatureSet>(
+    state: Arc<dyn Any + Send + Sync>,
+    part: TypeId,
+) -> Option<Arc<dyn Any + Send + Sync>> {
+    T::state_part(state.downcast_ref::<T::State>()?, part)
+}
+
+fn percentage_threshold(percentage: f64) -> u64 {
+    (percentage.clamp(0.0, 100.0) / 100.0 * ROLLOUT_BUCKETS as f64).round() as u64
+}
+
+/// Hashes the feature name, a `0xFF` separator (which never occurs in UTF-8), and the context's
+/// bytes with 64-bit FNV-1a. The std hashers aren't guaranteed to be stable across releases, and
+/// [`Hash`][std::hash::Hash] writes native-endian integers, either of which would reshuffle buckets
+/// on upgrade or between platforms.
+fn rollout_bucket(feature: &str, context: &(impl RolloutContext + ?Sized)) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325;
+    for byte in feature
+        .as_bytes()
+        .iter()
+        .chain([0xFF].iter())
+        .chain(context.rollout_bytes().iter())
+    {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash % ROLLOUT_BUCKETS
 }
 
 /// Implementation detail of the global tracker state. This is the initial state before [`set_global_tracker`]
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use conspiracy::config::{
+    ambient::{ambient_config, set_ambient_config},
+    config_struct,
+    fetcher::SwapFetcher,
+};
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+    }
+);
+
+// The ambient config is process wide, so the whole lifecycle is exercised in a single test
+#[test]
+fn set_once_then_read() {
+    assert!(ambient_config::<AppConfig>().is_none());
+
+    let fetcher = Arc::new(SwapFetcher::new(Arc::new(AppConfig { port: 8080 })));
+    set_ambient_config(fetcher.clone()).unwrap();
+    assert_eq!(8080, ambient_config::<AppConfig>().unwrap().port);
+
+    // Updates to the fetcher are visible to later reads
+    fetcher.store(Arc::new(AppConfig { port: 9090 }));
+    assert_eq!(9090, ambient_config::<AppConfig>().unwrap().port);
+
+    // Second set is rejected, the original fetcher is kept
+    let replacement = Arc::new(SwapFetcher::new(Arc::new(AppConfig { port: 1 })));
+    assert!(set_ambient_config(replacement).is_err());
+    assert!(set_ambient_config(Arc::new(SwapFetcher::new(Arc::new(1u32)))).is_err());
+    assert_eq!(9090, ambient_config::<AppConfig>().unwrap().port);
+
+    // Reading as a different type never panics
+    assert!(ambient_config::<u32>().is_none());
+}
@@ -0,0 +1,59 @@
+use arbitrary::{Arbitrary, Unstructured};
+use conspiracy::config::{config_struct, RestartRequired};
+
+config_struct!(
+    #[conspiracy(arbitrary)]
+    pub struct FuzzConfig {
+        #[conspiracy(restart)]
+        port: u16,
+        hosts: Vec<String>,
+        database: pub struct FuzzDatabase {
+            url: String,
+            pool_size: Option<u32>,
+        },
+        backend: pub enum FuzzBackend {
+            Memory(pub struct FuzzMemory {
+                capacity: u64,
+            }),
+            Disk(pub struct FuzzDisk {
+                path: String,
+            }),
+        }
+    }
+);
+
+/// A cheap deterministic stand-in for the fuzzer's input.
+fn pseudo_random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn configs_are_synthesized_from_raw_bytes() {
+    for seed in 0..256 {
+        let bytes = pseudo_random_bytes(seed, 128);
+
+        let compact = CompactFuzzConfig::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        let config = compact.arcify();
+        // Round-trips through the compact form, and a config never requires a restart from itself
+        assert!(*config.compact().arcify() == *config);
+        assert!(!config.restart_required(&config));
+
+        let direct = FuzzConfig::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+        assert!(!direct.restart_required(&direct));
+    }
+}
+
+#[test]
+fn empty_input_still_produces_a_config() {
+    let config = FuzzConfig::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert_eq!(0, config.port);
+    assert!(config.hosts.is_empty());
+}
@@ -0,0 +1,45 @@
+use conspiracy::feature_control::{
+    current_feature_state, define_features, macro_targets, set_global_tracker,
+    tracker::{ConspiracyFeatureTracker, StaticFetcher},
+};
+
+define_features!(
+    pub enum Features {
+        Foo => false,
+    }
+);
+
+define_features!(
+    pub enum OtherFeatures {
+        Bar => false,
+    }
+);
+
+const EXPECTED: &str = "Expected global feature state of type `bad_cast_tracker::FeaturesState`, \
+    but the global tracker `conspiracy::feature_control::tracker::ConspiracyFeatureTracker<\
+    bad_cast_tracker::OtherFeatures, conspiracy::feature_control::tracker::StaticFetcher<\
+    bad_cast_tracker::OtherFeatures>>` provides a different type";
+
+#[test]
+fn mismatched_tracker_names_both_types() {
+    // Registered as tracking `FeaturesState`, but the tracker was created for `OtherFeatures`
+    let error = set_global_tracker::<
+        FeaturesState,
+        ConspiracyFeatureTracker<OtherFeatures, StaticFetcher<OtherFeatures>>,
+    >(ConspiracyFeatureTracker::from_default())
+    .unwrap_err();
+    assert!(error.to_string().starts_with(EXPECTED));
+
+    let error = current_feature_state::<Features>().unwrap_err();
+    assert!(error.to_string().starts_with(EXPECTED));
+
+    // What `feature_enabled!` does outside of `#[cfg(test)]`
+    let panic = std::panic::catch_unwind(|| unsafe {
+        macro_targets::feature_state_unchecked::<FeaturesState>();
+    })
+    .unwrap_err();
+    let message = panic
+        .downcast_ref::<String>()
+        .expect("Panics with a formatted message");
+    assert!(message.starts_with(EXPECTED));
+}
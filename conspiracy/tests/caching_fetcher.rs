@@ -0,0 +1,30 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use conspiracy::config::{fetcher::CachingFetcher, shared_fetcher_from_fn};
+use conspiracy_theories::config::ConfigFetcher;
+
+#[test]
+fn inner_called_once_per_ttl() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let inner = shared_fetcher_from_fn(move || Arc::new(counter.fetch_add(1, Ordering::SeqCst)));
+
+    let fetcher = CachingFetcher::new(inner, Duration::from_millis(200));
+
+    assert_eq!(0, *fetcher.latest_snapshot());
+    assert_eq!(0, *fetcher.latest_snapshot());
+    assert_eq!(0, *fetcher.latest_snapshot());
+    assert_eq!(1, calls.load(Ordering::SeqCst));
+
+    std::thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(1, *fetcher.latest_snapshot());
+    assert_eq!(1, *fetcher.latest_snapshot());
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "tokio")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{fetcher::ChannelFetcher, ConfigFetcher};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn reflects_latest_pushed_update() {
+    let (sender, receiver) = mpsc::channel(4);
+    let fetcher = Arc::new(ChannelFetcher::new(Arc::new("initial".to_string())));
+    let listener = fetcher.listen(receiver);
+    assert_eq!("initial", *fetcher.latest_snapshot());
+
+    sender.send(Arc::new("first".to_string())).await.unwrap();
+    sender.send(Arc::new("second".to_string())).await.unwrap();
+
+    drop(sender);
+    listener.await.unwrap();
+    assert_eq!("second", *fetcher.latest_snapshot());
+}
+
+#[tokio::test]
+async fn listener_stops_once_fetcher_is_dropped() {
+    let (sender, receiver) = mpsc::channel(4);
+    let fetcher = Arc::new(ChannelFetcher::new(Arc::new(0)));
+    let listener = fetcher.listen(receiver);
+
+    drop(fetcher);
+    sender.send(Arc::new(1)).await.unwrap();
+    listener.await.unwrap();
+    assert!(sender.is_closed());
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use conspiracy::{
+    config::RestartRequired,
+    feature_control::{
+        combine_features, current_feature_state, feature_enabled, feature_enabled_all,
+        tracker::ConspiracyFeatureTracker, AsFeature, FeatureOverrides, FeatureSet,
+    },
+};
+
+mod payments {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            #[conspiracy(restart)]
+            NewCheckout => false,
+            SavedCards => true,
+        }
+    );
+}
+
+mod search {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            FuzzyMatching => false,
+        }
+    );
+}
+
+combine_features!(
+    pub enum AllFeatures {
+        Payments(payments::Features),
+        Search(search::Features),
+    }
+);
+
+#[test]
+fn one_tracker_serves_every_part() {
+    let state = AllFeaturesState {
+        payments: Arc::new(
+            payments::Features::builder()
+                .saved_cards(false)
+                .build()
+                .unwrap(),
+        ),
+        search: Arc::new(
+            search::Features::builder()
+                .fuzzy_matching(true)
+                .build()
+                .unwrap(),
+        ),
+    };
+    ConspiracyFeatureTracker::<AllFeatures, _>::from_static(state)
+        .set_as_global_tracker()
+        .unwrap();
+
+    assert!(!feature_enabled!(payments::Features::NewCheckout));
+    assert!(!feature_enabled!(payments::Features::SavedCards));
+    assert!(feature_enabled!(search::Features::FuzzyMatching));
+    assert!(!feature_enabled_all!(
+        payments::Features::SavedCards,
+        payments::Features::NewCheckout
+    ));
+
+    // Parts are shared with the combined state rather than copied
+    let combined = current_feature_state::<AllFeatures>().unwrap();
+    let search = current_feature_state::<search::Features>().unwrap();
+    assert!(Arc::ptr_eq(&combined.search, &search));
+}
+
+#[test]
+fn combined_set_delegates_to_parts() {
+    assert_eq!(
+        vec![
+            AllFeatures::Payments(payments::Features::NewCheckout),
+            AllFeatures::Payments(payments::Features::SavedCards),
+            AllFeatures::Search(search::Features::FuzzyMatching),
+        ],
+        AllFeatures::all()
+    );
+    assert_eq!(
+        "fuzzy_matching",
+        AllFeatures::from(search::Features::FuzzyMatching).name()
+    );
+
+    let mut state = AllFeaturesState::default();
+    assert!(state.as_feature(AllFeatures::Payments(payments::Features::SavedCards)));
+
+    let mut overrides = AllFeaturesOverrides::default();
+    overrides.set(&AllFeatures::Search(search::Features::FuzzyMatching), true);
+    assert_eq!(
+        None,
+        overrides.get(&AllFeatures::Payments(payments::Features::SavedCards))
+    );
+    overrides.apply(&mut state);
+    assert!(state.as_feature(AllFeatures::Search(search::Features::FuzzyMatching)));
+}
+
+#[test]
+fn restart_of_a_part_restarts_the_whole() {
+    let current = AllFeaturesState::default();
+    let toggled = AllFeaturesState {
+        payments: Arc::new(
+            payments::Features::builder()
+                .new_checkout(true)
+                .build()
+                .unwrap(),
+        ),
+        ..Default::default()
+    };
+    let unflagged = AllFeaturesState {
+        search: Arc::new(
+            search::Features::builder()
+                .fuzzy_matching(true)
+                .build()
+                .unwrap(),
+        ),
+        ..Default::default()
+    };
+
+    assert!(current.restart_required(&toggled));
+    assert_eq!(vec!["payments"], current.restart_reasons(&toggled));
+    assert!(!current.restart_required(&unflagged));
+}
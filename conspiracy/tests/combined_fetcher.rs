@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use conspiracy::{
+    config::{config_struct, fetcher::CombinedFetcher, ConfigFetcher, SharedConfigFetcher},
+    feature_control::define_features,
+};
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+    }
+);
+
+define_features!(
+    pub enum Features {
+        UseQuic => false,
+    }
+);
+
+#[test]
+fn combined_snapshot_projects_to_each_half() {
+    let combined = Arc::new(CombinedFetcher::<AppConfig, Features>::new(
+        Arc::new(AppConfig { port: 80 }),
+        Arc::new(FeaturesState::default()),
+    ));
+
+    let snapshot = combined.latest_snapshot();
+    assert_eq!(80, snapshot.0.port);
+    assert!(!snapshot.1.use_quic);
+    // Nothing was stored, so the snapshot is shared
+    assert!(Arc::ptr_eq(&snapshot, &combined.latest_snapshot()));
+
+    let config_fetcher: SharedConfigFetcher<AppConfig> = combined.config_fetcher();
+    let feature_state_fetcher: SharedConfigFetcher<FeaturesState> =
+        combined.feature_state_fetcher();
+    assert!(Arc::ptr_eq(&snapshot.0, &config_fetcher.latest_snapshot()));
+    assert!(Arc::ptr_eq(
+        &snapshot.1,
+        &feature_state_fetcher.latest_snapshot()
+    ));
+
+    combined.store(
+        Arc::new(AppConfig { port: 8080 }),
+        Arc::new(Features::builder().use_quic(true).build().unwrap()),
+    );
+
+    // A snapshot taken before the update is unaffected
+    assert_eq!(80, snapshot.0.port);
+    assert!(!snapshot.1.use_quic);
+
+    let (config, features) = &*combined.latest_snapshot();
+    assert_eq!(8080, config.port);
+    assert!(features.use_quic);
+    assert_eq!(8080, config_fetcher.latest_snapshot().port);
+    assert!(feature_state_fetcher.latest_snapshot().use_quic);
+}
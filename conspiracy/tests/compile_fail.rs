@@ -0,0 +1,9 @@
+// The UI cases use the serde helpers
+#![cfg(feature = "serde")]
+
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/ui/pass/*.rs");
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "json")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{config_struct, fields::ConfigFields, full_serde, ConfigStruct};
+use serde_json::json;
+
+config_struct!(
+    #[full_serde]
+    pub struct AppConfig {
+        name: String,
+        tags: Vec<String>,
+        server:
+            #[full_serde]
+            pub struct ServerConfig {
+                #[serde(rename = "listenPort")]
+                port: u16,
+                tls:
+                    #[full_serde]
+                    pub struct TlsConfig {
+                        enabled: bool,
+                        cert_path: Option<String>,
+                }
+        }
+    }
+);
+
+#[test]
+fn nested_config_yields_dotted_leaf_fields() {
+    let config = AppConfig {
+        name: "api".to_string(),
+        tags: vec!["edge".to_string()],
+        server: Arc::new(ServerConfig {
+            port: 443,
+            tls: Arc::new(TlsConfig {
+                enabled: true,
+                cert_path: None,
+            }),
+        }),
+    };
+
+    assert_eq!(
+        vec![
+            ("name".to_string(), json!("api")),
+            ("server.listenPort".to_string(), json!(443)),
+            ("server.tls.cert_path".to_string(), json!(null)),
+            ("server.tls.enabled".to_string(), json!(true)),
+            ("tags".to_string(), json!(["edge"])),
+        ],
+        config.fields().unwrap()
+    );
+}
+
+#[derive(ConfigStruct, Clone, PartialEq)]
+#[full_serde]
+pub struct DerivedConfig {
+    pub port: u16,
+}
+
+#[test]
+fn derived_config_yields_fields() {
+    assert_eq!(
+        vec![("port".to_string(), json!(8080))],
+        DerivedConfig { port: 8080 }.fields().unwrap()
+    );
+}
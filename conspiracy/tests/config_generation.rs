@@ -1,11 +1,14 @@
-use std::{sync::Arc, time::Duration};
+#![cfg(feature = "serde")]
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use conspiracy::config::{
-    as_shared_fetcher, config_struct, shared_fetcher_from_fn, shared_fetcher_from_static, AsField,
-    RestartRequired, SharedConfigFetcher,
+    as_optional_shared_fetcher, as_shared_fetcher, config_struct, fetcher::SwapFetcher,
+    shared_fetcher_from_fn, shared_fetcher_from_static, AsField, AsVariant, RestartRequired,
+    SharedConfigFetcher,
 };
 use conspiracy_macros::{full_serde, full_serde_as};
-use serde_with::{serde_as, DurationMilliSeconds, DurationSeconds};
+use serde_with::{DurationMilliSeconds, DurationSeconds};
 
 mod wrapper {
     use conspiracy_macros::config_struct;
@@ -21,6 +24,29 @@ mod wrapper {
     );
 }
 
+mod restricted {
+    pub mod config {
+        use conspiracy_macros::config_struct;
+        // Confirm restricted paths are preserved, the types are used from the parent module below.
+        config_struct!(
+            #[derive(Default)]
+            pub(in crate::restricted) struct Limits {
+                pub(in crate::restricted) rate:
+                    #[derive(Default)]
+                    pub(in crate::restricted) struct RateLimit {
+                        pub(in crate::restricted) per_second: u32,
+                    }
+            }
+        );
+    }
+
+    pub fn bump_rate() -> u32 {
+        let mut compact: config::CompactLimits = config::Limits::default().compact();
+        compact.rate.per_second += 1;
+        compact.arcify().rate.per_second
+    }
+}
+
 config_struct!(
     pub struct ConfigA {
         foo: u32,
@@ -72,6 +98,294 @@ config_struct!(
     }
 );
 
+config_struct!(
+    #[full_serde]
+    #[conspiracy(strict)]
+    pub struct StrictConfig {
+        foo: u32,
+        nested:
+            #[full_serde]
+            pub struct StrictNested {
+                bar: u32,
+                deeper:
+                    #[full_serde]
+                    pub struct StrictDeeper {
+                        cow: u32,
+                }
+        }
+    }
+);
+
+mod external {
+    use conspiracy_macros::config_struct;
+
+    config_struct!(
+        pub struct ExternalConfig {
+            #[conspiracy(restart)]
+            pub addr: String,
+            pub name: String,
+        }
+    );
+}
+
+config_struct!(
+    pub struct WithExternal {
+        foo: u32,
+        inner: struct WithExternalInner {
+            external: @external::ExternalConfig,
+        }
+    }
+);
+
+fn with_external_base() -> Arc<WithExternal> {
+    Arc::new(WithExternal {
+        foo: 0,
+        inner: Arc::new(WithExternalInner {
+            external: Arc::new(external::ExternalConfig {
+                addr: "localhost".to_string(),
+                name: "foo".to_string(),
+            }),
+        }),
+    })
+}
+
+config_struct!(
+    #[full_serde]
+    #[serde(rename_all = "camelCase")]
+    #[conspiracy(schema)]
+    pub struct SchemaConfig {
+        max_connections: u32,
+        web_server:
+            #[full_serde]
+            #[serde(rename_all = "camelCase")]
+            pub struct SchemaWebServer {
+                #[serde(rename = "listenAddress")]
+                addr: String,
+                request_timeout_ms: u64,
+        }
+    }
+);
+
+fn millis<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let millis: Option<u64> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(millis.map(Duration::from_millis))
+}
+
+config_struct!(
+    #[full_serde]
+    pub struct WithOptionals {
+        #[serde(deserialize_with = "millis")]
+        timeout: Option<Duration>,
+        #[conspiracy(no_default)]
+        #[serde(deserialize_with = "millis")]
+        backoff: Option<Duration>,
+        nested:
+            #[full_serde]
+            pub struct NestedOptionals {
+                name: Option<String>,
+        }
+    }
+);
+
+config_struct!(
+    #[full_serde]
+    pub struct WithEnvOverrides {
+        #[conspiracy(env = "CONFIG_GENERATION_DATABASE_URL")]
+        database_url: String,
+        name: String,
+        nested:
+            #[full_serde]
+            pub struct NestedEnvOverrides {
+                #[conspiracy(restart, env = "CONFIG_GENERATION_PORT")]
+                port: u16,
+        }
+    }
+);
+
+fn different_second(a: &Duration, b: &Duration) -> bool {
+    a.as_secs() != b.as_secs()
+}
+
+config_struct!(
+    pub struct CustomCompare {
+        #[conspiracy(restart, compare = "different_second")]
+        timeout: Duration,
+        #[conspiracy(restart, shared, compare = "different_second")]
+        shared_timeout: Duration,
+    }
+);
+
+config_struct!(
+    #[derive(Default)]
+    pub struct Defaulted {
+        retries: u32,
+        nested:
+            #[derive(Default)]
+            pub struct DefaultedNested {
+                name: String,
+        }
+    }
+);
+
+config_struct!(
+    pub struct ChangeCategories {
+        #[conspiracy(change = "restart")]
+        port: u16,
+        #[conspiracy(change = "reload")]
+        log_level: String,
+        #[conspiracy(change = "reload")]
+        cache:
+            pub struct CacheConfig {
+                #[conspiracy(restart)]
+                capacity: usize,
+                ttl_secs: u64,
+        }
+    }
+);
+
+/// Deliberately doesn't implement `PartialEq`
+#[derive(Clone)]
+pub struct NotComparable(u32);
+
+config_struct!(
+    #[conspiracy(no_restart)]
+    pub struct NoRestartConfig {
+        leaf: NotComparable,
+        nested: pub struct NoRestartNested {
+            other: NotComparable,
+        }
+    }
+);
+
+/// Deliberately implements neither `Clone` nor `PartialEq`, like a type holding a file handle
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Handle {
+    path: String,
+}
+
+config_struct!(
+    #[full_serde]
+    pub struct WithSharedLeaf {
+        #[conspiracy(shared, restart)]
+        handle: Handle,
+        #[conspiracy(shared)]
+        backup: Option<Handle>,
+        name: String,
+    }
+);
+
+config_struct!(
+    pub struct WithSecrets {
+        name: String,
+        timeout: Option<u32>,
+        #[conspiracy(shared)]
+        handle: Handle,
+        database: pub struct SecretDatabase {
+            user: String,
+            #[conspiracy(secret)]
+            password: String,
+        },
+        #[conspiracy(secret)]
+        credentials: pub struct SecretCredentials {
+            token: String,
+        }
+    }
+);
+
+config_struct!(
+    #[full_serde]
+    pub struct StorageConfig {
+        name: String,
+        backend:
+            #[full_serde]
+            #[serde(tag = "type", rename_all = "snake_case")]
+            pub enum Backend {
+                Postgres(
+                    #[full_serde]
+                    pub struct PostgresConfig {
+                        #[conspiracy(restart)]
+                        url: String,
+                        pool:
+                            #[full_serde]
+                            pub struct PoolConfig {
+                                size: u32,
+                            }
+                    }
+                ),
+                Sqlite(
+                    #[full_serde]
+                    pub struct SqliteConfig {
+                        path: String,
+                    }
+                ),
+            }
+    }
+);
+
+config_struct!(
+    #[full_serde]
+    pub struct BufferConfig {
+        #[conspiracy(restart)]
+        size: u32,
+    }
+);
+
+config_struct!(
+    #[full_serde]
+    pub struct ServiceConfig {
+        name: String,
+        settings:
+            #[full_serde]
+            pub struct ServiceSettings {
+                buffer: Option<@BufferConfig>,
+            }
+    }
+);
+
+config_struct!(
+    #[full_serde]
+    pub struct WithTransparent {
+        retry_limit:
+            #[full_serde]
+            #[conspiracy(transparent)]
+            pub struct RetryLimit {
+                value: u32,
+            },
+        endpoint:
+            #[full_serde]
+            #[conspiracy(transparent)]
+            pub struct Endpoint {
+                url: String,
+            },
+    }
+);
+
+// Strict only applies where there's a `Deserialize` derive, the rest compile as usual
+config_struct!(
+    #[conspiracy(strict)]
+    pub struct StrictWithoutSerde {
+        foo: u32,
+        nested: pub struct StrictWithoutSerdeNested {
+            bar: u32,
+        }
+    }
+);
+
+config_struct!(
+    pub struct WithCollections {
+        #[conspiracy(restart)]
+        endpoints: Vec<String>,
+        tags: Vec<String>,
+    }
+);
+
+fn with_collections_base() -> WithCollections {
+    WithCollections {
+        endpoints: vec!["a".to_string(), "b".to_string()],
+        tags: vec!["x".to_string(), "y".to_string()],
+    }
+}
+
 fn with_attributes_base() -> WithAttributesTest {
     WithAttributesTest {
         foo: 0,
@@ -91,6 +405,320 @@ fn compact_attribute_passthrough() {
     wrapper::Bar::default().compact().arcify();
 }
 
+config_struct!(
+    #[full_serde]
+    pub struct ForwardCompatible {
+        port: u16,
+        #[conspiracy(restart)]
+        #[serde(flatten)]
+        extra: HashMap<String, serde_json::Value>,
+        nested:
+            #[full_serde]
+            pub struct ForwardCompatibleNested {
+                name: String,
+                #[serde(flatten)]
+                extra: HashMap<String, serde_json::Value>,
+            }
+    }
+);
+
+#[test]
+fn flattened_catch_all_round_trips() {
+    let json = serde_json::json!({
+        "port": 80,
+        "added_later": { "enabled": true },
+        "nested": { "name": "foo", "also_added": [1, 2] },
+    });
+
+    let config: ForwardCompatible = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(80, config.port);
+    assert_eq!(json["added_later"], config.extra["added_later"]);
+    assert_eq!(
+        json["nested"]["also_added"],
+        config.nested.extra["also_added"]
+    );
+
+    assert_eq!(json, serde_json::to_value(&config).unwrap());
+}
+
+#[test]
+fn flattened_catch_all_restart_ignores_key_order() {
+    let config: ForwardCompatible =
+        serde_json::from_str(r#"{ "port": 80, "a": 1, "b": 2, "nested": { "name": "foo" } }"#)
+            .unwrap();
+    let reordered: ForwardCompatible =
+        serde_json::from_str(r#"{ "b": 2, "a": 1, "port": 80, "nested": { "name": "foo" } }"#)
+            .unwrap();
+    let changed: ForwardCompatible =
+        serde_json::from_str(r#"{ "port": 80, "a": 1, "b": 3, "nested": { "name": "foo" } }"#)
+            .unwrap();
+
+    assert!(!config.restart_required(&reordered));
+    assert_eq!(vec!["extra"], config.restart_reasons(&changed));
+}
+
+config_struct!(
+    #[full_serde]
+    #[conspiracy(strict)]
+    pub struct Renamed {
+        #[serde(alias = "max_conns")]
+        max_connections: u32,
+        pool:
+            #[full_serde]
+            #[serde(rename_all = "camelCase")]
+            pub struct RenamedPool {
+                #[serde(alias = "idle_timeout_secs", alias = "idleTimeout")]
+                idle_timeout_seconds: u64,
+            }
+    }
+);
+
+#[test]
+fn aliases_accept_old_names() {
+    let json = r#"{ "max_conns": 16, "pool": { "idle_timeout_secs": 30 } }"#;
+
+    let config: Renamed = serde_json::from_str(json).unwrap();
+    assert_eq!(16, config.max_connections);
+    assert_eq!(30, config.pool.idle_timeout_seconds);
+
+    let compact: CompactRenamed = serde_json::from_str(json).unwrap();
+    assert!(config == *compact.arcify());
+
+    // The new names are still accepted, and are what's written back
+    let rewritten = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        serde_json::json!({ "max_connections": 16, "pool": { "idleTimeoutSeconds": 30 } }),
+        rewritten
+    );
+    assert!(config == serde_json::from_value(rewritten).unwrap());
+}
+
+config_struct!(
+    #[full_serde]
+    pub struct ImplicitSerdeAs {
+        #[serde_as(as = "DurationSeconds")]
+        timeout: Duration,
+        retry:
+            #[full_serde]
+            pub struct ImplicitSerdeAsRetry {
+                #[serde_as(as = "DurationMilliSeconds")]
+                backoff: Duration,
+            }
+    }
+);
+
+#[test]
+fn serde_as_fields_apply_serde_as() {
+    let json = r#"{ "timeout": 30, "retry": { "backoff": 250 } }"#;
+
+    let config: ImplicitSerdeAs = serde_json::from_str(json).unwrap();
+    assert_eq!(Duration::from_secs(30), config.timeout);
+    assert_eq!(Duration::from_millis(250), config.retry.backoff);
+
+    let compact: CompactImplicitSerdeAs = serde_json::from_str(json).unwrap();
+    assert!(config == *compact.arcify());
+    assert_eq!(
+        serde_json::json!({ "timeout": 30, "retry": { "backoff": 250 } }),
+        serde_json::to_value(&config).unwrap()
+    );
+}
+
+config_struct!(
+    #[conspiracy(hashable)]
+    pub struct CacheKeyConfig {
+        region: String,
+        backend: enum CacheBackend {
+            Memory(struct MemoryBackend {
+                capacity: u32,
+            }),
+        },
+        limits: struct CacheKeyLimits {
+            #[conspiracy(restart)]
+            max_entries: u64,
+        }
+    }
+);
+
+#[test]
+fn hashable_configs_key_maps() {
+    let config = |max_entries| CacheKeyConfig {
+        region: "eu".to_string(),
+        backend: Arc::new(CacheBackend::Memory(Arc::new(MemoryBackend {
+            capacity: 8,
+        }))),
+        limits: Arc::new(CacheKeyLimits { max_entries }),
+    };
+
+    let mut caches = HashMap::new();
+    caches.insert(config(10), "small");
+    caches.insert(config(1000), "large");
+    caches.insert(config(10), "replaced");
+
+    assert_eq!(2, caches.len());
+    assert_eq!("replaced", caches[&config(10)]);
+}
+
+config_struct!(
+    #[conspiracy(builder)]
+    pub struct BuiltConfig {
+        name: String,
+        port: u16,
+        proxy: Option<String>,
+        timeout_ms: Option<u64>,
+        #[conspiracy(shared)]
+        tags: Vec<String>,
+        storage: enum BuiltStorage {
+            Disk(struct BuiltDisk {
+                path: String,
+            }),
+        },
+        limits: struct BuiltLimits {
+            max_connections: u32,
+        }
+    }
+);
+
+#[test]
+fn builder_setters_accept_into() {
+    let config = BuiltConfig::builder()
+        .name("primary")
+        .port(8080)
+        .proxy("proxy:3128")
+        .tags(vec!["a".to_string()])
+        .storage(BuiltStorage::Disk(Arc::new(
+            BuiltDisk::builder().path("/data").build().unwrap(),
+        )))
+        .limits(BuiltLimits::builder().max_connections(16).build().unwrap())
+        .build()
+        .unwrap();
+
+    assert_eq!("primary", config.name);
+    assert_eq!(8080, config.port);
+    assert_eq!(Some("proxy:3128".to_string()), config.proxy);
+    assert_eq!(None, config.timeout_ms);
+    assert_eq!(vec!["a".to_string()], *config.tags);
+    assert_eq!(16, config.limits.max_connections);
+}
+
+#[test]
+fn builder_reports_missing_fields() {
+    let error = BuiltConfig::builder()
+        .port(8080)
+        .timeout_ms(500)
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(vec!["name", "tags", "storage", "limits"], error.0);
+}
+
+config_struct!(
+    #[full_serde]
+    pub struct Minimal {
+        port: u16,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        proxy: Option<String>,
+        tls:
+            #[full_serde]
+            pub struct MinimalTls {
+                #[serde(skip_serializing_if = "Vec::is_empty")]
+                ciphers: Vec<String>,
+            }
+    }
+);
+
+#[test]
+fn skip_serializing_if_is_preserved() {
+    let config = Minimal {
+        port: 80,
+        proxy: None,
+        tls: Arc::new(MinimalTls { ciphers: vec![] }),
+    };
+
+    let expected = serde_json::json!({ "port": 80, "tls": {} });
+    assert_eq!(expected, serde_json::to_value(&config).unwrap());
+    assert_eq!(
+        expected,
+        serde_json::to_value(config.clone().compact()).unwrap()
+    );
+
+    let mut compact = config.compact();
+    compact.proxy = Some("proxy:3128".to_string());
+    assert_eq!(
+        serde_json::json!({ "port": 80, "proxy": "proxy:3128", "tls": {} }),
+        serde_json::to_value(&compact).unwrap()
+    );
+}
+
+#[test]
+fn compact_round_trips_through_json() {
+    let mut compact = with_attributes_base().compact();
+    compact.nested_no_attributes.nested_with_attributes.timeout = Duration::from_millis(250);
+
+    let json = serde_json::to_value(&compact).unwrap();
+    assert_eq!(
+        250,
+        json["nested_no_attributes"]["nested_with_attributes"]["timeout"]
+    );
+
+    let round_tripped: CompactWithAttributesTest = serde_json::from_value(json.clone()).unwrap();
+    let arcified = round_tripped.arcify();
+    // The compact and arcified forms share the same wire format
+    assert_eq!(json, serde_json::to_value(&*arcified).unwrap());
+    assert!(compact.arcify() == arcified);
+}
+
+#[test]
+fn compact_default_matches_real_default() {
+    let mut compact = CompactDefaulted::default();
+    assert_eq!(0, compact.retries);
+    assert_eq!("", compact.nested.name);
+    assert!(*compact.arcify() == Defaulted::default());
+
+    // Nested compact structs have their own default
+    compact = CompactDefaulted {
+        retries: 3,
+        nested: CompactDefaultedNested::default(),
+    };
+    assert!(compact.arcify().nested == Arc::new(DefaultedNested::default()));
+}
+
+#[derive(serde::Deserialize)]
+pub struct LegacyPort {
+    legacy_port: u16,
+}
+
+impl From<LegacyPort> for Converted {
+    fn from(legacy: LegacyPort) -> Self {
+        Converted {
+            port: legacy.legacy_port,
+        }
+    }
+}
+
+config_struct!(
+    #[full_serde]
+    #[serde(from = "LegacyPort")]
+    pub struct Converted {
+        port: u16,
+    }
+);
+
+#[test]
+fn compact_skips_container_conversions() {
+    let config: Converted = serde_json::from_str(r#"{ "legacy_port": 80 }"#).unwrap();
+    assert_eq!(80, config.port);
+
+    // The conversion is only implemented for the real struct, the compact form uses its fields
+    let compact: CompactConverted = serde_json::from_str(r#"{ "port": 8080 }"#).unwrap();
+    assert_eq!(8080, compact.arcify().port);
+}
+
+#[test]
+fn restricted_visibility_is_preserved() {
+    assert_eq!(1, restricted::bump_rate());
+}
+
 #[test]
 fn whole_struct_marked_and_changed_restart() {
     let config = with_attributes_base();
@@ -114,6 +742,383 @@ fn nested_config_field_changed_restart() {
     assert!(config.restart_required(&other_config));
 }
 
+#[test]
+fn restart_reasons_lists_only_changed_fields() {
+    let config = with_attributes_base();
+    assert!(config.restart_reasons(&with_attributes_base()).is_empty());
+
+    let mut other_config = config.clone().compact();
+    other_config.nested_no_attributes.bar = 50;
+    // Not restart marked, so it isn't a reason
+    other_config.foo = 50;
+    let other_config = other_config.arcify();
+
+    assert_eq!(
+        vec!["nested_no_attributes.bar"],
+        config.restart_reasons(&other_config)
+    );
+}
+
+#[test]
+fn reload_fields_are_tracked_separately() {
+    let config = CompactChangeCategories {
+        port: 80,
+        log_level: "info".to_string(),
+        cache: CompactCacheConfig {
+            capacity: 10,
+            ttl_secs: 60,
+        },
+    }
+    .arcify();
+
+    let mut reload = config.compact();
+    reload.log_level = "debug".to_string();
+    reload.cache.ttl_secs = 30;
+    let reload = reload.arcify();
+
+    assert!(config.reload_required(&reload));
+    assert!(!config.restart_required(&reload));
+    assert_eq!(vec!["log_level", "cache"], config.reload_reasons(&reload));
+    assert!(config.restart_reasons(&reload).is_empty());
+
+    let mut restart = config.compact();
+    restart.cache.capacity = 20;
+    let restart = restart.arcify();
+
+    // The capacity is restart marked within a reload marked sub-config, so it's both
+    assert!(config.restart_required(&restart));
+    assert!(config.reload_required(&restart));
+    assert_eq!(vec!["cache.capacity"], config.restart_reasons(&restart));
+}
+
+#[test]
+fn custom_compare_ignores_insignificant_changes() {
+    let config = |timeout_ms, shared_timeout_ms| CustomCompare {
+        timeout: Duration::from_millis(timeout_ms),
+        shared_timeout: Arc::new(Duration::from_millis(shared_timeout_ms)),
+    };
+    let baseline = config(1_000, 1_000);
+
+    assert!(!baseline.restart_required(&config(1_999, 1_000)));
+    assert!(!baseline.restart_required(&config(1_000, 1_500)));
+    assert!(baseline.restart_required(&config(2_000, 1_000)));
+    assert_eq!(
+        vec!["shared_timeout"],
+        baseline.restart_reasons(&config(1_000, 500))
+    );
+}
+
+#[test]
+fn no_restart_without_partial_eq() {
+    let config = NoRestartConfig {
+        leaf: NotComparable(1),
+        nested: Arc::new(NoRestartNested {
+            other: NotComparable(2),
+        }),
+    };
+
+    let mut compact = config.compact();
+    compact.nested.other.0 += 1;
+    assert_eq!(3, compact.arcify().nested.other.0);
+}
+
+#[test]
+fn shared_leaf_compared_by_identity() {
+    let config: Arc<WithSharedLeaf> =
+        serde_json::from_str(r#"{ "handle": { "path": "/tmp/foo" }, "name": "foo" }"#).unwrap();
+    assert_eq!("/tmp/foo", config.handle.path);
+    // Shared `Option` leaves still default to `None` when absent
+    assert!(config.backup.is_none());
+
+    // Cloning shares the handle, so nothing changed
+    let mut renamed = config.compact();
+    renamed.name = "bar".to_string();
+    let renamed = renamed.arcify();
+    assert!(!config.restart_required(&renamed));
+    assert!(Arc::ptr_eq(&config.handle, &renamed.handle));
+
+    // An equivalent but distinct handle is a change
+    let mut reopened = config.compact();
+    reopened.handle = Arc::new(Handle {
+        path: "/tmp/foo".to_string(),
+    });
+    let reopened = reopened.arcify();
+    assert!(config != reopened);
+    assert_eq!(vec!["handle"], config.restart_reasons(&reopened));
+}
+
+#[test]
+fn enum_sub_config_projects_active_variant() {
+    let postgres: Arc<StorageConfig> = serde_json::from_str(
+        r#"{ "name": "foo", "backend": { "type": "postgres", "url": "pg://", "pool": { "size": 4 } } }"#,
+    )
+    .unwrap();
+    let sqlite: Arc<StorageConfig> = serde_json::from_str(
+        r#"{ "name": "foo", "backend": { "type": "sqlite", "path": "/tmp/db" } }"#,
+    )
+    .unwrap();
+
+    let active: Option<Arc<PostgresConfig>> = postgres.share_variant();
+    assert_eq!("pg://", active.unwrap().url);
+    assert!(AsVariant::<SqliteConfig>::share_variant(&*postgres).is_none());
+
+    let active: Option<Arc<SqliteConfig>> = sqlite.share_variant();
+    assert_eq!("/tmp/db", active.unwrap().path);
+    assert!(AsVariant::<PostgresConfig>::share_variant(&*sqlite).is_none());
+
+    // The enum itself projects like any sub-config, and variants project to their own sub-configs
+    let backend: Arc<Backend> = postgres.share();
+    let postgres_config: Arc<PostgresConfig> = backend.share_variant().unwrap();
+    let pool: Arc<PoolConfig> = postgres_config.share();
+    assert_eq!(4, pool.size);
+}
+
+#[test]
+fn optional_fetcher_follows_active_variant() {
+    let postgres: Arc<StorageConfig> = serde_json::from_str(
+        r#"{ "name": "foo", "backend": { "type": "postgres", "url": "pg://", "pool": { "size": 4 } } }"#,
+    )
+    .unwrap();
+    let sqlite: Arc<StorageConfig> = serde_json::from_str(
+        r#"{ "name": "foo", "backend": { "type": "sqlite", "path": "/tmp/db" } }"#,
+    )
+    .unwrap();
+
+    let source = Arc::new(SwapFetcher::new(postgres.clone()));
+    let fetcher: SharedConfigFetcher<Option<Arc<PostgresConfig>>> =
+        as_optional_shared_fetcher(&source);
+    let present = fetcher.latest_snapshot();
+    assert_eq!("pg://", present.as_ref().as_ref().unwrap().url);
+
+    // Removed in a later snapshot
+    source.store(sqlite);
+    assert!(fetcher.latest_snapshot().is_none());
+
+    // And present again once it's restored
+    source.store(postgres.clone());
+    let restored: Arc<PostgresConfig> = (*fetcher.latest_snapshot()).clone().unwrap();
+    assert!(Arc::ptr_eq(&postgres.share_variant().unwrap(), &restored));
+}
+
+#[test]
+fn optional_sub_config_projects_when_present() {
+    let without: Arc<ServiceConfig> =
+        serde_json::from_str(r#"{ "name": "foo", "settings": {} }"#).unwrap();
+    let with: Arc<ServiceConfig> =
+        serde_json::from_str(r#"{ "name": "foo", "settings": { "buffer": { "size": 4 } } }"#)
+            .unwrap();
+
+    assert!(AsVariant::<BufferConfig>::share_variant(&*without).is_none());
+    let buffer: Option<Arc<BufferConfig>> = with.share_variant();
+    assert_eq!(4, buffer.unwrap().size);
+
+    // Also projected from the struct that declares the field
+    let settings: Arc<ServiceSettings> = with.share();
+    let buffer: Option<Arc<BufferConfig>> = settings.share_variant();
+    assert!(Arc::ptr_eq(
+        settings.buffer.as_ref().unwrap(),
+        &buffer.unwrap()
+    ));
+}
+
+#[test]
+fn optional_fetcher_follows_optional_sub_config() {
+    let without: Arc<ServiceConfig> =
+        serde_json::from_str(r#"{ "name": "foo", "settings": {} }"#).unwrap();
+    let with: Arc<ServiceConfig> =
+        serde_json::from_str(r#"{ "name": "foo", "settings": { "buffer": { "size": 4 } } }"#)
+            .unwrap();
+
+    let source = Arc::new(SwapFetcher::new(without.clone()));
+    let fetcher: SharedConfigFetcher<Option<Arc<BufferConfig>>> =
+        as_optional_shared_fetcher(&source);
+    assert!(fetcher.latest_snapshot().is_none());
+
+    source.store(with);
+    assert_eq!(4, (*fetcher.latest_snapshot()).as_ref().unwrap().size);
+
+    source.store(without);
+    assert!(fetcher.latest_snapshot().is_none());
+}
+
+#[test]
+fn optional_sub_config_restart() {
+    let service = |buffer: Option<u32>| {
+        Arc::new(ServiceConfig {
+            name: "foo".to_string(),
+            settings: Arc::new(ServiceSettings {
+                buffer: buffer.map(|size| Arc::new(BufferConfig { size })),
+            }),
+        })
+    };
+
+    // Restart fields of the sub-config are compared while it's present
+    assert!(!service(Some(4)).restart_required(&service(Some(4))));
+    assert!(service(Some(4)).restart_required(&service(Some(8))));
+    assert!(!service(None).restart_required(&service(None)));
+
+    // Appearing or disappearing is a change
+    assert!(service(None).restart_required(&service(Some(4))));
+    assert!(service(Some(4)).restart_required(&service(None)));
+}
+
+#[test]
+fn enum_sub_config_restart() {
+    let postgres = |url: &str, size: u32| {
+        Arc::new(StorageConfig {
+            name: "foo".to_string(),
+            backend: Arc::new(Backend::Postgres(Arc::new(PostgresConfig {
+                url: url.to_string(),
+                pool: Arc::new(PoolConfig { size }),
+            }))),
+        })
+    };
+    let sqlite = Arc::new(StorageConfig {
+        name: "foo".to_string(),
+        backend: Arc::new(Backend::Sqlite(Arc::new(SqliteConfig {
+            path: "/tmp/db".to_string(),
+        }))),
+    });
+
+    let base = postgres("pg://a", 4);
+    assert!(!base.restart_required(&postgres("pg://a", 8)));
+    assert_eq!(
+        vec!["backend"],
+        base.restart_reasons(&postgres("pg://b", 4))
+    );
+    assert!(base.restart_required(&sqlite));
+    assert_eq!(
+        vec!["Sqlite"],
+        base.backend.restart_reasons(&sqlite.backend)
+    );
+}
+
+#[test]
+fn transparent_sub_config_derefs_to_field() {
+    let config: WithTransparent = serde_json::from_str(
+        r#"{ "retry_limit": { "value": 3 }, "endpoint": { "url": "https://example.com" } }"#,
+    )
+    .unwrap();
+
+    assert_eq!(3, **config.retry_limit);
+    let limit: &u32 = config.retry_limit.as_ref().as_ref();
+    assert_eq!(3, *limit);
+
+    // Methods of the inner value are reachable through auto-deref
+    assert!(config.endpoint.starts_with("https://"));
+    assert_eq!("https://example.com", config.endpoint.as_str());
+}
+
+#[test]
+fn collection_restart_is_order_sensitive() {
+    let config = with_collections_base();
+    assert!(!config.restart_required(&with_collections_base()));
+
+    let mut reordered = config.compact();
+    reordered.endpoints.reverse();
+    assert!(config.restart_required(&reordered.arcify()));
+
+    // Untracked collections never signal a restart
+    let mut retagged = config.compact();
+    retagged.tags.push("z".to_string());
+    assert!(!config.restart_required(&retagged.arcify()));
+}
+
+#[test]
+fn strict_rejects_unknown_nested_fields() {
+    let valid = r#"{ "foo": 1, "nested": { "bar": 2, "deeper": { "cow": 3 } } }"#;
+    assert!(serde_json::from_str::<StrictConfig>(valid).is_ok());
+
+    let unknown_nested =
+        r#"{ "foo": 1, "nested": { "bar": 2, "deeper": { "cow": 3, "typo": 4 } } }"#;
+    assert!(serde_json::from_str::<StrictConfig>(unknown_nested).is_err());
+
+    let unknown_child =
+        r#"{ "foo": 1, "nested": { "bar": 2, "typo": 4, "deeper": { "cow": 3 } } }"#;
+    assert!(serde_json::from_str::<StrictConfig>(unknown_child).is_err());
+}
+
+#[test]
+fn absent_option_fields_default_to_none() {
+    let config: WithOptionals = serde_json::from_str(r#"{ "backoff": 5, "nested": {} }"#).unwrap();
+    assert_eq!(None, config.timeout);
+    assert_eq!(Some(Duration::from_millis(5)), config.backoff);
+    assert_eq!(None, config.nested.name);
+
+    // Without the default, a custom deserializer requires the key to be present
+    assert!(serde_json::from_str::<WithOptionals>(r#"{ "nested": {} }"#).is_err());
+}
+
+#[test]
+fn env_overrides_win_over_file() {
+    std::env::set_var("CONFIG_GENERATION_DATABASE_URL", "from-env");
+    std::env::set_var("CONFIG_GENERATION_PORT", "8080");
+
+    let mut config: WithEnvOverrides = serde_json::from_str(
+        r#"{ "database_url": "from-file", "name": "foo", "nested": { "port": 80 } }"#,
+    )
+    .unwrap();
+    config.apply_env_overrides().unwrap();
+
+    assert_eq!("from-env", config.database_url);
+    assert_eq!("foo", config.name);
+    assert_eq!(8080, config.nested.port);
+}
+
+#[test]
+fn external_sub_config_projection() {
+    let config = with_external_base();
+    let fetcher = shared_fetcher_from_static(config.clone());
+
+    let external_fetcher: SharedConfigFetcher<external::ExternalConfig> =
+        as_shared_fetcher(&fetcher);
+    assert_eq!("foo", external_fetcher.latest_snapshot().name);
+
+    let inner_fetcher: SharedConfigFetcher<WithExternalInner> = as_shared_fetcher(&fetcher);
+    let external_fetcher: SharedConfigFetcher<external::ExternalConfig> =
+        as_shared_fetcher(&inner_fetcher);
+    assert!(Arc::ptr_eq(
+        &config.inner.external,
+        &external_fetcher.latest_snapshot()
+    ));
+}
+
+#[test]
+fn external_sub_config_restart() {
+    let config = with_external_base();
+
+    let mut renamed = config.compact();
+    renamed.inner.external = Arc::new(external::ExternalConfig {
+        addr: "localhost".to_string(),
+        name: "bar".to_string(),
+    });
+    assert!(!config.restart_required(&renamed.arcify()));
+
+    let mut readdressed = config.compact();
+    readdressed.inner.external = Arc::new(external::ExternalConfig {
+        addr: "remote".to_string(),
+        name: "foo".to_string(),
+    });
+    let readdressed = readdressed.arcify();
+    assert!(config.restart_required(&readdressed));
+    assert_eq!(vec!["inner.external"], config.restart_reasons(&readdressed));
+}
+
+#[test]
+fn schema_uses_wire_names() {
+    let schema = serde_json::to_value(SchemaConfig::schema()).unwrap();
+
+    let properties = &schema["properties"];
+    assert!(properties.get("maxConnections").is_some());
+    assert!(properties.get("webServer").is_some());
+
+    let web_server = serde_json::to_value(SchemaWebServer::schema()).unwrap();
+    let properties = &web_server["properties"];
+    assert!(properties.get("listenAddress").is_some());
+    assert!(properties.get("requestTimeoutMs").is_some());
+    assert!(properties.get("addr").is_none());
+}
+
 #[test]
 fn manual_construction() {
     let _test = ConfigA {
@@ -162,6 +1167,175 @@ fn sub_config_conversion() {
     uses_b(as_shared_fetcher(&shared_fetcher_from_static(sample)));
 }
 
+#[test]
+fn sub_config_into() {
+    let sample = sample_config();
+
+    let b: Arc<ConfigB> = (&*sample).into();
+    assert!(Arc::ptr_eq(&sample.bar, &b));
+
+    fn uses_f(f: impl Into<Arc<ConfigF>>) -> String {
+        f.into().foo.clone()
+    }
+    assert_eq!("yo", uses_f(&*sample));
+    assert_eq!("yo", uses_f(&*sample.d));
+}
+
+#[test]
+fn named_accessors_share_sub_configs() {
+    let sample = sample_config();
+
+    let bar = sample.bar();
+    assert!(Arc::ptr_eq(&sample.bar, &bar));
+    assert_eq!(sample.bar.foo, bar.foo);
+    assert_eq!("yo", sample.d().e().f().foo);
+
+    let with_external = with_external_base();
+    assert!(Arc::ptr_eq(
+        &with_external.inner.external,
+        &with_external.inner().external()
+    ));
+}
+
+#[test]
+fn replace_shares_untouched_branches() {
+    let sample = sample_config();
+    let bar = Arc::new(ConfigB {
+        foo: 10,
+        bar: sample.bar.bar.clone(),
+    });
+
+    let replaced = sample.replace_bar(bar.clone());
+
+    assert!(Arc::ptr_eq(&bar, &replaced.bar));
+    assert!(Arc::ptr_eq(&sample.d, &replaced.d));
+    assert_eq!(sample.foo, replaced.foo);
+    assert_eq!(5, sample.bar.foo);
+
+    let e = Arc::new(ConfigE {
+        f: Arc::new(ConfigF {
+            foo: "replaced".to_string(),
+        }),
+    });
+    let replaced_d = replaced.d.replace_e(e);
+    assert_eq!("replaced", replaced_d.e.f.foo);
+    assert_eq!("yo", replaced.d.e.f.foo);
+}
+
+#[test]
+fn dotted_string_redacts_secrets() {
+    let config = WithSecrets {
+        name: "api".to_string(),
+        timeout: Some(30),
+        handle: Arc::new(Handle {
+            path: "/dev/null".to_string(),
+        }),
+        database: Arc::new(SecretDatabase {
+            user: "admin".to_string(),
+            password: "hunter2".to_string(),
+        }),
+        credentials: Arc::new(SecretCredentials {
+            token: "abc123".to_string(),
+        }),
+    };
+
+    let dotted = config.to_dotted_string();
+    assert_eq!(
+        vec![
+            "name = api",
+            "timeout = Some(30)",
+            "handle = <unprintable>",
+            "database.user = admin",
+            "database.password = <redacted>",
+            "credentials = <redacted>",
+        ],
+        dotted.lines().collect::<Vec<_>>()
+    );
+    assert!(!dotted.contains("hunter2"));
+    assert!(!dotted.contains("abc123"));
+}
+
+#[test]
+fn dotted_string_follows_enums_and_external_configs() {
+    let storage = StorageConfig {
+        name: "foo".to_string(),
+        backend: Arc::new(Backend::Postgres(Arc::new(PostgresConfig {
+            url: "db:5432".to_string(),
+            pool: Arc::new(PoolConfig { size: 4 }),
+        }))),
+    };
+    assert_eq!(
+        "name = foo\nbackend.Postgres.url = db:5432\nbackend.Postgres.pool.size = 4",
+        storage.to_dotted_string()
+    );
+
+    assert_eq!(
+        "foo = 0\ninner.external.addr = localhost\ninner.external.name = foo",
+        with_external_base().to_dotted_string()
+    );
+}
+
+#[test]
+fn grandchild_fetcher_projection() {
+    let sample = sample_config();
+    let c_fetcher: SharedConfigFetcher<ConfigC> =
+        as_shared_fetcher(&shared_fetcher_from_static(sample.clone()));
+
+    assert!(Arc::ptr_eq(&sample.bar.bar, &c_fetcher.latest_snapshot()));
+}
+
+#[test]
+fn great_grandchild_fetcher_projection() {
+    let sample = sample_config();
+    let a_fetcher = shared_fetcher_from_static(sample.clone());
+
+    let e_fetcher: SharedConfigFetcher<ConfigE> = as_shared_fetcher(&a_fetcher);
+    let f_fetcher: SharedConfigFetcher<ConfigF> = as_shared_fetcher(&a_fetcher);
+
+    assert!(Arc::ptr_eq(&sample.d.e, &e_fetcher.latest_snapshot()));
+    assert!(Arc::ptr_eq(&sample.d.e.f, &f_fetcher.latest_snapshot()));
+    assert_eq!("yo", f_fetcher.latest_snapshot().foo);
+
+    // Intermediate fetchers can project the rest of the way too
+    let d_fetcher: SharedConfigFetcher<ConfigD> = as_shared_fetcher(&a_fetcher);
+    let f_from_d: SharedConfigFetcher<ConfigF> = as_shared_fetcher(&d_fetcher);
+    assert!(Arc::ptr_eq(&sample.d.e.f, &f_from_d.latest_snapshot()));
+}
+
+#[test]
+fn identity_fetcher_projection() {
+    let sample = sample_config();
+    let a_fetcher: SharedConfigFetcher<ConfigA> =
+        as_shared_fetcher(&shared_fetcher_from_static(sample.clone()));
+
+    let projected = a_fetcher.latest_snapshot();
+    assert!(*projected == *sample);
+    // The root is cloned, but its sub-configs are still shared
+    assert!(!Arc::ptr_eq(&sample, &projected));
+    assert!(Arc::ptr_eq(&sample.bar, &projected.bar));
+
+    fn uses_generic<T: AsField<ConfigF>>(config: &T) -> String {
+        config.share().foo.clone()
+    }
+    assert_eq!("yo", uses_generic(&*sample));
+    assert_eq!("yo", uses_generic(&*sample.d.e.f));
+}
+
+#[test]
+fn project_through_arc_and_ref() {
+    fn name_of_f(config: impl AsField<ConfigF>) -> String {
+        config.share().foo.clone()
+    }
+
+    let sample = sample_config();
+    assert_eq!("yo", name_of_f(sample.clone()));
+    assert_eq!("yo", name_of_f(&*sample));
+    assert_eq!("yo", name_of_f(&sample));
+
+    let b = AsField::<ConfigB>::share(&sample);
+    assert!(Arc::ptr_eq(&sample.bar, &b));
+}
+
 fn convert_from_a(a_fetcher: SharedConfigFetcher<ConfigA>) {
     uses_b(as_shared_fetcher(&a_fetcher));
     uses_c(as_shared_fetcher(&a_fetcher));
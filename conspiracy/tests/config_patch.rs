@@ -0,0 +1,91 @@
+#![cfg(feature = "json")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{
+    config_struct, full_serde,
+    patch::{merge_patch, ApplyPatch},
+};
+use serde_json::json;
+
+config_struct!(
+    #[full_serde]
+    #[conspiracy(strict)]
+    pub struct AppConfig {
+        port: u16,
+        tags: Vec<String>,
+        database:
+            #[full_serde]
+            pub struct DatabaseConfig {
+                url: String,
+                pool_size: u32,
+                timeout_ms: Option<u64>,
+            }
+    }
+);
+
+fn base() -> AppConfig {
+    AppConfig {
+        port: 8080,
+        tags: vec!["a".to_string()],
+        database: Arc::new(DatabaseConfig {
+            url: "db:5432".to_string(),
+            pool_size: 4,
+            timeout_ms: Some(100),
+        }),
+    }
+}
+
+#[test]
+fn patch_single_nested_field() {
+    let config = base();
+    let patched = config
+        .apply_patch(&json!({ "database": { "pool_size": 16 } }))
+        .unwrap();
+
+    let mut expected = base().compact();
+    expected.database.pool_size = 16;
+    assert!(*expected.arcify() == *patched);
+    // The original is untouched
+    assert_eq!(4, config.database.pool_size);
+}
+
+#[test]
+fn null_removes_and_arrays_replace() {
+    let patched = base()
+        .apply_patch(&json!({ "tags": ["b", "c"], "database": { "timeout_ms": null } }))
+        .unwrap();
+
+    assert_eq!(vec!["b", "c"], patched.tags);
+    assert_eq!(None, patched.database.timeout_ms);
+}
+
+#[test]
+fn invalid_patch_is_rejected() {
+    let config = base();
+
+    assert!(config
+        .apply_patch(&json!({ "port": "not a port" }))
+        .is_err());
+    // Strict configs still reject unknown keys
+    assert!(config.apply_patch(&json!({ "prot": 80 })).is_err());
+}
+
+#[test]
+fn rfc_7386_examples() {
+    let mut target = json!({ "a": "b", "c": { "d": "e", "f": "g" } });
+    merge_patch(&mut target, &json!({ "a": "z", "c": { "f": null } }));
+    assert_eq!(json!({ "a": "z", "c": { "d": "e" } }), target);
+
+    let mut target = json!({ "a": [{ "b": "c" }] });
+    merge_patch(&mut target, &json!({ "a": [1] }));
+    assert_eq!(json!({ "a": [1] }), target);
+
+    let mut target = json!(["a", "b"]);
+    merge_patch(&mut target, &json!({ "a": "b" }));
+    assert_eq!(json!({ "a": "b" }), target);
+
+    let mut target = json!({ "e": null });
+    merge_patch(&mut target, &json!({ "a": 1 }));
+    assert_eq!(json!({ "e": null, "a": 1 }), target);
+}
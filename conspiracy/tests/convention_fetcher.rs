@@ -0,0 +1,126 @@
+#![cfg(feature = "json")]
+
+use std::{fs, path::PathBuf};
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{load_by_convention, ConventionConfigError, PollingFetcher},
+    full_serde, ConfigFetcher,
+};
+
+config_struct!(
+    #[full_serde]
+    pub struct AppConfig {
+        pub port: u16,
+        pub verbose: bool,
+        pub database: #[full_serde] pub struct DatabaseConfig {
+            pub url: String,
+            pub pool_size: u32,
+        }
+    }
+);
+
+fn config_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("conspiracy_convention_{test_name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("config.json"),
+        r#"{ "port": 8080, "verbose": false, "database": { "url": "db:5432", "pool_size": 4 } }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("config.prod.json"),
+        r#"{ "port": 443, "database": { "pool_size": 64 } }"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn environment_overlays_base_and_missing_local_is_skipped() {
+    let dir = config_dir("missing_local");
+    let fetcher = PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "prod")).unwrap();
+    let config = fetcher.latest_snapshot();
+
+    assert_eq!(443, config.port);
+    assert!(!config.verbose);
+    assert_eq!("db:5432", config.database.url);
+    assert_eq!(64, config.database.pool_size);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn local_overlays_environment() {
+    let dir = config_dir("local");
+    fs::write(
+        dir.join("config.local.json"),
+        r#"{ "verbose": true, "database": { "pool_size": 1 } }"#,
+    )
+    .unwrap();
+    let fetcher = PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "prod")).unwrap();
+    let config = fetcher.latest_snapshot();
+
+    assert_eq!(443, config.port);
+    assert!(config.verbose);
+    assert_eq!(1, config.database.pool_size);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn local_environment_applies_local_overlay() {
+    let dir = config_dir("local_environment");
+    fs::write(dir.join("config.local.json"), r#"{ "verbose": true }"#).unwrap();
+    let fetcher = PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "local")).unwrap();
+    let config = fetcher.latest_snapshot();
+
+    assert_eq!(8080, config.port);
+    assert!(config.verbose);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_environment_uses_base() {
+    let dir = config_dir("unknown_environment");
+    let fetcher = PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "dev")).unwrap();
+
+    assert_eq!(8080, fetcher.latest_snapshot().port);
+    assert_eq!(4, fetcher.latest_snapshot().database.pool_size);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reload_keeps_last_good_config() {
+    let dir = config_dir("reload");
+    let fetcher = PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "prod")).unwrap();
+
+    fs::write(dir.join("config.local.json"), "{ not json").unwrap();
+    assert!(matches!(
+        fetcher.reload(),
+        Err(ConventionConfigError::Parse { path, .. }) if path == dir.join("config.local.json")
+    ));
+    assert_eq!(443, fetcher.latest_snapshot().port);
+
+    fs::write(dir.join("config.local.json"), r#"{ "port": 9090 }"#).unwrap();
+    fetcher.reload().unwrap();
+    assert_eq!(9090, fetcher.latest_snapshot().port);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missing_base_is_an_error() {
+    let dir = config_dir("missing_base");
+    fs::remove_file(dir.join("config.json")).unwrap();
+
+    assert!(matches!(
+        PollingFetcher::new(load_by_convention::<AppConfig>(&dir, "prod")),
+        Err(ConventionConfigError::MissingBase(path)) if path == dir.join("config.json")
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,38 @@
+use conspiracy::feature_control::{
+    current_feature_state, define_features,
+    tracker::{ConspiracyFeatureTracker, StaticFetcher},
+    FeatureEnabledError,
+};
+
+define_features!(
+    pub enum Features {
+        Foo => false,
+        Bar => true,
+        UseQuic => false,
+    }
+);
+
+define_features!(
+    pub enum OtherFeatures {
+        Baz => false,
+    }
+);
+
+#[test]
+fn retrieves_full_state() {
+    let state = Features::builder().foo(true).bar(false).build().unwrap();
+    ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_static(state)
+        .set_as_global_tracker()
+        .unwrap();
+
+    let state = current_feature_state::<Features>().unwrap();
+    assert!(state.foo);
+    assert!(!state.bar);
+    assert!(!state.use_quic);
+
+    // A feature set other than the one being tracked can't be retrieved
+    assert!(matches!(
+        current_feature_state::<OtherFeatures>(),
+        Err(FeatureEnabledError::BadCast(_))
+    ));
+}
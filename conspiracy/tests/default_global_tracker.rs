@@ -0,0 +1,30 @@
+use conspiracy::feature_control::{
+    current_feature_state, define_features, feature_enabled, use_defaults_as_global_tracker,
+    SetGlobalTrackerError,
+};
+
+define_features!(
+    pub enum Features {
+        Foo => true,
+        Bar => false,
+    }
+);
+
+#[test]
+fn defaults_are_installed_as_global_tracker() {
+    use_defaults_as_global_tracker::<Features>().unwrap();
+
+    // Unlike the macros, this doesn't fall back to the defaults under cfg(test), so it only
+    // succeeds because the tracker was installed
+    let state = current_feature_state::<Features>().unwrap();
+    assert!(state.foo);
+    assert!(!state.bar);
+
+    assert!(feature_enabled!(Features::Foo));
+    assert!(!feature_enabled!(Features::Bar));
+
+    assert!(matches!(
+        use_defaults_as_global_tracker::<Features>(),
+        Err(SetGlobalTrackerError::GlobalTrackerAlreadySet)
+    ));
+}
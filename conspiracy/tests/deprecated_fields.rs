@@ -0,0 +1,130 @@
+#![cfg(feature = "json")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{
+    config_struct, deprecated, full_serde, DeprecatedField, DeprecatedFields,
+};
+use serde_json::json;
+
+config_struct!(
+    #[full_serde]
+    #[serde(rename_all = "camelCase")]
+    pub struct AppConfig {
+        port: u16,
+        #[conspiracy(deprecated = "use `port` instead")]
+        #[serde(alias = "listen")]
+        listen_port: Option<u16>,
+        database: #[full_serde] pub struct DatabaseConfig {
+            url: String,
+            #[conspiracy(deprecated = "pooling is automatic")]
+            pool_size: Option<u32>,
+        }
+    }
+);
+
+#[test]
+fn deprecated_fields_are_listed_by_key() {
+    let listed = AppConfig::deprecated_fields()
+        .iter()
+        .map(DeprecatedField::dotted_path)
+        .collect::<Vec<_>>();
+
+    assert_eq!(vec!["listenPort", "listen", "database.pool_size"], listed);
+}
+
+#[test]
+fn only_present_fields_are_reported() {
+    let document = json!({
+        "port": 8080,
+        "listen": 8080,
+        "database": { "url": "db:5432" },
+    });
+
+    assert_eq!(
+        vec![DeprecatedField {
+            path: vec!["listen"],
+            message: "use `port` instead",
+        }],
+        deprecated::warn_present_fields::<AppConfig>(&document)
+    );
+
+    let config: AppConfig = deprecated::from_value(document).unwrap();
+    assert_eq!(Some(8080), config.listen_port);
+    assert!(
+        config.database
+            == Arc::new(DatabaseConfig {
+                url: "db:5432".to_string(),
+                pool_size: None,
+            })
+    );
+}
+
+#[test]
+fn nested_deprecated_fields_are_found() {
+    let document = json!({
+        "port": 8080,
+        "database": { "url": "db:5432", "pool_size": 4 },
+    });
+
+    let present = deprecated::present_fields::<AppConfig>(&document);
+    assert_eq!(1, present.len());
+    assert_eq!("database.pool_size", present[0].dotted_path());
+    assert_eq!("pooling is automatic", present[0].message);
+}
+
+config_struct!(
+    #[full_serde]
+    pub struct ServiceConfig {
+        name: String,
+        #[serde(flatten)]
+        limits: #[full_serde] pub struct LimitsConfig {
+            max_requests: u32,
+            #[conspiracy(deprecated = "use `max_requests` instead")]
+            rate: Option<u32>,
+        }
+    }
+);
+
+#[test]
+fn flattened_deprecated_fields_are_found_at_the_parent() {
+    let listed = ServiceConfig::deprecated_fields()
+        .iter()
+        .map(DeprecatedField::dotted_path)
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["rate"], listed);
+
+    let document = json!({ "name": "api", "max_requests": 10, "rate": 5 });
+    let present = deprecated::present_fields::<ServiceConfig>(&document);
+    assert_eq!(1, present.len());
+    assert_eq!("use `max_requests` instead", present[0].message);
+}
+
+config_struct!(
+    #[full_serde]
+    #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+    pub struct NetworkConfig {
+        #[conspiracy(deprecated = "IPv4 is going away")]
+        ipv4_addr: Option<String>,
+    }
+);
+
+#[test]
+fn keys_follow_serde_renaming() {
+    // Digits don't start a new word, matching serde
+    assert_eq!(
+        vec!["IPV4-ADDR"],
+        NetworkConfig::deprecated_fields()
+            .iter()
+            .map(DeprecatedField::dotted_path)
+            .collect::<Vec<_>>()
+    );
+
+    let document = json!({ "IPV4-ADDR": "10.0.0.1" });
+    let config: NetworkConfig = serde_json::from_value(document.clone()).unwrap();
+    assert_eq!(Some("10.0.0.1"), config.ipv4_addr.as_deref());
+    assert_eq!(
+        1,
+        deprecated::warn_present_fields::<NetworkConfig>(&document).len()
+    );
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "serde")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{
+    as_shared_fetcher, fetcher::SwapFetcher, full_serde, ConfigFetcher, ConfigStruct,
+    RestartRequired, SharedConfigFetcher,
+};
+
+mod database {
+    use conspiracy::config::{full_serde, ConfigStruct};
+
+    #[derive(ConfigStruct, Clone, PartialEq, Default)]
+    #[full_serde]
+    #[conspiracy(builder)]
+    pub struct DatabaseConfig {
+        #[conspiracy(restart)]
+        pub url: String,
+        pub pool_size: u32,
+    }
+}
+
+#[derive(ConfigStruct, Clone, PartialEq, Default)]
+#[full_serde]
+pub struct AppConfig {
+    #[conspiracy(restart)]
+    pub port: u16,
+    pub verbose: bool,
+    #[conspiracy(nested)]
+    pub database: Arc<database::DatabaseConfig>,
+}
+
+fn app_config(url: &str) -> Arc<AppConfig> {
+    Arc::new(AppConfig {
+        port: 8080,
+        verbose: false,
+        database: Arc::new(database::DatabaseConfig {
+            url: url.to_string(),
+            pool_size: 4,
+        }),
+    })
+}
+
+#[test]
+fn sub_fetcher_follows_the_parent() {
+    let fetcher = Arc::new(SwapFetcher::new(app_config("db:5432")));
+    let database: SharedConfigFetcher<database::DatabaseConfig> = as_shared_fetcher(&fetcher);
+    assert_eq!("db:5432", database.latest_snapshot().url);
+
+    fetcher.store(app_config("replica:5432"));
+    assert_eq!("replica:5432", database.latest_snapshot().url);
+}
+
+#[test]
+fn builder_is_generated_for_derived_structs() {
+    let database = database::DatabaseConfig::builder()
+        .url("db:5432")
+        .pool_size(4)
+        .build()
+        .unwrap();
+
+    assert!(app_config("db:5432").database == Arc::new(database));
+}
+
+#[test]
+fn restart_reasons_include_nested_fields() {
+    let before = app_config("db:5432");
+
+    let mut after = (*before).clone();
+    after.verbose = true;
+    assert!(!before.restart_required(&Arc::new(after)));
+
+    let after = app_config("replica:5432");
+    assert!(before.restart_required(&after));
+    assert_eq!(vec!["database"], before.restart_reasons(&after));
+}
+
+#[test]
+fn compact_round_trip() {
+    let mut compact = AppConfig::default().compact();
+    compact.database.pool_size = 16;
+    let config = compact.arcify();
+    assert_eq!(16, config.database.pool_size);
+
+    let json = r#"{ "port": 80, "verbose": true, "database": { "url": "db", "pool_size": 2 } }"#;
+    let compact: CompactAppConfig = serde_json::from_str(json).unwrap();
+    assert!(compact.arcify() == Arc::new(serde_json::from_str::<AppConfig>(json).unwrap()));
+}
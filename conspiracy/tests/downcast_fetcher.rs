@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use conspiracy::config::{
+    as_shared_fetcher, config_struct, fetcher::SwapFetcher, into_any_shared_fetcher,
+    AnySharedConfigFetcher, ConfigFetcher, SharedConfigFetcher,
+};
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+        database: pub struct DatabaseConfig {
+            url: String,
+        }
+    }
+);
+
+fn config(port: u16) -> Arc<AppConfig> {
+    Arc::new(AppConfig {
+        port,
+        database: Arc::new(DatabaseConfig {
+            url: "db:5432".to_string(),
+        }),
+    })
+}
+
+#[test]
+fn erased_fetcher_downcasts_to_store() {
+    let shared: AnySharedConfigFetcher<AppConfig> =
+        into_any_shared_fetcher(SwapFetcher::new(config(1)));
+
+    shared
+        .as_any()
+        .downcast_ref::<SwapFetcher<AppConfig>>()
+        .unwrap()
+        .store(config(2));
+    assert_eq!(2, shared.latest_snapshot().port);
+
+    let swap = shared
+        .clone()
+        .into_any()
+        .downcast::<SwapFetcher<AppConfig>>()
+        .ok()
+        .unwrap();
+    swap.store(config(3));
+    assert_eq!(3, shared.latest_snapshot().port);
+}
+
+#[test]
+fn only_the_outermost_fetcher_is_visible() {
+    let shared = Arc::new(SwapFetcher::new(config(1)));
+    let database: AnySharedConfigFetcher<DatabaseConfig> =
+        into_any_shared_fetcher(as_shared_fetcher(&shared));
+
+    assert!(database
+        .as_any()
+        .downcast_ref::<SwapFetcher<DatabaseConfig>>()
+        .is_none());
+    assert!(database
+        .as_any()
+        .downcast_ref::<SharedConfigFetcher<DatabaseConfig>>()
+        .is_some());
+}
+
+#[test]
+fn downcastable_fetcher_converts_to_shared() {
+    let shared: AnySharedConfigFetcher<AppConfig> =
+        into_any_shared_fetcher(SwapFetcher::new(config(1)));
+    let fetcher: SharedConfigFetcher<AppConfig> = shared.clone().into_shared();
+
+    shared
+        .as_any()
+        .downcast_ref::<SwapFetcher<AppConfig>>()
+        .unwrap()
+        .store(config(2));
+    assert_eq!(2, fetcher.latest_snapshot().port);
+}
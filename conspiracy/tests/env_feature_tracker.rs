@@ -0,0 +1,98 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use conspiracy::feature_control::{
+    current_feature_state, define_features, feature_enabled, tracker::EnvFeatureTracker,
+};
+
+define_features!(
+    pub enum Features {
+        UseQuic => false,
+        VerboseLogging => true,
+        Unset => true,
+    }
+);
+
+// The process environment is shared by every test thread, so the tests read from their own maps
+
+type Env = Arc<Mutex<HashMap<String, String>>>;
+
+fn env(vars: &[(&str, &str)]) -> Env {
+    Arc::new(Mutex::new(
+        vars.iter()
+            .map(|(var, value)| (var.to_string(), value.to_string()))
+            .collect(),
+    ))
+}
+
+fn lookup(env: &Env) -> impl Fn(&str) -> Option<String> + Send + Sync + 'static {
+    let env = env.clone();
+    move |var| env.lock().unwrap().get(var).cloned()
+}
+
+#[test]
+fn env_overrides_defaults() {
+    let env = env(&[
+        ("ENV_TRACKER_USE_QUIC", "true"),
+        ("ENV_TRACKER_VERBOSE_LOGGING", "false"),
+    ]);
+
+    EnvFeatureTracker::<Features>::new_with_lookup("ENV_TRACKER", lookup(&env))
+        .unwrap()
+        .set_as_global_tracker()
+        .unwrap();
+
+    assert!(feature_enabled!(Features::UseQuic));
+    assert!(!feature_enabled!(Features::VerboseLogging));
+    assert!(feature_enabled!(Features::Unset));
+
+    // Read once at construction, later changes don't apply
+    env.lock()
+        .unwrap()
+        .insert("ENV_TRACKER_USE_QUIC".to_string(), "false".to_string());
+    assert!(current_feature_state::<Features>().unwrap().use_quic);
+}
+
+#[test]
+fn invalid_value_is_rejected() {
+    let env = env(&[("ENV_TRACKER_USE_QUIC", "yes")]);
+
+    let error = EnvFeatureTracker::<Features>::new_with_lookup("ENV_TRACKER", lookup(&env))
+        .err()
+        .unwrap();
+    assert_eq!(
+        "Environment variable `ENV_TRACKER_USE_QUIC` must be `true` or `false`, found `yes`",
+        error.to_string()
+    );
+}
+
+#[test]
+fn live_tracker_rereads_env() {
+    use conspiracy::feature_control::{AsFeature, FeatureTracker};
+
+    let env = env(&[]);
+    let tracker = EnvFeatureTracker::<Features>::live_with_lookup("ENV_TRACKER", lookup(&env));
+    assert_eq!("ENV_TRACKER_USE_QUIC", tracker.var_name(&Features::UseQuic));
+
+    let use_quic = |tracker: &EnvFeatureTracker<Features>| {
+        tracker
+            .static_feature_state()
+            .downcast::<FeaturesState>()
+            .unwrap()
+            .as_feature(Features::UseQuic)
+    };
+    let set_use_quic = |value: &str| {
+        env.lock()
+            .unwrap()
+            .insert("ENV_TRACKER_USE_QUIC".to_string(), value.to_string());
+    };
+
+    assert!(!use_quic(&tracker));
+    set_use_quic("true");
+    assert!(use_quic(&tracker));
+    // Invalid values fall back to the default
+    set_use_quic("yes");
+    assert!(!use_quic(&tracker));
+}
@@ -0,0 +1,98 @@
+#![cfg(all(feature = "env", feature = "serde"))]
+
+use conspiracy::config::{config_struct, fetcher::EnvConfigFetcher, full_serde, ConfigFetcher};
+
+config_struct!(
+    #[full_serde]
+    pub struct EnvConfig {
+        name: String,
+        max_connections: u32,
+        web_server:
+            #[full_serde]
+            pub struct EnvWebServer {
+                port: u16,
+                tls: bool,
+        }
+    }
+);
+
+#[test]
+fn reads_nested_config_from_environment() {
+    // Injected rather than set on the process environment, which is shared by every test thread
+    let fetcher = EnvConfigFetcher::<EnvConfig>::from_vars(
+        "ENV_FETCHER_TEST",
+        [
+            ("ENV_FETCHER_TEST_NAME".to_string(), "1234".to_string()),
+            (
+                "ENV_FETCHER_TEST_MAX_CONNECTIONS".to_string(),
+                "16".to_string(),
+            ),
+            (
+                "ENV_FETCHER_TEST_WEB_SERVER__PORT".to_string(),
+                "8080".to_string(),
+            ),
+            (
+                "ENV_FETCHER_TEST_WEB_SERVER__TLS".to_string(),
+                "true".to_string(),
+            ),
+            // Variables without the prefix are ignored
+            ("OTHER_NAME".to_string(), "other".to_string()),
+        ],
+    )
+    .unwrap();
+    let config = fetcher.latest_snapshot();
+
+    // Numeric looking values still populate string fields
+    assert_eq!("1234", config.name);
+    assert_eq!(16, config.max_connections);
+    assert_eq!(8080, config.web_server.port);
+    assert!(config.web_server.tls);
+}
+
+#[test]
+fn invalid_value_is_rejected() {
+    let result = EnvConfigFetcher::<EnvConfig>::from_vars(
+        "APP",
+        [
+            ("APP_NAME".to_string(), "foo".to_string()),
+            ("APP_MAX_CONNECTIONS".to_string(), "many".to_string()),
+            ("APP_WEB_SERVER__PORT".to_string(), "8080".to_string()),
+            ("APP_WEB_SERVER__TLS".to_string(), "false".to_string()),
+        ],
+    );
+
+    assert!(result.is_err());
+}
+
+config_struct!(
+    #[full_serde]
+    pub struct EnvWithSequence {
+        name: String,
+        #[serde(default)]
+        hosts: Vec<String>,
+    }
+);
+
+#[test]
+fn sequence_is_rejected_naming_the_variable() {
+    let error = EnvConfigFetcher::<EnvWithSequence>::from_vars(
+        "APP",
+        [
+            ("APP_NAME".to_string(), "foo".to_string()),
+            ("APP_HOSTS".to_string(), "a,b".to_string()),
+        ],
+    )
+    .err()
+    .unwrap();
+    assert!(error
+        .to_string()
+        .contains("`APP_HOSTS` can't populate a sequence"));
+
+    // Sequence fields can still be left out of the environment
+    let fetcher = EnvConfigFetcher::<EnvWithSequence>::from_vars(
+        "APP",
+        [("APP_NAME".to_string(), "foo".to_string())],
+    )
+    .unwrap();
+    assert!(fetcher.latest_snapshot().hosts.is_empty());
+}
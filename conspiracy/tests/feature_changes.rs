@@ -0,0 +1,53 @@
+#![cfg(feature = "tokio")]
+
+use std::{sync::Arc, time::Duration};
+
+use conspiracy::{
+    config::fetcher::SwapFetcher,
+    feature_control::{define_features, tracker::ConspiracyFeatureTracker},
+};
+use tokio_stream::StreamExt;
+
+define_features!(
+    pub enum Features {
+        Checkout => false,
+        Search => true,
+    }
+);
+
+#[tokio::test]
+async fn stream_yields_flipped_state() {
+    let state = Arc::new(SwapFetcher::new(Arc::new(FeaturesState::default())));
+    let tracker = ConspiracyFeatureTracker::<Features, _>::new(state.clone());
+    let mut changes = tracker.feature_changes(Duration::from_millis(5));
+
+    let initial = changes.next().await.unwrap();
+    assert!(!initial.checkout);
+    assert!(initial.search);
+
+    state.store(Arc::new(
+        Features::builder().checkout(true).build().unwrap(),
+    ));
+    let flipped = tokio::time::timeout(Duration::from_secs(5), changes.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(flipped.checkout);
+    assert!(flipped.search);
+}
+
+#[tokio::test]
+async fn unchanged_state_isnt_yielded() {
+    let state = Arc::new(SwapFetcher::new(Arc::new(FeaturesState::default())));
+    let tracker = ConspiracyFeatureTracker::<Features, _>::new(state.clone());
+    let mut changes = tracker.feature_changes(Duration::from_millis(5));
+    changes.next().await.unwrap();
+
+    // A new snapshot with equal values isn't a change
+    state.store(Arc::new(FeaturesState::default()));
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), changes.next())
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,45 @@
+use conspiracy::feature_control::tracker::{ConspiracyFeatureTracker, StaticFetcher};
+use conspiracy_macros::{feature_enabled_all, feature_enabled_any};
+
+mod generated {
+    use conspiracy_macros::define_features;
+
+    define_features!(
+        pub enum Features {
+            Foo => false,
+            Bar => false,
+            Baz => false,
+        }
+    );
+}
+
+use crate::generated::{Features, FeaturesState};
+
+// noinspection RsUnnecessaryQualifications
+#[test]
+fn composite_checks_share_the_global_state() {
+    let state = Features::builder().foo(true).bar(true).build().unwrap();
+    ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_static(state)
+        .set_as_global_tracker()
+        .unwrap();
+
+    // Foo and Bar are enabled, Baz is left disabled
+    assert!(feature_enabled_all!(Features::Foo, Features::Bar));
+    assert!(!feature_enabled_all!(
+        Features::Foo,
+        Features::Bar,
+        Features::Baz
+    ));
+    assert!(feature_enabled_any!(Features::Baz, Features::Foo));
+    assert!(!feature_enabled_any!(Features::Baz));
+
+    // Different paths to the same enum
+    assert!(feature_enabled_all!(
+        Features::Foo,
+        crate::generated::Features::Bar
+    ));
+    assert!(!feature_enabled_any!(
+        generated::Features::Baz,
+        crate::generated::Features::Baz
+    ));
+}
@@ -0,0 +1,30 @@
+use conspiracy_macros::{define_features, feature_enabled, feature_enabled_or_default};
+
+define_features!(
+    pub enum Features {
+        Bar => false,
+        UseQuic => true,
+    }
+);
+
+#[test]
+fn panic_opt_out_uses_default() {
+    assert_eq!(
+        FeaturesState::default_bar(),
+        feature_enabled!(Features::Bar, panic = false)
+    );
+    assert_eq!(
+        FeaturesState::default_use_quic(),
+        feature_enabled!(Features::UseQuic, panic = false)
+    );
+}
+
+#[test]
+fn multi_word_features_use_their_default() {
+    // The default is looked up by the snake case field name, not the lowercased variant
+    assert_eq!(
+        FeaturesState::default_use_quic(),
+        feature_enabled_or_default!(Features::UseQuic)
+    );
+    assert!(feature_enabled!(Features::UseQuic));
+}
@@ -0,0 +1,93 @@
+#![cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+
+use std::{fs, path::PathBuf};
+
+use conspiracy::feature_control::{
+    define_features,
+    tracker::{ConspiracyFeatureTracker, FeatureFileError, StaticFetcher},
+    FeatureTracker,
+};
+
+define_features!(
+    pub enum Features {
+        UseQuic => false,
+        VerboseLogging => true,
+        Unset => true,
+    }
+);
+
+fn load(file_name: &str, contents: &str) -> Result<FeaturesState, FeatureFileError> {
+    let path = temp_file(file_name);
+    fs::write(&path, contents).unwrap();
+    let tracker = ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_file(&path);
+    fs::remove_file(&path).unwrap();
+
+    Ok(tracker?
+        .static_feature_state()
+        .downcast_ref::<FeaturesState>()
+        .unwrap()
+        .clone())
+}
+
+fn temp_file(file_name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("conspiracy_feature_file_{file_name}"))
+}
+
+fn expected() -> FeaturesState {
+    Features::builder()
+        .use_quic(true)
+        .verbose_logging(false)
+        .build()
+        .unwrap()
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn loads_json() {
+    let state = load(
+        "state.json",
+        r#"{ "use_quic": true, "verbose_logging": false }"#,
+    )
+    .unwrap();
+    assert!(state == expected());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn loads_toml() {
+    let state = load("state.toml", "use_quic = true\nverbose_logging = false\n").unwrap();
+    assert!(state == expected());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn loads_yaml() {
+    let state = load("state.yml", "use_quic: true\nverbose_logging: false\n").unwrap();
+    assert!(state == expected());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn invalid_file_is_rejected() {
+    let error = load("invalid.json", r#"{ "use_quic": "yes" }"#)
+        .err()
+        .unwrap();
+    assert!(matches!(error, FeatureFileError::Json(_)));
+}
+
+#[test]
+fn unknown_extension_is_rejected() {
+    let error = load("state.ini", "use_quic = true").err().unwrap();
+    assert!(matches!(error, FeatureFileError::UnsupportedFormat(_)));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn missing_file_is_rejected() {
+    let error = ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_file(
+        temp_file("missing.json"),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(error, FeatureFileError::Io(_)));
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use conspiracy::feature_control::{OverridesParseError, UnknownFeatureError, UnknownFeatures};
 use conspiracy_macros::define_features;
 use conspiracy_theories::config::RestartRequired;
 
@@ -38,6 +41,85 @@ define_features!(
     }
 );
 
+define_features!(
+    pub enum ConstDefaults {
+        Literal => true,
+        Platform => cfg!(unix),
+        Composite => cfg!(unix) && !cfg!(windows),
+        FromConstFn => quic_enabled(),
+    }
+);
+
+const fn quic_enabled() -> bool {
+    cfg!(target_pointer_width = "64")
+}
+
+define_features!(
+    pub enum WithRequired {
+        #[conspiracy(required)]
+        UseQuic => false,
+        #[conspiracy(required, restart)]
+        ReplicaReads => true,
+        VerboseLogging => false,
+    }
+);
+
+define_features!(
+    pub enum Documented {
+        /// Route reads to the closest replica.
+        ///
+        /// Falls back to the primary when no replica is healthy.
+        #[conspiracy(owner = "storage-team")]
+        ReplicaReads => false,
+        #[conspiracy(restart)]
+        Undocumented => false,
+    }
+);
+
+define_features!(
+    #[conspiracy(restart_group = "networking")]
+    pub enum Grouped {
+        #[conspiracy(group = "networking")]
+        UseQuic => false,
+        #[conspiracy(group = "networking")]
+        UseHttp => true,
+        #[conspiracy(group = "logging")]
+        VerboseLogging => false,
+        Ungrouped => false,
+    }
+);
+
+define_features!(
+    #[derive(Debug, Hash)]
+    pub enum UserDerived {
+        Foo => false,
+        Bar => false,
+    }
+);
+
+const LITERAL: bool = ConstDefaultsState::default_literal();
+const _: () = assert!(LITERAL);
+const PLATFORM: bool = ConstDefaultsState::default_platform();
+const COMPOSITE: bool = ConstDefaultsState::default_composite();
+const FROM_CONST_FN: bool = ConstDefaultsState::default_from_const_fn();
+
+#[test]
+fn const_defaults() {
+    assert_eq!(cfg!(unix), PLATFORM);
+    assert_eq!(cfg!(unix), COMPOSITE);
+    assert_eq!(cfg!(target_pointer_width = "64"), FROM_CONST_FN);
+    assert_eq!(
+        ConstDefaultsState::builder()
+            .literal(LITERAL)
+            .platform(PLATFORM)
+            .composite(COMPOSITE)
+            .from_const_fn(FROM_CONST_FN)
+            .build()
+            .unwrap(),
+        ConstDefaultsState::default()
+    );
+}
+
 #[test]
 fn no_change_no_restart() {
     assert!(
@@ -81,3 +163,252 @@ fn tracked_change_restart() {
     other.cow = !other.cow;
     assert!(AllRequireRestartState::default().restart_required(&other));
 }
+
+#[test]
+fn restart_reasons() {
+    let mut other = SomeRequireRestartState::default();
+    other.bar = !other.bar;
+    other.cow = !other.cow;
+    assert_eq!(
+        vec!["cow"],
+        SomeRequireRestartState::default().restart_reasons(&other)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn state_round_trip() {
+    let state = SomeRequireRestartState::builder()
+        .foo(true)
+        .cow(false)
+        .build()
+        .unwrap();
+    let json = serde_json::to_string(&state).unwrap();
+
+    assert_eq!(state, serde_json::from_str(&json).unwrap());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn state_missing_features_load_defaults() {
+    // As if persisted before `Bar` and `Cow` were added
+    let state: SomeRequireRestartState = serde_json::from_str(r#"{ "foo": true }"#).unwrap();
+
+    assert_eq!(
+        SomeRequireRestartState::builder()
+            .foo(true)
+            .build()
+            .unwrap(),
+        state
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn state_removed_features_are_ignored() {
+    // As if persisted before `Removed` was deleted
+    let state: SomeRequireRestartState =
+        serde_json::from_str(r#"{ "foo": true, "removed": { "nested": [1, 2] } }"#).unwrap();
+
+    assert_eq!(
+        SomeRequireRestartState::builder()
+            .foo(true)
+            .build()
+            .unwrap(),
+        state
+    );
+}
+
+#[test]
+fn merge_partial_map() {
+    let partial = HashMap::from([("bar".to_string(), true)]);
+    let overrides = SomeRequireRestartOverrides::from_map(partial, UnknownFeatures::Error).unwrap();
+
+    let mut state = SomeRequireRestartState::default();
+    state.merge(&overrides);
+
+    assert_eq!(
+        SomeRequireRestartState::builder()
+            .bar(true)
+            .build()
+            .unwrap(),
+        state
+    );
+}
+
+#[test]
+fn state_from_partial_variant_map() {
+    let partial = HashMap::from([
+        (SomeRequireRestart::Foo, true),
+        (SomeRequireRestart::Cow, true),
+    ]);
+    let state = SomeRequireRestartState::from_map(partial);
+
+    assert!(state.foo);
+    assert_eq!(SomeRequireRestartState::default_bar(), state.bar);
+    assert!(state.cow);
+    assert_eq!(
+        SomeRequireRestartState::default(),
+        SomeRequireRestartState::from_map(HashMap::new())
+    );
+}
+
+#[test]
+fn merge_partial_map_unknown_names() {
+    let partial = [("bar", true), ("removed", true)];
+
+    assert!(SomeRequireRestartOverrides::from_map(partial, UnknownFeatures::Error).is_err());
+    assert_eq!(
+        SomeRequireRestartOverrides::default().bar(true),
+        SomeRequireRestartOverrides::from_map(partial, UnknownFeatures::Ignore).unwrap()
+    );
+}
+
+#[test]
+fn overrides_str_applies_on_top_of_defaults() {
+    let state = SomeRequireRestartState::from_overrides_str(" foo=true, cow = false,").unwrap();
+    assert_eq!(
+        SomeRequireRestartState::builder()
+            .foo(true)
+            .cow(false)
+            .build()
+            .unwrap(),
+        state
+    );
+
+    assert_eq!(
+        SomeRequireRestartState::default(),
+        SomeRequireRestartState::from_overrides_str("").unwrap()
+    );
+}
+
+#[test]
+fn overrides_str_rejects_invalid_input() {
+    assert!(matches!(
+        SomeRequireRestartState::from_overrides_str("foo=true,removed=true"),
+        Err(OverridesParseError::UnknownFeature(UnknownFeatureError(name))) if name == "removed"
+    ));
+    assert!(matches!(
+        SomeRequireRestartState::from_overrides_str("foo=yes"),
+        Err(OverridesParseError::InvalidValue { feature, value }) if feature == "foo" && value == "yes"
+    ));
+    assert!(matches!(
+        SomeRequireRestartState::from_overrides_str("foo"),
+        Err(OverridesParseError::Malformed(entry)) if entry == "foo"
+    ));
+}
+
+#[test]
+fn description_and_owner() {
+    assert_eq!(
+        "Route reads to the closest replica.\n\nFalls back to the primary when no replica is healthy.",
+        Documented::ReplicaReads.description()
+    );
+    assert_eq!(Some("storage-team"), Documented::ReplicaReads.owner());
+
+    assert_eq!("", Documented::Undocumented.description());
+    assert_eq!(None, Documented::Undocumented.owner());
+}
+
+#[test]
+fn sort_features_in_declaration_order() {
+    let mut features = vec![
+        Grouped::Ungrouped,
+        Grouped::UseHttp,
+        Grouped::VerboseLogging,
+        Grouped::UseQuic,
+        Grouped::UseHttp,
+    ];
+    features.sort();
+    features.dedup();
+
+    assert_eq!(Grouped::ALL.to_vec(), features);
+    assert!(Grouped::UseQuic < Grouped::Ungrouped);
+}
+
+#[test]
+fn user_derives_are_not_duplicated() {
+    let features = UserDerived::iter().collect::<std::collections::HashSet<_>>();
+    assert_eq!(2, features.len());
+    assert_eq!("Foo", format!("{:?}", UserDerived::Foo));
+}
+
+#[test]
+fn iterate_features_in_declaration_order() {
+    let features = Documented::iter().collect::<Vec<_>>();
+
+    assert_eq!(2, features.len());
+    assert_eq!(Documented::ALL.len(), features.len());
+    assert!(matches!(
+        features[..],
+        [Documented::ReplicaReads, Documented::Undocumented]
+    ));
+    assert_eq!(
+        vec!["replica_reads", "undocumented"],
+        features.iter().map(Documented::name).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn restart_group_members_require_restart() {
+    let base = GroupedState::default();
+
+    let quic = GroupedState::builder().use_quic(true).build().unwrap();
+    assert!(base.restart_required(&quic));
+
+    let http = GroupedState::builder().use_http(false).build().unwrap();
+    assert!(base.restart_required(&http));
+
+    // Groups that aren't restart groups, and ungrouped features, don't require a restart
+    let logging = GroupedState::builder()
+        .verbose_logging(true)
+        .build()
+        .unwrap();
+    assert!(!base.restart_required(&logging));
+
+    let ungrouped = GroupedState::builder().ungrouped(true).build().unwrap();
+    assert!(!base.restart_required(&ungrouped));
+}
+
+#[test]
+fn required_features_must_be_set() {
+    let error = WithRequiredState::builder()
+        .verbose_logging(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(vec!["UseQuic", "ReplicaReads"], error.0);
+
+    let error = WithRequiredState::builder()
+        .use_quic(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(vec!["ReplicaReads"], error.0);
+
+    // Setting a required feature to its default still counts as explicit
+    let state = WithRequiredState::builder()
+        .use_quic(true)
+        .replica_reads(WithRequiredState::default_replica_reads())
+        .build()
+        .unwrap();
+    assert!(state.use_quic);
+    assert!(state.replica_reads);
+    assert!(!state.verbose_logging);
+}
+
+#[test]
+fn build_error_names_missing_required_features() {
+    let error = WithRequiredState::builder()
+        .verbose_logging(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        "Required features were not set: UseQuic, ReplicaReads",
+        error.to_string()
+    );
+}
+
+#[test]
+fn build_without_required_features() {
+    // Fallible either way, so call sites don't depend on whether any feature is required
+    assert!(GroupedState::builder().ungrouped(true).build().is_ok());
+}
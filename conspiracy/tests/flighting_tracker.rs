@@ -0,0 +1,99 @@
+use std::{collections::HashSet, sync::Arc};
+
+use conspiracy::{
+    config::fetcher::SwapFetcher,
+    feature_control::{
+        define_features,
+        tracker::{FlightingFeatureTracker, RolloutConfig},
+    },
+};
+
+define_features!(
+    pub enum Features {
+        NewCheckout => false,
+        DarkMode => true,
+    }
+);
+
+fn enabled_contexts(
+    tracker: &FlightingFeatureTracker<
+        Features,
+        impl conspiracy::config::ConfigFetcher<FeaturesState>,
+        Arc<SwapFetcher<RolloutConfig>>,
+    >,
+) -> HashSet<u32> {
+    (0..10_000)
+        .filter(|context| tracker.is_enabled(Features::NewCheckout, context))
+        .collect()
+}
+
+#[test]
+fn ramping_up_never_disables_a_context() {
+    let rollout = Arc::new(SwapFetcher::new(Arc::new(RolloutConfig::default())));
+    let tracker = FlightingFeatureTracker::<Features, _, _>::from_default(rollout.clone());
+
+    // No percentage, falls back to the base state
+    assert!(enabled_contexts(&tracker).is_empty());
+
+    let mut previous = HashSet::new();
+    for percentage in [0.0, 5.0, 20.0, 50.0, 100.0] {
+        rollout.store(Arc::new(
+            RolloutConfig::default().with("new_checkout", percentage),
+        ));
+        let enabled = enabled_contexts(&tracker);
+
+        assert!(
+            previous.is_subset(&enabled),
+            "ramping to {percentage}% disabled a previously enabled context"
+        );
+        let expected = (percentage * 100.0) as usize;
+        assert!(
+            enabled.len().abs_diff(expected) < 300,
+            "{percentage}% enabled {} of 10000 contexts",
+            enabled.len()
+        );
+        previous = enabled;
+    }
+
+    assert_eq!(previous.len(), 10_000);
+}
+
+#[test]
+fn buckets_are_per_feature() {
+    let rollout = RolloutConfig::default()
+        .with("new_checkout", 50.0)
+        .with("dark_mode", 50.0);
+    let tracker = FlightingFeatureTracker::<Features, _, _>::from_default(Arc::new(
+        SwapFetcher::new(Arc::new(rollout)),
+    ));
+
+    let differing = (0..1_000)
+        .filter(|context| {
+            tracker.is_enabled(Features::NewCheckout, context)
+                != tracker.is_enabled(Features::DarkMode, context)
+        })
+        .count();
+    assert!(differing > 0);
+}
+
+#[test]
+fn unflighted_features_use_base_state() {
+    let tracker = FlightingFeatureTracker::<Features, _, _>::from_default(Arc::new(
+        SwapFetcher::new(Arc::new(RolloutConfig::default().with("new_checkout", 0.0))),
+    ));
+
+    assert!(!tracker.is_enabled(Features::NewCheckout, &"anyone"));
+    assert!(tracker.is_enabled(Features::DarkMode, &"anyone"));
+}
+
+#[test]
+fn buckets_are_stable() {
+    let tracker = FlightingFeatureTracker::<Features, _, _>::from_default(Arc::new(
+        SwapFetcher::new(Arc::new(RolloutConfig::default())),
+    ));
+
+    // Pinned, since a change would reshuffle which contexts have a feature. Integers hash their
+    // little-endian bytes, so this holds on every platform.
+    assert_eq!(352, tracker.bucket(Features::NewCheckout, "tenant-42"));
+    assert_eq!(4953, tracker.bucket(Features::NewCheckout, &42u64));
+}
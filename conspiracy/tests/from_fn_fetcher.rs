@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use conspiracy::config::{fetcher::FromFn, ConfigFetcher};
+
+/// Deliberately not `Clone`, only the closure needs to be
+struct Handle {
+    path: String,
+}
+
+#[test]
+fn clones_without_cloning_the_config() {
+    let handle = Arc::new(Handle {
+        path: "/tmp/foo".to_string(),
+    });
+    let fetcher = FromFn::new(move || handle.clone());
+    let clone = fetcher.clone();
+
+    assert_eq!("/tmp/foo", clone.latest_snapshot().path);
+    assert!(Arc::ptr_eq(
+        &fetcher.latest_snapshot(),
+        &clone.latest_snapshot()
+    ));
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use conspiracy::config::{
+    as_shared_fetcher, config_struct, fetcher::GlobalConfigFetcher, shared_fetcher_from_fn,
+    ConfigFetcher, SharedConfigFetcher,
+};
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+        database: pub struct DatabaseConfig {
+            name: String,
+        }
+    }
+);
+
+fn app_config(port: u16) -> Arc<AppConfig> {
+    Arc::new(AppConfig {
+        port,
+        database: Arc::new(DatabaseConfig {
+            name: "prod".to_string(),
+        }),
+    })
+}
+
+static INITIALIZED: GlobalConfigFetcher<AppConfig> = GlobalConfigFetcher::new();
+static UNINITIALIZED: GlobalConfigFetcher<AppConfig> = GlobalConfigFetcher::new();
+
+#[test]
+fn init_then_read() {
+    assert!(INITIALIZED.try_latest_snapshot().is_none());
+
+    let config = app_config(8080);
+    INITIALIZED.init(config.clone()).unwrap();
+    assert!(Arc::ptr_eq(&config, &INITIALIZED.latest_snapshot()));
+
+    // Second init is rejected, the original config is kept
+    assert!(INITIALIZED.init(app_config(9090)).is_err());
+    assert_eq!(8080, INITIALIZED.latest_snapshot().port);
+    assert_eq!(8080, INITIALIZED.try_latest_snapshot().unwrap().port);
+
+    // Composes like any other fetcher
+    let fetcher = shared_fetcher_from_fn(|| INITIALIZED.latest_snapshot());
+    let database: SharedConfigFetcher<DatabaseConfig> = as_shared_fetcher(&fetcher);
+    assert_eq!("prod", database.latest_snapshot().name);
+}
+
+#[test]
+#[should_panic(expected = "read before `init`")]
+fn read_before_init_panics() {
+    UNINITIALIZED.latest_snapshot();
+}
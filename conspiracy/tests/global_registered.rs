@@ -52,7 +52,8 @@ fn set_inverse_defaults_global() -> Result<(), SetGlobalTrackerError> {
     let state = Features::builder()
         .foo(!FeaturesState::default_foo())
         .bar(!FeaturesState::default_bar())
-        .build();
+        .build()
+        .unwrap();
 
     ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_static(state)
         .set_as_global_tracker()
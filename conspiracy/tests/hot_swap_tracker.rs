@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use conspiracy::{
+    config::fetcher::SwapFetcher,
+    feature_control::{
+        current_feature_state, define_features, feature_enabled, feature_enabled_all,
+        tracker::ConspiracyFeatureTracker,
+    },
+};
+
+define_features!(
+    pub enum Features {
+        Foo => false,
+        Bar => true,
+    }
+);
+
+#[test]
+fn store_changes_global_feature_state() {
+    let state = Arc::new(SwapFetcher::new(Arc::new(FeaturesState::default())));
+    ConspiracyFeatureTracker::<Features, _>::new(state.clone())
+        .set_as_global_tracker()
+        .unwrap();
+
+    assert!(!feature_enabled!(Features::Foo));
+    assert!(feature_enabled!(Features::Bar));
+
+    // The global tracker is already set, but pushing new state through the fetcher still applies
+    state.store(Arc::new(Features::builder().foo(true).build().unwrap()));
+    assert!(feature_enabled!(Features::Foo));
+    assert!(feature_enabled_all!(Features::Foo, Features::Bar));
+
+    state.store(Arc::new(
+        Features::builder().foo(true).bar(false).build().unwrap(),
+    ));
+    assert!(!feature_enabled!(Features::Bar));
+    assert!(!current_feature_state::<Features>().unwrap().bar);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "json")]
+
+use conspiracy::config::{
+    config_struct, full_serde,
+    interpolate::{interpolate_env, interpolate_with, InterpolationError},
+};
+use serde_json::json;
+
+config_struct!(
+    #[full_serde]
+    pub struct AppConfig {
+        connection_string: String,
+        port: u16,
+        hosts: Vec<String>,
+    }
+);
+
+#[test]
+fn present_var_is_substituted() {
+    let lookup = |var: &str| (var == "DB_HOST").then(|| "db.internal".to_string());
+
+    let mut raw = json!({
+        "connection_string": "postgres://${DB_HOST}/app",
+        "port": 5432,
+        "hosts": ["${DB_HOST}", "static"],
+    });
+    interpolate_with(&mut raw, lookup).unwrap();
+
+    let config: AppConfig = serde_json::from_value(raw).unwrap();
+    assert_eq!("postgres://db.internal/app", config.connection_string);
+    assert_eq!(vec!["db.internal", "static"], config.hosts);
+}
+
+// Only reads the environment, a variable that's never set
+#[test]
+fn missing_var_uses_fallback() {
+    let mut raw = json!({ "url": "http://${INTERPOLATE_TEST_UNSET:-localhost:8080}/" });
+    interpolate_env(&mut raw).unwrap();
+
+    assert_eq!(json!({ "url": "http://localhost:8080/" }), raw);
+}
+
+#[test]
+fn missing_var_without_fallback_errors() {
+    let mut raw = json!({ "database": { "hosts": ["ok", "${INTERPOLATE_TEST_UNSET}"] } });
+    let error = interpolate_with(&mut raw, |_| None).unwrap_err();
+
+    assert!(matches!(
+        &error,
+        InterpolationError::MissingVar { var, path }
+            if var == "INTERPOLATE_TEST_UNSET" && path == "database.hosts[1]"
+    ));
+}
+
+#[test]
+fn only_strings_are_touched() {
+    let lookup = |var: &str| (var == "X").then(|| "1".to_string());
+
+    let mut raw = json!({ "${X}": "${X}", "n": 5, "b": true, "price": "$5", "literal": "$${X}" });
+    interpolate_with(&mut raw, lookup).unwrap();
+
+    assert_eq!(
+        json!({ "${X}": "1", "n": 5, "b": true, "price": "$5", "literal": "${X}" }),
+        raw
+    );
+}
+
+#[test]
+fn unterminated_placeholder_errors() {
+    let mut raw = json!({ "url": "${UNTERMINATED" });
+
+    assert!(matches!(
+        interpolate_with(&mut raw, |_| None),
+        Err(InterpolationError::Unterminated { path }) if path == "url"
+    ));
+}
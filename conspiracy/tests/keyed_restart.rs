@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use conspiracy::config::{config_struct, RestartRequired};
+
+config_struct!(
+    pub struct Endpoint {
+        id: String,
+        #[conspiracy(restart)]
+        address: String,
+        weight: u32,
+    }
+);
+
+config_struct!(
+    pub struct RoutingConfig {
+        #[conspiracy(restart, key = "id")]
+        endpoints: Vec<Endpoint>,
+    }
+);
+
+fn endpoint(id: &str, address: &str, weight: u32) -> Endpoint {
+    Endpoint {
+        id: id.to_string(),
+        address: address.to_string(),
+        weight,
+    }
+}
+
+fn routing(endpoints: Vec<Endpoint>) -> Arc<RoutingConfig> {
+    Arc::new(RoutingConfig { endpoints })
+}
+
+#[test]
+fn reordering_keyed_entries_does_not_restart() {
+    let config = routing(vec![
+        endpoint("a", "10.0.0.1", 1),
+        endpoint("b", "10.0.0.2", 1),
+    ]);
+    let reordered = routing(vec![
+        endpoint("b", "10.0.0.2", 1),
+        endpoint("a", "10.0.0.1", 1),
+    ]);
+
+    assert!(!config.restart_required(&reordered));
+    assert!(config.restart_reasons(&reordered).is_empty());
+}
+
+#[test]
+fn non_restart_field_change_does_not_restart() {
+    let config = routing(vec![endpoint("a", "10.0.0.1", 1)]);
+    let reweighted = routing(vec![endpoint("a", "10.0.0.1", 5)]);
+
+    assert!(!config.restart_required(&reweighted));
+}
+
+#[test]
+fn restart_field_change_within_entry_restarts() {
+    let config = routing(vec![
+        endpoint("a", "10.0.0.1", 1),
+        endpoint("b", "10.0.0.2", 1),
+    ]);
+    let moved = routing(vec![
+        endpoint("b", "10.0.0.3", 1),
+        endpoint("a", "10.0.0.1", 1),
+    ]);
+
+    assert!(config.restart_required(&moved));
+    assert_eq!(vec!["endpoints"], config.restart_reasons(&moved));
+}
+
+#[test]
+fn added_or_removed_entries_restart() {
+    let config = routing(vec![endpoint("a", "10.0.0.1", 1)]);
+    let added = routing(vec![
+        endpoint("a", "10.0.0.1", 1),
+        endpoint("b", "10.0.0.2", 1),
+    ]);
+
+    assert!(config.restart_required(&added));
+    assert!(added.restart_required(&config));
+}
+
+#[test]
+fn duplicate_keys_are_compared_per_entry() {
+    let config = routing(vec![
+        endpoint("a", "10.0.0.1", 1),
+        endpoint("b", "10.0.0.2", 1),
+    ]);
+    // Same number of entries, but `b` was replaced by a second `a`
+    let duplicated = routing(vec![
+        endpoint("a", "10.0.0.1", 1),
+        endpoint("a", "10.0.0.1", 1),
+    ]);
+
+    assert!(config.restart_required(&duplicated));
+    assert!(duplicated.restart_required(&config));
+
+    let readdressed = routing(vec![
+        endpoint("a", "10.0.0.1", 1),
+        endpoint("a", "10.0.0.3", 1),
+    ]);
+    assert!(duplicated.restart_required(&readdressed));
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use conspiracy::{
+    config::shared_fetcher_from_static,
+    feature_control::{
+        tracker::{ConspiracyFeatureTracker, LayeredFeatureTracker, StaticFetcher},
+        ExplainFeature, FeatureExplanation, FeatureOverrides, FeatureSource, FeatureTracker,
+    },
+};
+use conspiracy_macros::define_features;
+
+define_features!(
+    pub enum Features {
+        Foo => false,
+        Bar => false,
+        Cow => true,
+    }
+);
+
+fn state_of(tracker: &impl FeatureTracker) -> Arc<FeaturesState> {
+    tracker.static_feature_state().downcast().unwrap()
+}
+
+#[test]
+fn no_layers_uses_base() {
+    let tracker = LayeredFeatureTracker::<Features, StaticFetcher<Features>>::from_default();
+    assert_eq!(FeaturesState::default(), *state_of(&tracker));
+}
+
+#[test]
+fn top_layer_overrides_base_supplies_rest() {
+    let base = Features::builder().bar(true).build().unwrap();
+    let tracker = LayeredFeatureTracker::<Features, StaticFetcher<Features>>::from_static(base)
+        .with_layer(shared_fetcher_from_static(Arc::new(
+            FeaturesOverrides::default().foo(true),
+        )));
+
+    let expected = Features::builder().foo(true).bar(true).build().unwrap();
+    assert_eq!(expected, *state_of(&tracker));
+}
+
+#[test]
+fn first_layer_with_opinion_wins() {
+    let tracker = LayeredFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+        .with_layer(shared_fetcher_from_static(Arc::new(
+            FeaturesOverrides::default().cow(false),
+        )))
+        .with_layer(shared_fetcher_from_static(Arc::new(
+            FeaturesOverrides::default().foo(true).cow(true),
+        )));
+
+    let expected = Features::builder().foo(true).cow(false).build().unwrap();
+    assert_eq!(expected, *state_of(&tracker));
+}
+
+#[test]
+fn explain_identifies_deciding_layer() {
+    let tracker = LayeredFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+        .with_layer(shared_fetcher_from_static(Arc::new(
+            FeaturesOverrides::default().cow(false),
+        )))
+        .with_layer(shared_fetcher_from_static(Arc::new(
+            FeaturesOverrides::default().foo(true).cow(true),
+        )));
+
+    assert_eq!(
+        FeatureExplanation {
+            enabled: false,
+            source: FeatureSource::Layer(0),
+        },
+        tracker.explain(Features::Cow)
+    );
+    assert_eq!(
+        FeatureExplanation {
+            enabled: true,
+            source: FeatureSource::Layer(1),
+        },
+        tracker.explain(Features::Foo)
+    );
+    assert_eq!(
+        FeatureExplanation {
+            enabled: false,
+            source: FeatureSource::Base,
+        },
+        tracker.explain(Features::Bar)
+    );
+}
+
+/// Forces `Foo` on, implementing only the required methods
+struct ForceFoo;
+
+impl FeatureOverrides for ForceFoo {
+    type State = FeaturesState;
+
+    fn apply(&self, state: &mut FeaturesState) {
+        state.foo = true;
+    }
+
+    fn set(&mut self, _feature: &Features, _value: bool) {}
+}
+
+#[test]
+fn overrides_without_get_have_no_opinion() {
+    let mut state = FeaturesState::default();
+    ForceFoo.apply(&mut state);
+
+    assert!(state.foo);
+    assert_eq!(None, ForceFoo.get(&Features::Foo));
+}
+
+#[test]
+fn explain_static_tracker() {
+    let tracker = ConspiracyFeatureTracker::<Features, StaticFetcher<Features>>::from_default();
+
+    assert_eq!(
+        FeatureExplanation {
+            enabled: true,
+            source: FeatureSource::Static,
+        },
+        tracker.explain(Features::Cow)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn overrides_deserialize_partially() {
+    let overrides: FeaturesOverrides = serde_json::from_str(r#"{ "foo": true }"#).unwrap();
+    assert_eq!(FeaturesOverrides::default().foo(true), overrides);
+
+    LayeredFeatureTracker::<Features, StaticFetcher<Features>>::from_default()
+        .with_layer(shared_fetcher_from_static(Arc::new(overrides)))
+        .set_as_global_tracker()
+        .unwrap();
+
+    assert!(conspiracy_macros::feature_enabled!(Features::Foo));
+    assert!(!conspiracy_macros::feature_enabled!(Features::Bar));
+    assert!(conspiracy_macros::feature_enabled!(Features::Cow));
+}
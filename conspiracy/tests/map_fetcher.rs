@@ -0,0 +1,63 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+
+use conspiracy::config::{config_struct, fetcher::MapFetcher, shared_fetcher_from_fn};
+use conspiracy_theories::config::ConfigFetcher;
+
+config_struct!(
+    pub struct DatabaseConfig {
+        connection_string: String,
+    }
+);
+
+#[derive(Debug, PartialEq)]
+struct ConnectionInfo {
+    host: String,
+    port: u16,
+}
+
+fn parse(config: &Arc<DatabaseConfig>) -> Arc<ConnectionInfo> {
+    let (host, port) = config.connection_string.split_once(':').unwrap();
+    Arc::new(ConnectionInfo {
+        host: host.to_string(),
+        port: port.parse().unwrap(),
+    })
+}
+
+#[test]
+fn derived_snapshot_follows_updates() {
+    let current = Arc::new(Mutex::new(Arc::new(DatabaseConfig {
+        connection_string: "localhost:5432".to_string(),
+    })));
+    let source = current.clone();
+    let inner = shared_fetcher_from_fn(move || source.lock().unwrap().clone());
+
+    let maps = Arc::new(AtomicU32::new(0));
+    let counter = maps.clone();
+    let fetcher = MapFetcher::new(inner, move |config: &Arc<DatabaseConfig>| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        parse(config)
+    });
+
+    let first = fetcher.latest_snapshot();
+    assert_eq!("localhost", first.host);
+    assert_eq!(5432, first.port);
+    // Unchanged source, so the derived snapshot is shared rather than recomputed
+    assert!(Arc::ptr_eq(&first, &fetcher.latest_snapshot()));
+    assert_eq!(1, maps.load(Ordering::SeqCst));
+
+    *current.lock().unwrap() = Arc::new(DatabaseConfig {
+        connection_string: "remote:6543".to_string(),
+    });
+
+    assert_eq!(
+        ConnectionInfo {
+            host: "remote".to_string(),
+            port: 6543,
+        },
+        *fetcher.latest_snapshot()
+    );
+    assert_eq!(2, maps.load(Ordering::SeqCst));
+}
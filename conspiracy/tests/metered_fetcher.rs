@@ -0,0 +1,33 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use conspiracy::config::{fetcher::MeteredFetcher, shared_fetcher_from_fn};
+use conspiracy_theories::config::ConfigFetcher;
+
+#[test]
+fn counts_calls_and_changes() {
+    let first = Arc::new(1u32);
+    let second = Arc::new(2u32);
+    // Yields first, first, second, second, first
+    let sequence = [&first, &first, &second, &second, &first].map(Arc::clone);
+    let calls = Arc::new(AtomicU32::new(0));
+
+    let inner = shared_fetcher_from_fn(move || {
+        sequence[calls.fetch_add(1, Ordering::SeqCst) as usize].clone()
+    });
+    let fetcher = MeteredFetcher::new(inner);
+
+    assert_eq!(0, fetcher.snapshot_count());
+    assert_eq!(0, fetcher.change_count());
+
+    let expected_counts = [(1, 1), (2, 1), (3, 2), (4, 2), (5, 3)];
+    for (snapshots, changes) in expected_counts {
+        fetcher.latest_snapshot();
+        assert_eq!(snapshots, fetcher.snapshot_count());
+        assert_eq!(changes, fetcher.change_count());
+    }
+}
@@ -0,0 +1,127 @@
+use conspiracy::feature_control::{
+    combine_features, current_feature_state, feature_enabled, set_global_tracker,
+    tracker::{ConspiracyFeatureTracker, StaticFetcher},
+    SetGlobalTrackerError,
+};
+
+mod payments {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            NewCheckout => false,
+        }
+    );
+}
+
+mod search {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            FuzzyMatching => false,
+        }
+    );
+}
+
+mod unregistered {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            Unused => false,
+        }
+    );
+}
+
+mod billing {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            Invoices => false,
+        }
+    );
+}
+
+combine_features!(
+    pub enum SearchAndBilling {
+        Search(search::Features),
+        Billing(billing::Features),
+    }
+);
+
+combine_features!(
+    pub enum BillingAndPayments {
+        Billing(billing::Features),
+        Payments(payments::Features),
+    }
+);
+
+#[test]
+fn each_feature_set_has_its_own_tracker() {
+    // Inverse the defaults, so the trackers are known to be used rather than the `cfg(test)` defaults
+    ConspiracyFeatureTracker::<payments::Features, StaticFetcher<_>>::from_static(
+        payments::Features::builder()
+            .new_checkout(true)
+            .build()
+            .unwrap(),
+    )
+    .set_as_global_tracker()
+    .unwrap();
+    ConspiracyFeatureTracker::<search::Features, StaticFetcher<_>>::from_static(
+        search::Features::builder()
+            .fuzzy_matching(true)
+            .build()
+            .unwrap(),
+    )
+    .set_as_global_tracker()
+    .unwrap();
+
+    assert!(feature_enabled!(payments::Features::NewCheckout));
+    assert!(feature_enabled!(search::Features::FuzzyMatching));
+    assert_eq!(
+        search::Features::builder()
+            .fuzzy_matching(true)
+            .build()
+            .unwrap(),
+        *current_feature_state::<search::Features>().unwrap()
+    );
+
+    // Both the first and the later trackers can only be registered once
+    assert!(matches!(
+        ConspiracyFeatureTracker::<payments::Features, StaticFetcher<_>>::from_default()
+            .set_as_global_tracker(),
+        Err(SetGlobalTrackerError::GlobalTrackerAlreadySet)
+    ));
+    assert!(matches!(
+        ConspiracyFeatureTracker::<search::Features, StaticFetcher<_>>::from_default()
+            .set_as_global_tracker(),
+        Err(SetGlobalTrackerError::GlobalTrackerAlreadySet)
+    ));
+
+    // A later tracker for the wrong state type is rejected rather than installed
+    assert!(matches!(
+        set_global_tracker::<
+            unregistered::FeaturesState,
+            ConspiracyFeatureTracker<search::Features, StaticFetcher<search::Features>>,
+        >(ConspiracyFeatureTracker::from_default()),
+        Err(SetGlobalTrackerError::BadCast(_))
+    ));
+    assert!(current_feature_state::<unregistered::Features>().is_err());
+
+    // The error of an unserved lookup names every registered tracker
+    let error = current_feature_state::<unregistered::Features>()
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("the global trackers"), "{error}");
+    assert!(error.contains("payments::Features"), "{error}");
+    assert!(error.contains("search::Features"), "{error}");
+
+    // A combined tracker can't take over a part that's already served, whether by the first tracker
+    // or a later one
+    assert!(matches!(
+        ConspiracyFeatureTracker::<SearchAndBilling, StaticFetcher<_>>::from_default()
+            .set_as_global_tracker(),
+        Err(SetGlobalTrackerError::GlobalTrackerAlreadySet)
+    ));
+    assert!(matches!(
+        ConspiracyFeatureTracker::<BillingAndPayments, StaticFetcher<_>>::from_default()
+            .set_as_global_tracker(),
+        Err(SetGlobalTrackerError::GlobalTrackerAlreadySet)
+    ));
+    assert!(current_feature_state::<billing::Features>().is_err());
+}
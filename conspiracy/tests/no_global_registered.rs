@@ -1,7 +1,7 @@
-use conspiracy::feature_control::FeatureEnabledError;
+use conspiracy::feature_control::{current_feature_state, FeatureEnabledError};
 use conspiracy_macros::{
-    define_features, feature_enabled, feature_enabled_or, feature_enabled_or_default,
-    try_feature_enabled,
+    define_features, feature_enabled, feature_enabled_all, feature_enabled_any, feature_enabled_or,
+    feature_enabled_or_default, try_feature_enabled,
 };
 
 mod generated {
@@ -56,3 +56,21 @@ fn no_global_registered_no_panic_under_cfg_test() {
     assert!(feature_enabled!(Features2::Foo));
     assert!(!feature_enabled!(Features2::Bar));
 }
+
+#[test]
+fn composite_uses_defaults_under_cfg_test() {
+    // Defaults are Foo => true, Bar => false
+    assert!(feature_enabled_all!(Features::Foo));
+    assert!(!feature_enabled_all!(Features::Foo, Features::Bar));
+    assert!(feature_enabled_any!(Features::Bar, Features::Foo));
+    assert!(!feature_enabled_any!(Features::Bar));
+}
+
+#[test]
+fn current_state_requires_tracker() {
+    // Unlike the macros, there is no fallback to the defaults under `#[cfg(test)]`
+    assert!(matches!(
+        current_feature_state::<Features>(),
+        Err(FeatureEnabledError::NoGlobalTracker)
+    ));
+}
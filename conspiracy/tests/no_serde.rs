@@ -0,0 +1,48 @@
+//! Nothing here depends on serde, run with `--no-default-features` to confirm the generated code
+//! doesn't either.
+
+use std::sync::Arc;
+
+use conspiracy::{
+    config::{as_shared_fetcher, config_struct, shared_fetcher_from_static, SharedConfigFetcher},
+    feature_control::define_features,
+};
+
+config_struct!(
+    pub struct InMemoryConfig {
+        name: String,
+        database: pub struct InMemoryDatabase {
+            #[conspiracy(restart)]
+            connection_string: String,
+        }
+    }
+);
+
+define_features!(
+    pub enum InMemoryFeatures {
+        Foo => true,
+    }
+);
+
+#[test]
+fn programmatic_config_projection() {
+    let config = Arc::new(InMemoryConfig {
+        name: "app".to_string(),
+        database: Arc::new(InMemoryDatabase {
+            connection_string: "localhost".to_string(),
+        }),
+    });
+
+    let fetcher = shared_fetcher_from_static(config);
+    let database: SharedConfigFetcher<InMemoryDatabase> = as_shared_fetcher(&fetcher);
+
+    assert_eq!("localhost", database.latest_snapshot().connection_string);
+}
+
+#[test]
+fn features_without_serde() {
+    assert_eq!(
+        InMemoryFeaturesState::builder().foo(true).build().unwrap(),
+        InMemoryFeaturesState::default()
+    );
+}
@@ -0,0 +1,125 @@
+use std::sync::{Arc, Mutex};
+
+use conspiracy::config::{
+    as_shared_fetcher, config_struct, fetcher::PollingFetcher, ConfigFetcher, Reconcile,
+    SharedConfigFetcher,
+};
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+        database: pub struct DatabaseConfig {
+            url: String,
+            pool: pub struct PoolConfig {
+                size: u32,
+            }
+        },
+        cache: enum CacheConfig {
+            Memory(pub struct MemoryCache {
+                capacity: u32,
+            }),
+            Redis(pub struct RedisCache {
+                url: String,
+            }),
+        },
+    }
+);
+
+/// The parts of the config the tests change, every load builds an entirely new tree from them.
+#[derive(Clone)]
+struct Source {
+    port: u16,
+    pool_size: u32,
+    cache_capacity: u32,
+}
+
+fn load(source: &Source) -> AppConfig {
+    AppConfig {
+        port: source.port,
+        database: Arc::new(DatabaseConfig {
+            url: "db:5432".to_string(),
+            pool: Arc::new(PoolConfig {
+                size: source.pool_size,
+            }),
+        }),
+        cache: Arc::new(CacheConfig::Memory(Arc::new(MemoryCache {
+            capacity: source.cache_capacity,
+        }))),
+    }
+}
+
+fn polling_fetcher(
+    source: &Arc<Mutex<Source>>,
+) -> Arc<PollingFetcher<AppConfig, impl Fn() -> Result<AppConfig, String>>> {
+    let loader_source = source.clone();
+    Arc::new(
+        PollingFetcher::new(move || Ok(load(&loader_source.lock().unwrap())))
+            .unwrap()
+            .reconciled(),
+    )
+}
+
+#[test]
+fn unchanged_branches_keep_identity() {
+    let source = Arc::new(Mutex::new(Source {
+        port: 80,
+        pool_size: 4,
+        cache_capacity: 100,
+    }));
+    let fetcher = polling_fetcher(&source);
+    let database: SharedConfigFetcher<DatabaseConfig> = as_shared_fetcher(&fetcher);
+    let pool: SharedConfigFetcher<PoolConfig> = as_shared_fetcher(&fetcher);
+    let cache: SharedConfigFetcher<CacheConfig> = as_shared_fetcher(&fetcher);
+    let before = fetcher.latest_snapshot();
+
+    // Only the pool changes, the database branch is rebuilt around it
+    source.lock().unwrap().pool_size = 8;
+    fetcher.reload().unwrap();
+    let after = fetcher.latest_snapshot();
+
+    assert!(!Arc::ptr_eq(&before, &after));
+    assert!(!Arc::ptr_eq(&before.database, &database.latest_snapshot()));
+    assert!(!Arc::ptr_eq(&before.database.pool, &pool.latest_snapshot()));
+    assert_eq!(8, pool.latest_snapshot().size);
+    assert!(Arc::ptr_eq(&before.cache, &cache.latest_snapshot()));
+
+    // A change to the root's own fields keeps every branch
+    source.lock().unwrap().port = 8080;
+    fetcher.reload().unwrap();
+    let latest = fetcher.latest_snapshot();
+    assert_eq!(8080, latest.port);
+    assert!(Arc::ptr_eq(&after.database, &latest.database));
+    assert!(Arc::ptr_eq(&after.cache, &latest.cache));
+}
+
+#[test]
+fn unchanged_reload_keeps_the_snapshot() {
+    let source = Arc::new(Mutex::new(Source {
+        port: 80,
+        pool_size: 4,
+        cache_capacity: 100,
+    }));
+    let fetcher = polling_fetcher(&source);
+    let before = fetcher.latest_snapshot();
+
+    fetcher.reload().unwrap();
+    assert!(Arc::ptr_eq(&before, &fetcher.latest_snapshot()));
+
+    source.lock().unwrap().cache_capacity = 200;
+    fetcher.reload().unwrap();
+    let after = fetcher.latest_snapshot();
+    assert!(!Arc::ptr_eq(&before.cache, &after.cache));
+    assert!(Arc::ptr_eq(&before.database, &after.database));
+}
+
+#[test]
+fn variant_change_replaces_the_enum() {
+    let previous = Arc::new(CacheConfig::Memory(Arc::new(MemoryCache { capacity: 1 })));
+    let next = Arc::new(CacheConfig::Redis(Arc::new(RedisCache {
+        url: "redis:6379".to_string(),
+    })));
+
+    let reconciled = CacheConfig::reconcile(&previous, next);
+    assert!(!Arc::ptr_eq(&previous, &reconciled));
+    assert!(matches!(&*reconciled, CacheConfig::Redis(redis) if redis.url == "redis:6379"));
+}
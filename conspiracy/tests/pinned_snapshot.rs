@@ -0,0 +1,44 @@
+use std::{sync::Arc, thread, time::Duration};
+
+use conspiracy::config::{
+    fetcher::SwapFetcher,
+    pin::{ConfigFetcherExt, PinTracker},
+    ConfigFetcher,
+};
+
+#[test]
+fn outstanding_pins_are_counted_until_dropped() {
+    let fetcher = SwapFetcher::new(Arc::new(1));
+    let tracker = PinTracker::default();
+    assert_eq!(0, tracker.outstanding());
+    assert_eq!(None, tracker.oldest_pin_age());
+
+    let first = fetcher.pin_tracked(&tracker);
+    thread::sleep(Duration::from_millis(20));
+    fetcher.store(Arc::new(2));
+    let second = fetcher.pin_tracked(&tracker);
+    assert_eq!(2, tracker.outstanding());
+    assert!(tracker.oldest_pin_age().unwrap() >= Duration::from_millis(20));
+
+    // Each pin keeps the snapshot it was taken from
+    assert_eq!(1, *first);
+    assert_eq!(2, *second);
+    assert_eq!(2, *fetcher.latest_snapshot());
+
+    drop(first);
+    assert_eq!(1, tracker.outstanding());
+    assert!(tracker.oldest_pin_age().unwrap() < Duration::from_millis(20));
+
+    drop(second);
+    assert_eq!(0, tracker.outstanding());
+    assert_eq!(None, tracker.oldest_pin_age());
+}
+
+#[test]
+fn untracked_pins_hold_the_snapshot() {
+    let fetcher = SwapFetcher::new(Arc::new("before".to_string()));
+    let pinned = fetcher.pin();
+    fetcher.store(Arc::new("after".to_string()));
+
+    assert_eq!("before", pinned.as_str());
+}
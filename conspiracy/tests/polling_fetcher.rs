@@ -0,0 +1,148 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use conspiracy::config::{
+    fetcher::{with_context, LayeredConfigFetcher, PollingFetcher, TryConfigFetcher},
+    ConfigFetcher,
+};
+
+/// Loads 1, 2, ... from the shared counter, failing while `fail` is set.
+fn scripted_loader(
+    loads: Arc<AtomicU32>,
+    fail: Arc<AtomicU32>,
+) -> impl Fn() -> Result<u32, String> + Send + Sync + 'static {
+    move || {
+        if fail.load(Ordering::SeqCst) > 0 {
+            Err("source unavailable".to_string())
+        } else {
+            Ok(loads.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+    }
+}
+
+#[test]
+fn failed_reload_keeps_last_success() {
+    let loads = Arc::new(AtomicU32::new(0));
+    let fail = Arc::new(AtomicU32::new(0));
+    let fetcher = PollingFetcher::new(scripted_loader(loads.clone(), fail.clone())).unwrap();
+
+    let health = fetcher.health();
+    assert_eq!(0, health.consecutive_failures);
+    assert!(health.last_error.is_none());
+    let loaded_at = health.last_success;
+
+    fail.store(1, Ordering::SeqCst);
+    assert!(fetcher.reload().is_err());
+    assert!(fetcher.reload().is_err());
+
+    let health = fetcher.health();
+    assert_eq!(2, health.consecutive_failures);
+    assert_eq!(Some("source unavailable"), health.last_error.as_deref());
+    assert_eq!(loaded_at, health.last_success);
+    assert_eq!(1, *fetcher.latest_snapshot());
+
+    // Recovering resets the failures
+    fail.store(0, Ordering::SeqCst);
+    fetcher.reload().unwrap();
+
+    let health = fetcher.health();
+    assert_eq!(0, health.consecutive_failures);
+    assert!(health.last_error.is_none());
+    assert!(health.last_success > loaded_at);
+    assert_eq!(2, *fetcher.latest_snapshot());
+}
+
+#[test]
+fn initial_load_must_succeed() {
+    let result = PollingFetcher::new(|| Err::<u32, _>("missing file"));
+    assert_eq!("missing file", result.err().unwrap());
+}
+
+#[test]
+fn polls_in_background_until_dropped() {
+    let loads = Arc::new(AtomicU32::new(0));
+    let fetcher = Arc::new(
+        PollingFetcher::new(scripted_loader(loads.clone(), Arc::new(AtomicU32::new(0)))).unwrap(),
+    );
+
+    let poller = fetcher.poll_every(Duration::from_millis(10));
+    thread::sleep(Duration::from_millis(100));
+    assert!(*fetcher.latest_snapshot() > 1);
+
+    drop(fetcher);
+    poller.join().unwrap();
+}
+
+#[test]
+fn reload_errors_carry_context() {
+    let loads = Arc::new(AtomicU32::new(0));
+    let fail = Arc::new(AtomicU32::new(0));
+    let loader = with_context(
+        "remote layer",
+        with_context(
+            "https://config.internal",
+            scripted_loader(loads, fail.clone()),
+        ),
+    );
+    let fetcher = PollingFetcher::new(loader).unwrap();
+
+    fail.store(1, Ordering::SeqCst);
+    let error = fetcher.reload().unwrap_err();
+    assert_eq!("remote layer", error.context);
+    assert_eq!("https://config.internal", error.error.context);
+    assert_eq!(
+        "remote layer: https://config.internal: source unavailable",
+        error.to_string()
+    );
+    assert_eq!(Some(error.to_string()), fetcher.health().last_error);
+}
+
+#[test]
+fn layered_falls_back_until_a_layer_loads() {
+    let remote_fail = Arc::new(AtomicU32::new(1));
+    let file_loads = Arc::new(AtomicU32::new(100));
+    let fetcher = PollingFetcher::new(
+        LayeredConfigFetcher::new()
+            .with_layer(scripted_loader(
+                Arc::new(AtomicU32::new(0)),
+                remote_fail.clone(),
+            ))
+            .with_layer(scripted_loader(file_loads, Arc::new(AtomicU32::new(0)))),
+    )
+    .unwrap();
+    assert_eq!(101, *fetcher.latest_snapshot());
+
+    // Once the first layer recovers it takes precedence again
+    remote_fail.store(0, Ordering::SeqCst);
+    fetcher.reload().unwrap();
+    assert_eq!(1, *fetcher.latest_snapshot());
+}
+
+#[test]
+fn layered_errors_carry_context_of_each_layer() {
+    let fail = Arc::new(AtomicU32::new(1));
+    let loads = Arc::new(AtomicU32::new(0));
+    let layered = LayeredConfigFetcher::new()
+        .with_layer(scripted_loader(loads.clone(), fail.clone()).with_context("remote"))
+        .with_layer(
+            scripted_loader(loads, fail)
+                .map_err(|error| format!("{error} (cached)"))
+                .with_context("cache"),
+        )
+        .with_context("app config");
+
+    let error = layered.try_load().unwrap_err();
+    assert_eq!(2, error.error.0.len());
+    assert_eq!("remote", error.error.0[0].context);
+    assert_eq!(
+        "app config: every layer failed: remote: source unavailable; cache: source unavailable \
+        (cached)",
+        error.to_string()
+    );
+}
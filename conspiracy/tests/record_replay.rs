@@ -0,0 +1,76 @@
+#![cfg(feature = "test-util")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{
+    config_struct,
+    testing::{RecordingFetcher, ReplayFetcher, StepFetcher},
+    ConfigFetcher,
+};
+
+config_struct!(
+    #[cfg_attr(feature = "json", conspiracy::config::full_serde)]
+    pub struct AppConfig {
+        port: u16,
+    }
+);
+
+fn recorded_sequence() -> RecordingFetcher<AppConfig, StepFetcher<AppConfig>> {
+    let fetcher = RecordingFetcher::new(StepFetcher::new(
+        [8080, 8081, 8082].map(|port| Arc::new(AppConfig { port })),
+    ));
+    for _ in 0..3 {
+        fetcher.latest_snapshot();
+    }
+    fetcher
+}
+
+fn ports(fetcher: &impl ConfigFetcher<AppConfig>, reads: usize) -> Vec<u16> {
+    (0..reads).map(|_| fetcher.latest_snapshot().port).collect()
+}
+
+#[test]
+fn replay_matches_recording() {
+    let recording = recorded_sequence().recording();
+    assert_eq!(
+        vec![8080, 8081, 8082],
+        recording
+            .iter()
+            .map(|config| config.port)
+            .collect::<Vec<_>>()
+    );
+
+    let replay = ReplayFetcher::new(recording);
+    assert_eq!(vec![8080, 8081], ports(&replay, 2));
+    assert!(!replay.finished());
+    assert_eq!(vec![8082, 8082], ports(&replay, 2));
+    assert!(replay.finished());
+}
+
+#[test]
+fn unchanged_snapshots_are_recorded_once() {
+    let fetcher = RecordingFetcher::new(StepFetcher::new(
+        [8080, 8081].map(|port| Arc::new(AppConfig { port })),
+    ));
+    // The step fetcher keeps returning its last snapshot
+    assert_eq!(vec![8080, 8081, 8081, 8081], ports(&fetcher, 4));
+
+    assert_eq!(
+        vec![8080, 8081],
+        fetcher
+            .recording()
+            .iter()
+            .map(|config| config.port)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn replay_from_json() {
+    let json = recorded_sequence().recording_to_json().unwrap();
+
+    let replay = ReplayFetcher::<AppConfig>::from_json(&json).unwrap();
+    assert_eq!(vec![8080, 8081, 8082], ports(&replay, 3));
+    assert!(replay.finished());
+}
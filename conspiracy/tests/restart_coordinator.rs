@@ -0,0 +1,84 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{RestartAwareFetcher, SwapFetcher},
+    restart::RestartCoordinator,
+    ConfigFetcher,
+};
+
+config_struct!(
+    pub struct Config {
+        #[conspiracy(restart)]
+        port: u16,
+        name: String,
+    }
+);
+
+fn config(port: u16, name: &str) -> Arc<Config> {
+    Arc::new(Config {
+        port,
+        name: name.to_string(),
+    })
+}
+
+const WINDOW: Duration = Duration::from_millis(100);
+
+fn recording_coordinator() -> (RestartCoordinator<Config>, Arc<Mutex<Vec<u16>>>) {
+    let restarts = Arc::new(Mutex::new(Vec::new()));
+    let recorder = restarts.clone();
+    let coordinator = RestartCoordinator::new(WINDOW, move |config: Arc<Config>| {
+        recorder.lock().unwrap().push(config.port);
+    });
+
+    (coordinator, restarts)
+}
+
+#[test]
+fn rapid_changes_restart_once_with_latest() {
+    let (coordinator, restarts) = recording_coordinator();
+    let source = Arc::new(SwapFetcher::new(config(80, "foo")));
+    let fetcher =
+        RestartAwareFetcher::new(source.clone(), move |config| coordinator.notify(config));
+
+    fetcher.latest_snapshot();
+    for port in [8080, 8081, 8082] {
+        source.store(config(port, "foo"));
+        fetcher.latest_snapshot();
+    }
+    assert!(restarts.lock().unwrap().is_empty());
+
+    thread::sleep(WINDOW * 3);
+    assert_eq!(vec![8082], *restarts.lock().unwrap());
+}
+
+#[test]
+fn changes_in_separate_windows_restart_separately() {
+    let (coordinator, restarts) = recording_coordinator();
+
+    coordinator.notify(config(8080, "foo"));
+    thread::sleep(WINDOW * 3);
+    coordinator.notify(config(8081, "foo"));
+    thread::sleep(WINDOW * 3);
+
+    assert_eq!(vec![8080, 8081], *restarts.lock().unwrap());
+}
+
+#[test]
+fn fetcher_ignores_changes_not_requiring_restart() {
+    let (coordinator, restarts) = recording_coordinator();
+    let source = Arc::new(SwapFetcher::new(config(80, "foo")));
+    let fetcher =
+        RestartAwareFetcher::new(source.clone(), move |config| coordinator.notify(config));
+
+    fetcher.latest_snapshot();
+    source.store(config(80, "bar"));
+    assert_eq!("bar", fetcher.latest_snapshot().name);
+
+    thread::sleep(WINDOW * 3);
+    assert!(restarts.lock().unwrap().is_empty());
+}
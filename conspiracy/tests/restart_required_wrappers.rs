@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use conspiracy::config::{config_struct, RestartRequired};
+
+config_struct!(
+    pub struct ServiceConfig {
+        #[conspiracy(restart)]
+        port: u16,
+        #[conspiracy(change = "reload")]
+        log_level: String,
+        name: String,
+    }
+);
+
+fn service(port: u16, log_level: &str, name: &str) -> ServiceConfig {
+    ServiceConfig {
+        port,
+        log_level: log_level.to_string(),
+        name: name.to_string(),
+    }
+}
+
+#[test]
+fn arc_compares_pointed_to_configs() {
+    let current = Arc::new(service(80, "info", "a"));
+
+    assert!(!current.restart_required(&current));
+    assert!(!current.restart_required(&Arc::new(service(80, "info", "b"))));
+
+    let moved = Arc::new(service(8080, "debug", "a"));
+    assert!(RestartRequired::restart_required(&current, &moved));
+    assert_eq!(
+        vec!["port"],
+        RestartRequired::restart_reasons(&current, &moved)
+    );
+    assert!(RestartRequired::reload_required(&current, &moved));
+    assert_eq!(
+        vec!["log_level"],
+        RestartRequired::reload_reasons(&current, &moved)
+    );
+}
+
+#[test]
+fn option_compares_inner_configs() {
+    let current = Some(service(80, "info", "a"));
+
+    assert!(!current.restart_required(&Some(service(80, "info", "b"))));
+    assert!(current.restart_required(&Some(service(8080, "info", "a"))));
+    assert!(current.reload_required(&Some(service(80, "debug", "a"))));
+    assert!(!None::<ServiceConfig>.restart_required(&None));
+}
+
+#[test]
+fn option_transition_requires_restart() {
+    let absent: Option<ServiceConfig> = None;
+    let present = Some(service(80, "info", "a"));
+
+    assert!(absent.restart_required(&present));
+    assert_eq!(vec!["Some"], absent.restart_reasons(&present));
+    assert!(present.restart_required(&absent));
+    assert_eq!(vec!["None"], present.restart_reasons(&absent));
+
+    // The restart subsumes any reload
+    assert!(!absent.reload_required(&present));
+    assert!(absent.reload_reasons(&present).is_empty());
+}
+
+#[test]
+fn wrappers_compose() {
+    let current = Some(Arc::new(service(80, "info", "a")));
+    let next = Some(Arc::new(service(443, "info", "a")));
+
+    assert!(current.restart_required(&next));
+    assert_eq!(vec!["port"], current.restart_reasons(&next));
+}
+
+/// Only implements the required method, like implementations predating `restart_reasons`
+struct Endpoint(String);
+
+impl RestartRequired for Endpoint {
+    fn restart_required(&self, other: &Self) -> bool {
+        self.0 != other.0
+    }
+}
+
+#[test]
+fn restart_reasons_default_to_the_type_name() {
+    let current = Endpoint("a".to_string());
+
+    assert!(current
+        .restart_reasons(&Endpoint("a".to_string()))
+        .is_empty());
+    assert_eq!(
+        vec!["restart_required_wrappers::Endpoint"],
+        current.restart_reasons(&Endpoint("b".to_string()))
+    );
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "json-schema")]
+
+use std::sync::Mutex;
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{schema_validated, PollingFetcher, SchemaValidationError},
+    full_serde, ConfigFetcher,
+};
+use serde_json::{json, Value};
+
+config_struct!(
+    #[full_serde]
+    #[conspiracy(schema)]
+    pub struct ServerConfig {
+        name: String,
+        port: u16,
+    }
+);
+
+#[test]
+fn invalid_document_is_rejected_and_last_good_survives() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "port": { "type": "integer" },
+        },
+    });
+    let document = Mutex::new(json!({ "name": "primary", "port": 8080 }));
+    let load = schema_validated::<ServerConfig, _, _>(&schema, || {
+        Ok::<Value, String>(document.lock().unwrap().clone())
+    })
+    .unwrap();
+    let fetcher = PollingFetcher::new(load).unwrap();
+    let previous = fetcher.latest_snapshot();
+
+    // Deserializes fine, but the schema forbids an empty name
+    *document.lock().unwrap() = json!({ "name": "", "port": 9090 });
+    match fetcher.reload() {
+        Err(SchemaValidationError::Invalid {
+            instance_path,
+            schema_path,
+            ..
+        }) => {
+            assert_eq!("/name", instance_path);
+            assert_eq!("/properties/name/minLength", schema_path);
+        }
+        _ => panic!("expected the document to fail validation"),
+    }
+    assert!(std::sync::Arc::ptr_eq(
+        &previous,
+        &fetcher.latest_snapshot()
+    ));
+
+    *document.lock().unwrap() = json!({ "name": "secondary", "port": 9090 });
+    fetcher.reload().unwrap();
+    assert_eq!("secondary", fetcher.latest_snapshot().name);
+    assert_eq!(9090, fetcher.latest_snapshot().port);
+}
+
+#[test]
+fn generated_schema_rejects_wrong_types() {
+    let schema = ServerConfig::schema();
+    let document = Mutex::new(json!({ "name": "primary", "port": 8080 }));
+    let load = schema_validated::<ServerConfig, _, _>(schema.as_value(), || {
+        Ok::<Value, String>(document.lock().unwrap().clone())
+    })
+    .unwrap();
+    let fetcher = PollingFetcher::new(load).unwrap();
+
+    *document.lock().unwrap() = json!({ "name": "primary", "port": "8080" });
+    assert!(matches!(
+        fetcher.reload(),
+        Err(SchemaValidationError::Invalid { instance_path, .. }) if instance_path == "/port"
+    ));
+    assert_eq!(8080, fetcher.latest_snapshot().port);
+}
+
+#[test]
+fn source_and_schema_errors_are_reported() {
+    assert!(matches!(
+        schema_validated::<ServerConfig, _, _>(&json!({ "type": 5 }), || {
+            Ok::<Value, String>(json!({}))
+        }),
+        Err(SchemaValidationError::InvalidSchema(_))
+    ));
+
+    let load =
+        schema_validated::<ServerConfig, _, _>(&json!({}), || Err::<Value, _>("unavailable"))
+            .unwrap();
+    assert!(matches!(
+        PollingFetcher::new(load),
+        Err(SchemaValidationError::Source(message)) if message == "unavailable"
+    ));
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "test-util")]
+
+use std::sync::Arc;
+
+use conspiracy::config::{
+    as_shared_fetcher, config_struct,
+    testing::{assert_fetchers_agree, StepFetcher},
+    ConfigFetcher, SharedConfigFetcher,
+};
+
+config_struct!(
+    struct Foo {
+        val: u32,
+        bar: struct Bar {
+            val: u32,
+        }
+    }
+);
+
+fn foo(val: u32) -> Arc<Foo> {
+    Arc::new(Foo {
+        val,
+        bar: Arc::new(Bar { val }),
+    })
+}
+
+#[test]
+fn steps_through_script() {
+    let fetcher = StepFetcher::new([foo(0), foo(1), foo(2)]);
+
+    assert_eq!(0, fetcher.latest_snapshot().val);
+    assert_eq!(1, fetcher.latest_snapshot().val);
+    assert_eq!(2, fetcher.latest_snapshot().val);
+    // The last step is held once the script runs out
+    assert_eq!(2, fetcher.latest_snapshot().val);
+    assert_eq!(2, fetcher.latest_snapshot().val);
+    assert_eq!(5, fetcher.reads());
+}
+
+#[test]
+fn holds_each_step_for_reads_per_step() {
+    let fetcher = StepFetcher::new([foo(0), foo(1)]).with_reads_per_step(3);
+
+    let vals: Vec<_> = (0..7).map(|_| fetcher.latest_snapshot().val).collect();
+    assert_eq!(vec![0, 0, 0, 1, 1, 1, 1], vals);
+}
+
+#[test]
+fn sub_fetcher_agrees_at_every_step() {
+    // Each assertion reads the parent once directly and once through the sub-fetcher
+    let fetcher = Arc::new(StepFetcher::new([foo(0), foo(1), foo(2)]).with_reads_per_step(2));
+    let sub_fetcher: SharedConfigFetcher<Bar> = as_shared_fetcher(&fetcher);
+
+    for _ in 0..3 {
+        assert_fetchers_agree(&*fetcher, &sub_fetcher);
+    }
+    assert_eq!(2, sub_fetcher.latest_snapshot().val);
+}
+
+#[test]
+#[should_panic(expected = "doesn't match the projection")]
+fn disagreeing_fetchers_panic() {
+    let fetcher = Arc::new(StepFetcher::new([foo(0), foo(1)]));
+    let sub_fetcher: SharedConfigFetcher<Bar> = as_shared_fetcher(&fetcher);
+
+    assert_fetchers_agree(&*fetcher, &sub_fetcher);
+}
+
+#[test]
+#[should_panic(expected = "at least one step")]
+fn empty_script_panics() {
+    StepFetcher::<Foo>::new([]);
+}
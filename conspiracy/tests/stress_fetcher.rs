@@ -0,0 +1,54 @@
+#![cfg(feature = "test-util")]
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use conspiracy::config::{
+    fetcher::SwapFetcher,
+    testing::{stress_fetcher, StressSnapshot},
+    ConfigFetcher,
+};
+
+#[test]
+fn swap_fetcher_survives_stress() {
+    let fetcher = SwapFetcher::new(Arc::new(StressSnapshot::new(0)));
+
+    let report = stress_fetcher(
+        &fetcher,
+        4,
+        2,
+        Duration::from_millis(100),
+        SwapFetcher::store,
+    );
+
+    assert!(report.reads > 0);
+    assert!(report.writes > 0);
+    assert!(fetcher.latest_snapshot().generation() <= report.writes);
+}
+
+/// A custom fetcher, as the harness is intended for
+struct LockedFetcher(RwLock<Arc<StressSnapshot>>);
+
+impl ConfigFetcher<StressSnapshot> for LockedFetcher {
+    fn latest_snapshot(&self) -> Arc<StressSnapshot> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+#[test]
+fn custom_fetcher_survives_stress() {
+    let fetcher = LockedFetcher(RwLock::new(Arc::new(StressSnapshot::new(0))));
+
+    let report = stress_fetcher(
+        &fetcher,
+        2,
+        2,
+        Duration::from_millis(50),
+        |fetcher, snapshot| *fetcher.0.write().unwrap() = snapshot,
+    );
+
+    assert!(report.reads > 0);
+    assert!(report.writes > 0);
+}
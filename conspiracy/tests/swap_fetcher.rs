@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use conspiracy::config::{
+    as_shared_fetcher, config_struct, fetcher::SwapFetcher, ConfigFetcher, SharedConfigFetcher,
+};
+
+config_struct!(
+    pub struct HotConfig {
+        limit: u64,
+        nested: pub struct HotNested {
+            name: String,
+        }
+    }
+);
+
+fn config(limit: u64) -> Arc<HotConfig> {
+    Arc::new(HotConfig {
+        limit,
+        nested: Arc::new(HotNested {
+            name: "foo".to_string(),
+        }),
+    })
+}
+
+#[test]
+fn guard_matches_latest_snapshot() {
+    let fetcher = SwapFetcher::new(config(1));
+
+    let mut total = 0;
+    for _ in 0..100_000 {
+        let guard = fetcher.load_guard();
+        assert!(Arc::ptr_eq(&guard, &fetcher.latest_snapshot()));
+        total += guard.limit;
+    }
+    assert_eq!(100_000, total);
+
+    fetcher.store(config(2));
+    assert_eq!(2, fetcher.load_guard().limit);
+    assert!(**fetcher.load_guard() == *fetcher.latest_snapshot());
+}
+
+#[test]
+fn guard_does_not_increment_ref_count() {
+    let fetcher = SwapFetcher::new(config(1));
+    let snapshot = fetcher.latest_snapshot();
+    let count = Arc::strong_count(&snapshot);
+
+    let guard = fetcher.load_guard();
+    assert_eq!(count, Arc::strong_count(&snapshot));
+    assert_eq!("foo", guard.nested.name);
+}
+
+#[test]
+fn guard_outlives_store() {
+    let fetcher = SwapFetcher::new(config(1));
+    let guard = fetcher.load_guard();
+
+    fetcher.store(config(2));
+
+    assert_eq!(1, guard.limit);
+    assert_eq!(2, fetcher.latest_snapshot().limit);
+}
+
+#[test]
+fn projection_matches_shared_fetcher() {
+    let fetcher = Arc::new(SwapFetcher::new(config(1)));
+    let shared: SharedConfigFetcher<HotNested> = as_shared_fetcher(&fetcher);
+    let projected = fetcher.project::<HotNested>();
+
+    assert!(Arc::ptr_eq(
+        &shared.latest_snapshot(),
+        &projected.latest_snapshot()
+    ));
+
+    let nested = Arc::new(HotNested {
+        name: "bar".to_string(),
+    });
+    fetcher.store(Arc::new(HotConfig {
+        limit: 2,
+        nested: nested.clone(),
+    }));
+
+    assert!(Arc::ptr_eq(&nested, &shared.latest_snapshot()));
+    assert!(Arc::ptr_eq(&nested, &projected.latest_snapshot()));
+
+    // The identity projection is a clone of the root rather than the stored snapshot itself
+    let root = fetcher.project::<HotConfig>().latest_snapshot();
+    assert!(*root == *fetcher.latest_snapshot());
+}
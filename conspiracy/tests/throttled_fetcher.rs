@@ -0,0 +1,143 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc, Arc, Barrier, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use conspiracy::config::{fetcher::ThrottledFetcher, shared_fetcher_from_fn, ConfigFetcher};
+
+/// A clock that only moves when advanced, so the tests don't depend on real timing.
+#[derive(Clone)]
+struct ManualClock(Arc<Mutex<Instant>>);
+
+impl ManualClock {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+
+    fn reader(&self) -> impl Fn() -> Instant + Send + Sync + 'static {
+        let now = self.0.clone();
+        move || *now.lock().unwrap()
+    }
+}
+
+#[test]
+fn inner_called_once_per_interval() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let inner = shared_fetcher_from_fn(move || Arc::new(counter.fetch_add(1, Ordering::SeqCst)));
+    let clock = ManualClock::new();
+
+    let fetcher = ThrottledFetcher::with_clock(inner, Duration::from_secs(1), clock.reader());
+    assert_eq!(1, calls.load(Ordering::SeqCst));
+
+    assert_eq!(0, *fetcher.latest_snapshot());
+    clock.advance(Duration::from_millis(999));
+    assert_eq!(0, *fetcher.latest_snapshot());
+    assert_eq!(1, calls.load(Ordering::SeqCst));
+
+    clock.advance(Duration::from_millis(1));
+
+    assert_eq!(1, *fetcher.latest_snapshot());
+    assert_eq!(1, *fetcher.latest_snapshot());
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+}
+
+#[test]
+fn burst_against_stale_snapshot_refreshes_once() {
+    const READERS: usize = 16;
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let (entered, refresh_entered) = mpsc::channel();
+    let (release_refresh, released) = mpsc::channel::<()>();
+    let released = Mutex::new(released);
+    let inner = shared_fetcher_from_fn(move || {
+        let call = counter.fetch_add(1, Ordering::SeqCst);
+        // A slow backend, the refresh is held until the burst has been served
+        if call > 0 {
+            entered.send(()).unwrap();
+            released.lock().unwrap().recv().unwrap();
+        }
+        Arc::new(call)
+    });
+    let clock = ManualClock::new();
+
+    let fetcher = Arc::new(ThrottledFetcher::with_clock(
+        inner,
+        Duration::from_secs(1),
+        clock.reader(),
+    ));
+    clock.advance(Duration::from_secs(2));
+
+    let refreshing = {
+        let fetcher = fetcher.clone();
+        thread::spawn(move || *fetcher.latest_snapshot())
+    };
+    refresh_entered.recv().unwrap();
+
+    // Every other reader is served the stale snapshot without waiting on the refresh
+    let snapshots = (1..READERS)
+        .map(|_| {
+            let fetcher = fetcher.clone();
+            thread::spawn(move || *fetcher.latest_snapshot())
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|reader| reader.join().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![0; READERS - 1], snapshots);
+
+    release_refresh.send(()).unwrap();
+    assert_eq!(1, refreshing.join().unwrap());
+
+    // The initial fetch plus a single refresh
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+    assert_eq!(1, *fetcher.latest_snapshot());
+}
+
+#[test]
+fn concurrent_stale_readers_fetch_once() {
+    const READERS: usize = 16;
+    const ROUNDS: u32 = 500;
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let inner = shared_fetcher_from_fn(move || Arc::new(counter.fetch_add(1, Ordering::SeqCst)));
+    let clock = ManualClock::new();
+    let fetcher = Arc::new(ThrottledFetcher::with_clock(
+        inner,
+        Duration::from_secs(1),
+        clock.reader(),
+    ));
+
+    for round in 1..=ROUNDS {
+        clock.advance(Duration::from_secs(2));
+
+        // Released together, so readers that saw the stale snapshot race the one refreshing it
+        let start = Arc::new(Barrier::new(READERS));
+        let readers = (0..READERS)
+            .map(|_| {
+                let fetcher = fetcher.clone();
+                let start = start.clone();
+                thread::spawn(move || {
+                    start.wait();
+                    fetcher.latest_snapshot();
+                })
+            })
+            .collect::<Vec<_>>();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        // The initial fetch plus exactly one refresh per stale interval
+        assert_eq!(round + 1, calls.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,225 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{PollingFetcher, RestartAwareFetcher, SwapFetcher},
+    ConfigFetcher,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+config_struct!(
+    pub struct Config {
+        #[conspiracy(restart)]
+        port: u16,
+        timeout_ms: u32,
+    }
+);
+
+#[derive(Debug)]
+struct CapturedEvent {
+    level: Level,
+    fields: HashMap<String, String>,
+}
+
+/// Records every event, so the tests don't depend on a full subscriber implementation.
+#[derive(Clone, Default)]
+struct CapturingSubscriber {
+    events: Arc<Mutex<Vec<CapturedEvent>>>,
+}
+
+impl Visit for CapturedEvent {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl Subscriber for CapturingSubscriber {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut captured = CapturedEvent {
+            level: *event.metadata().level(),
+            fields: HashMap::new(),
+        };
+        event.record(&mut captured);
+        self.events.lock().unwrap().push(captured);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn removed_features_are_reported() {
+    conspiracy::feature_control::define_features!(
+        pub enum Features {
+            Foo => false,
+        }
+    );
+
+    let subscriber = CapturingSubscriber::default();
+    let events = subscriber.events.clone();
+
+    let state = tracing::subscriber::with_default(subscriber, || {
+        serde_json::from_str::<FeaturesState>(r#"{ "foo": true, "removed": true }"#)
+    });
+    assert!(state.unwrap().foo);
+
+    let events = events.lock().unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!(Level::WARN, events[0].level);
+    assert_eq!("[\"removed\"]", events[0].fields["unknown_features"]);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn deprecated_fields_are_reported() {
+    config_struct!(
+        #[conspiracy_macros::full_serde]
+        pub struct ServerConfig {
+            port: u16,
+            #[conspiracy(deprecated = "use `port` instead")]
+            listen_port: Option<u16>,
+        }
+    );
+
+    let subscriber = CapturingSubscriber::default();
+    let events = subscriber.events.clone();
+
+    let config = tracing::subscriber::with_default(subscriber, || {
+        conspiracy::config::deprecated::from_value::<ServerConfig>(
+            serde_json::json!({ "port": 80, "listen_port": 80 }),
+        )
+    });
+    assert_eq!(Some(80), config.unwrap().listen_port);
+
+    let events = events.lock().unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!(Level::WARN, events[0].level);
+    assert_eq!("\"listen_port\"", events[0].fields["field"]);
+    assert_eq!("\"use `port` instead\"", events[0].fields["message"]);
+}
+
+#[test]
+fn polling_reload_emits_events() {
+    let subscriber = CapturingSubscriber::default();
+    let events = subscriber.events.clone();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let fail = Mutex::new(false);
+        let fetcher = PollingFetcher::new(|| match *fail.lock().unwrap() {
+            false => Ok(Config {
+                port: 80,
+                timeout_ms: 10,
+            }),
+            true => Err("bad config"),
+        })
+        .unwrap();
+
+        fetcher.reload().unwrap();
+        *fail.lock().unwrap() = true;
+        assert!(fetcher.reload().is_err());
+    });
+
+    let events = events.lock().unwrap();
+    assert_eq!(2, events.len());
+
+    assert_eq!(Level::INFO, events[0].level);
+    assert_eq!("Applied new config snapshot", events[0].fields["message"]);
+
+    assert_eq!(Level::WARN, events[1].level);
+    assert_eq!("bad config", events[1].fields["error"]);
+    assert_eq!("1", events[1].fields["consecutive_failures"]);
+}
+
+#[test]
+fn polling_reload_reports_restarts() {
+    let subscriber = CapturingSubscriber::default();
+    let events = subscriber.events.clone();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let port = Mutex::new(80);
+        let fetcher = PollingFetcher::new(|| {
+            Ok::<_, &str>(Config {
+                port: *port.lock().unwrap(),
+                timeout_ms: 10,
+            })
+        })
+        .unwrap()
+        .report_restarts();
+
+        fetcher.reload().unwrap();
+        *port.lock().unwrap() = 8080;
+        fetcher.reload().unwrap();
+    });
+
+    let events = events.lock().unwrap();
+    assert_eq!(2, events.len());
+
+    assert_eq!("Applied new config snapshot", events[0].fields["message"]);
+    assert_eq!("false", events[0].fields["restart_required"]);
+    assert_eq!("[]", events[0].fields["restart_reasons"]);
+
+    assert_eq!("true", events[1].fields["restart_required"]);
+    assert_eq!("[\"port\"]", events[1].fields["restart_reasons"]);
+}
+
+#[test]
+fn restart_aware_fetcher_reports_restart_reasons() {
+    let subscriber = CapturingSubscriber::default();
+    let events = subscriber.events.clone();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let source = Arc::new(SwapFetcher::new(Arc::new(Config {
+            port: 80,
+            timeout_ms: 10,
+        })));
+        let fetcher = RestartAwareFetcher::new(source.clone(), |_: Arc<Config>| {});
+
+        // The baseline isn't a change
+        fetcher.latest_snapshot();
+
+        source.store(Arc::new(Config {
+            port: 80,
+            timeout_ms: 20,
+        }));
+        fetcher.latest_snapshot();
+
+        source.store(Arc::new(Config {
+            port: 8080,
+            timeout_ms: 20,
+        }));
+        fetcher.latest_snapshot();
+    });
+
+    let events = events.lock().unwrap();
+    assert_eq!(2, events.len());
+
+    assert_eq!("false", events[0].fields["restart_required"]);
+    assert_eq!("[]", events[0].fields["restart_reasons"]);
+
+    assert_eq!("true", events[1].fields["restart_required"]);
+    assert_eq!("[\"port\"]", events[1].fields["restart_reasons"]);
+}
@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+use conspiracy::config::config_struct;
+
+fn different_second(a: &Duration, b: &Duration) -> bool {
+    a.as_secs() != b.as_secs()
+}
+
+config_struct!(
+    pub struct AppConfig {
+        #[conspiracy(compare = "different_second")]
+        timeout: Duration,
+    }
+);
+
+fn main() {}
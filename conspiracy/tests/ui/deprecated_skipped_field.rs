@@ -0,0 +1,13 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy::config::full_serde]
+    pub struct Config {
+        port: u16,
+        #[conspiracy(deprecated = "no longer used")]
+        #[serde(skip)]
+        legacy: u16,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,14 @@
+use conspiracy::config::ConfigStruct;
+
+#[derive(Clone, PartialEq, ConfigStruct)]
+pub struct DatabaseConfig {
+    pub url: String,
+}
+
+#[derive(Clone, PartialEq, ConfigStruct)]
+pub struct AppConfig {
+    #[conspiracy(nested)]
+    pub database: DatabaseConfig,
+}
+
+fn main() {}
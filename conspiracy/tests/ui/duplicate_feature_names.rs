@@ -0,0 +1,10 @@
+use conspiracy::feature_control::define_features;
+
+define_features!(
+    pub enum Features {
+        FooBar => false,
+        Foo_Bar => true,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,16 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct Database {
+        url: String,
+    }
+);
+
+config_struct!(
+    pub struct AppConfig {
+        primary: @Database,
+        replica: @Database,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,12 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy::config::full_serde]
+    pub struct Config {
+        #[serde(rename = "port")]
+        listen_port: u16,
+        port: u16,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,17 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy::config::full_serde]
+    pub struct Config {
+        nested:
+            #[conspiracy::config::full_serde]
+            #[serde(rename_all = "camelCase")]
+            pub struct Nested {
+                max_connections: u32,
+                #[serde(rename(deserialize = "maxConnections"))]
+                connection_limit: u32,
+            }
+    }
+);
+
+fn main() {}
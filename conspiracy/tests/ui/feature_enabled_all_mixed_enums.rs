@@ -0,0 +1,17 @@
+use conspiracy::feature_control::{define_features, feature_enabled_all};
+
+define_features!(
+    pub enum Features {
+        Foo => true,
+    }
+);
+
+define_features!(
+    pub enum OtherFeatures {
+        Bar => true,
+    }
+);
+
+fn main() {
+    feature_enabled_all!(Features::Foo, OtherFeatures::Bar);
+}
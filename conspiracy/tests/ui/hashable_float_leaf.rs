@@ -0,0 +1,14 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy(hashable)]
+    pub struct PricingConfig {
+        currency: String,
+        discount: pub struct DiscountConfig {
+            percent: u8,
+            ratio: Option<f64>,
+        }
+    }
+);
+
+fn main() {}
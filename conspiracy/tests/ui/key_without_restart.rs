@@ -0,0 +1,16 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct Endpoint {
+        id: String,
+    }
+);
+
+config_struct!(
+    pub struct RoutingConfig {
+        #[conspiracy(key = "id")]
+        endpoints: Vec<Endpoint>,
+    }
+);
+
+fn main() {}
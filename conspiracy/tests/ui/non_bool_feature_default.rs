@@ -0,0 +1,9 @@
+use conspiracy::feature_control::define_features;
+
+define_features!(
+    pub enum Features {
+        UseQuic => 1,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,25 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct Database {
+        url: String,
+    }
+);
+
+// The same field names at different depths are fine, only the types must be unique
+config_struct!(
+    pub struct AppConfig {
+        primary: @Database,
+        replica: pub struct ReplicaConfig {
+            url: String,
+            cache: pub struct CacheConfig {
+                url: String,
+            }
+        },
+        cache: pub struct AppCacheConfig {
+            url: String,
+        }
+    }
+);
+
+fn main() {}
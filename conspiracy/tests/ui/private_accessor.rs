@@ -0,0 +1,21 @@
+mod settings {
+    use conspiracy::config::config_struct;
+
+    config_struct!(
+        pub struct Config {
+            pub public: pub struct Public {
+                foo: u32,
+            },
+            private: pub struct Private {
+                bar: u32,
+            }
+        }
+    );
+}
+
+fn read(config: &settings::Config) {
+    let _ = config.public();
+    let _ = config.private();
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+mod settings {
+    use conspiracy::config::config_struct;
+
+    config_struct!(
+        pub struct Config {
+            pub public: pub struct Public {
+                foo: u32,
+            },
+            private: pub struct Private {
+                bar: u32,
+            }
+        }
+    );
+}
+
+use std::sync::Arc;
+
+fn replace(config: &settings::Config, public: Arc<settings::Public>, private: Arc<settings::Private>) {
+    let _ = config.replace_public(public);
+    let _ = config.replace_private(private);
+}
+
+fn main() {}
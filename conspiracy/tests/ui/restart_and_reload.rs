@@ -0,0 +1,10 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct AppConfig {
+        #[conspiracy(restart, change = "reload")]
+        log_level: String,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,14 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy(no_restart)]
+    pub struct Config {
+        foo: u32,
+        nested: pub struct Nested {
+            #[conspiracy(restart)]
+            bar: u32,
+        }
+    }
+);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use conspiracy::config::ConfigStruct;
+
+#[derive(ConfigStruct, Clone)]
+#[conspiracy(no_restart)]
+pub struct Config {
+    #[conspiracy(restart)]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy(no_restart)]
+    pub struct Config {
+        backend: enum Backend {
+            Disk(struct DiskBackend {
+                #[conspiracy(restart)]
+                path: String,
+            }),
+        },
+    }
+);
+
+fn main() {}
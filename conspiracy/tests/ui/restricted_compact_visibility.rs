@@ -0,0 +1,15 @@
+mod restricted {
+    pub mod config {
+        use conspiracy::config::config_struct;
+
+        config_struct!(
+            pub(in crate::restricted) struct Limits {
+                per_second: u32,
+            }
+        );
+    }
+}
+
+fn main() {
+    let _: Option<restricted::config::CompactLimits> = None;
+}
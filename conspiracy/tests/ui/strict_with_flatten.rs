@@ -0,0 +1,15 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    #[conspiracy::config::full_serde]
+    #[conspiracy(strict)]
+    pub struct Config {
+        foo: u32,
+        nested: #[conspiracy::config::full_serde] pub struct Nested {
+            #[serde(flatten)]
+            extra: std::collections::HashMap<String, u32>,
+        }
+    }
+);
+
+fn main() {}
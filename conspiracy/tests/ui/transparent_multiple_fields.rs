@@ -0,0 +1,14 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct Config {
+        timeout:
+            #[conspiracy(transparent)]
+            pub struct Timeout {
+                seconds: u64,
+                retries: u32,
+            }
+    }
+);
+
+fn main() {}
@@ -0,0 +1,20 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct Timeout(u64);
+);
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+        limits: pub struct Limits(u32),
+    }
+);
+
+config_struct!(
+    pub struct Unnamed {
+        u16,
+    }
+);
+
+fn main() {}
@@ -0,0 +1,54 @@
+#![cfg(feature = "json")]
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{SwapFetcher, ValueFetcher},
+    full_serde,
+};
+use serde_json::json;
+
+config_struct!(
+    #[full_serde]
+    pub struct AppConfig {
+        pub port: u16,
+        pub database: #[full_serde] pub struct DatabaseConfig {
+            #[serde(rename = "connection_url")]
+            pub url: String,
+            pub pool_size: u32,
+        }
+    }
+);
+
+fn config(port: u16) -> Arc<AppConfig> {
+    Arc::new(AppConfig {
+        port,
+        database: Arc::new(DatabaseConfig {
+            url: "db:5432".to_string(),
+            pool_size: 4,
+        }),
+    })
+}
+
+#[test]
+fn nested_config_is_exposed_as_value() {
+    let swap = Arc::new(SwapFetcher::new(config(8080)));
+    let fetcher = ValueFetcher::new(swap.clone());
+
+    let value = fetcher.as_value();
+    assert_eq!(json!(8080), value["port"]);
+    assert_eq!(json!("db:5432"), value["database"]["connection_url"]);
+    assert_eq!(json!(4), value["database"]["pool_size"]);
+
+    // Each call reflects the latest snapshot
+    swap.store(config(9090));
+    assert_eq!(json!(9090), fetcher.as_value()["port"]);
+}
+
+#[test]
+fn unrepresentable_config_is_an_error() {
+    let fetcher = ValueFetcher::new(SwapFetcher::new(Arc::new(BTreeMap::from([((1, 2), true)]))));
+
+    assert!(fetcher.try_as_value().is_err());
+}
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{SwapFetcher, VersionedFetcher},
+    ConfigFetcher,
+};
+
+config_struct!(
+    pub struct AppConfig {
+        port: u16,
+    }
+);
+
+#[test]
+fn version_only_changes_with_the_snapshot() {
+    let source = Arc::new(SwapFetcher::new(Arc::new(AppConfig { port: 80 })));
+    let fetcher = VersionedFetcher::new(source.clone());
+
+    let (version, snapshot) = fetcher.latest_versioned();
+    assert_eq!(0, version);
+    assert_eq!(80, snapshot.port);
+    assert_eq!(0, fetcher.latest_versioned().0);
+
+    // A new but equal snapshot keeps the version
+    source.store(Arc::new(AppConfig { port: 80 }));
+    assert_eq!(0, fetcher.latest_versioned().0);
+
+    source.store(Arc::new(AppConfig { port: 8080 }));
+    let (version, snapshot) = fetcher.latest_versioned();
+    assert_eq!(1, version);
+    assert_eq!(8080, snapshot.port);
+    assert_eq!(1, fetcher.latest_versioned().0);
+
+    source.store(Arc::new(AppConfig { port: 80 }));
+    assert_eq!(2, fetcher.latest_versioned().0);
+    assert_eq!(80, fetcher.latest_snapshot().port);
+}
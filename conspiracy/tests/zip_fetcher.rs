@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use conspiracy::config::{
+    config_struct,
+    fetcher::{SwapFetcher, ZipFetcher},
+    shared_fetcher_from_static, ConfigFetcher,
+};
+
+config_struct!(
+    pub struct ServerConfig {
+        port: u16,
+    }
+);
+
+config_struct!(
+    pub struct ClientConfig {
+        retries: u32,
+    }
+);
+
+#[test]
+fn zips_static_fetchers() {
+    let server = Arc::new(ServerConfig { port: 8080 });
+    let client = Arc::new(ClientConfig { retries: 3 });
+    let fetcher = ZipFetcher::new(
+        shared_fetcher_from_static(server.clone()),
+        shared_fetcher_from_static(client.clone()),
+    );
+
+    let snapshot = fetcher.latest_snapshot();
+    let (zipped_server, zipped_client) = &*snapshot;
+    assert_eq!(8080, zipped_server.port);
+    assert_eq!(3, zipped_client.retries);
+    assert!(Arc::ptr_eq(&server, zipped_server));
+    assert!(Arc::ptr_eq(&client, zipped_client));
+
+    // Neither source changed, so the pair is shared
+    assert!(Arc::ptr_eq(&snapshot, &fetcher.latest_snapshot()));
+}
+
+#[test]
+fn either_source_changing_produces_a_new_pair() {
+    let server = Arc::new(SwapFetcher::new(Arc::new(ServerConfig { port: 80 })));
+    let fetcher = ZipFetcher::new(
+        server.clone(),
+        shared_fetcher_from_static(Arc::new(ClientConfig { retries: 3 })),
+    );
+
+    let before = fetcher.latest_snapshot();
+    server.store(Arc::new(ServerConfig { port: 8080 }));
+    let after = fetcher.latest_snapshot();
+
+    assert!(!Arc::ptr_eq(&before, &after));
+    assert_eq!(80, before.0.port);
+    assert_eq!(8080, after.0.port);
+    assert!(Arc::ptr_eq(&before.1, &after.1));
+}
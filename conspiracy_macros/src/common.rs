@@ -1,37 +1,213 @@
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Attribute, Path};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{meta::ParseNestedMeta, Attribute, Ident, LitStr, Path};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub(crate) enum ConspiracyAttribute {
+    /// `restart` or `change = "restart"`, a change requires a restart.
     Restart,
+    /// `change = "reload"`, a change requires a reload rather than a restart.
+    Reload,
+    NoDefault,
+    /// Stores the leaf behind an `Arc`, lifting the `Clone` requirement.
+    Shared,
+    /// `env = "VAR"`, the environment variable that overrides the field.
+    Env(String),
+    /// `owner = "team"`, the owner of a feature.
+    Owner(String),
+    /// `group = "name"`, a group the feature belongs to.
+    Group(String),
+    /// The feature must be explicitly set when building its state.
+    Required,
+    /// `key = "field"`, compares a restart marked collection by the entries' `field` rather than
+    /// by position.
+    Key(Ident),
+    /// `compare = "path::to::fn"`, a `fn(&T, &T) -> bool` deciding if a restart marked field
+    /// changed, used in place of `!=`.
+    Compare(ComparePath),
+    /// The field's value is redacted from `to_dotted_string`.
+    Secret,
+    /// An `Arc` wrapped sub-config that also derives `ConfigStruct`.
+    Nested,
+    /// `deprecated = "message"`, the field is still accepted but reported when present in a loaded
+    /// document.
+    Deprecated(String),
+}
+
+/// The path of a `compare = "..."` function. [`Path`] only implements [`PartialEq`] with syn's
+/// `extra-traits` feature, so paths are compared by their tokens instead.
+#[derive(Clone)]
+pub(crate) struct ComparePath(pub(crate) Path);
+
+impl PartialEq for ComparePath {
+    fn eq(&self, other: &Self) -> bool {
+        let (this, other) = (&self.0, &other.0);
+        quote! { #this }.to_string() == quote! { #other }.to_string()
+    }
 }
 
+impl ConspiracyAttribute {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ConspiracyAttribute::Restart => "restart",
+            ConspiracyAttribute::Reload => "change = \"reload\"",
+            ConspiracyAttribute::NoDefault => "no_default",
+            ConspiracyAttribute::Shared => "shared",
+            ConspiracyAttribute::Env(_) => "env",
+            ConspiracyAttribute::Owner(_) => "owner",
+            ConspiracyAttribute::Group(_) => "group",
+            ConspiracyAttribute::Required => "required",
+            ConspiracyAttribute::Key(_) => "key",
+            ConspiracyAttribute::Compare(_) => "compare",
+            ConspiracyAttribute::Secret => "secret",
+            ConspiracyAttribute::Nested => "nested",
+            ConspiracyAttribute::Deprecated(_) => "deprecated",
+        }
+    }
+}
+
+/// Removes the `#[conspiracy(...)]` attributes from a field, returning those that were present.
+/// Multiple attributes may be combined, e.g. `#[conspiracy(restart, no_default)]`.
 pub(crate) fn extract_conspiracy_attributes(
     attrs: &mut Vec<Attribute>,
-) -> Option<ConspiracyAttribute> {
-    let mut extracted_attr = None;
+) -> syn::Result<Vec<ConspiracyAttribute>> {
+    extract_nested_conspiracy_attributes(attrs, "field", |meta| {
+        if meta.path.is_ident("restart") {
+            Ok(Some(ConspiracyAttribute::Restart))
+        } else if meta.path.is_ident("change") {
+            let change: LitStr = meta.value()?.parse()?;
+            match change.value().as_str() {
+                "restart" => Ok(Some(ConspiracyAttribute::Restart)),
+                "reload" => Ok(Some(ConspiracyAttribute::Reload)),
+                _ => Err(syn::Error::new_spanned(
+                    change,
+                    "expected `change = \"restart\"` or `change = \"reload\"`",
+                )),
+            }
+        } else if meta.path.is_ident("no_default") {
+            Ok(Some(ConspiracyAttribute::NoDefault))
+        } else if meta.path.is_ident("shared") {
+            Ok(Some(ConspiracyAttribute::Shared))
+        } else if meta.path.is_ident("env") {
+            let var: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyAttribute::Env(var.value())))
+        } else if meta.path.is_ident("owner") {
+            let owner: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyAttribute::Owner(owner.value())))
+        } else if meta.path.is_ident("group") {
+            let group: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyAttribute::Group(group.value())))
+        } else if meta.path.is_ident("required") {
+            Ok(Some(ConspiracyAttribute::Required))
+        } else if meta.path.is_ident("key") {
+            let key: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyAttribute::Key(key.parse()?)))
+        } else if meta.path.is_ident("secret") {
+            Ok(Some(ConspiracyAttribute::Secret))
+        } else if meta.path.is_ident("nested") {
+            Ok(Some(ConspiracyAttribute::Nested))
+        } else if meta.path.is_ident("deprecated") {
+            let message: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyAttribute::Deprecated(message.value())))
+        } else if meta.path.is_ident("compare") {
+            let compare: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyAttribute::Compare(ComparePath(
+                compare.parse()?,
+            ))))
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) enum ConspiracyStructAttribute {
+    Strict,
+    Schema,
+    /// Derives `arbitrary::Arbitrary` for the whole hierarchy, for fuzzing.
+    Arbitrary,
+    /// Derives `Eq` and `Hash` for the whole hierarchy, e.g. to key a cache by config.
+    Hashable,
+    /// Generates a builder for every struct in the hierarchy.
+    Builder,
+    NoRestart,
+    /// Generates `Deref` / `AsRef` to the struct's only field.
+    Transparent,
+    /// `restart_group = "name"`, a feature group where any change requires a restart.
+    RestartGroup(String),
+}
+
+/// Struct level counterpart of [`extract_conspiracy_attributes`], e.g. `#[conspiracy(strict)]`.
+pub(crate) fn extract_conspiracy_struct_attributes(
+    attrs: &mut Vec<Attribute>,
+) -> syn::Result<Vec<ConspiracyStructAttribute>> {
+    extract_nested_conspiracy_attributes(attrs, "struct", |meta| {
+        if meta.path.is_ident("strict") {
+            Ok(Some(ConspiracyStructAttribute::Strict))
+        } else if meta.path.is_ident("schema") {
+            Ok(Some(ConspiracyStructAttribute::Schema))
+        } else if meta.path.is_ident("arbitrary") {
+            Ok(Some(ConspiracyStructAttribute::Arbitrary))
+        } else if meta.path.is_ident("hashable") {
+            Ok(Some(ConspiracyStructAttribute::Hashable))
+        } else if meta.path.is_ident("builder") {
+            Ok(Some(ConspiracyStructAttribute::Builder))
+        } else if meta.path.is_ident("no_restart") {
+            Ok(Some(ConspiracyStructAttribute::NoRestart))
+        } else if meta.path.is_ident("transparent") {
+            Ok(Some(ConspiracyStructAttribute::Transparent))
+        } else if meta.path.is_ident("restart_group") {
+            let group: LitStr = meta.value()?.parse()?;
+            Ok(Some(ConspiracyStructAttribute::RestartGroup(group.value())))
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+fn extract_nested_conspiracy_attributes<T>(
+    attrs: &mut Vec<Attribute>,
+    target: &str,
+    mut recognize: impl FnMut(&ParseNestedMeta) -> syn::Result<Option<T>>,
+) -> syn::Result<Vec<T>> {
+    let mut extracted_attrs = Vec::new();
+    let mut result = Ok(());
+
     attrs.retain(|attr| {
-        if attr.path().is_ident("conspiracy") {
-            let kind: Path = attr.parse_args().unwrap();
-            if kind.is_ident("restart") {
-                try_set_attribute(&mut extracted_attr, ConspiracyAttribute::Restart);
-                return false;
+        if !attr.path().is_ident("conspiracy") {
+            return true;
+        }
+
+        let parsed = attr.parse_nested_meta(|meta| match recognize(&meta)? {
+            Some(extracted) => {
+                extracted_attrs.push(extracted);
+                Ok(())
             }
+            None => Err(meta.error(format!("unsupported conspiracy {target} attribute"))),
+        });
+
+        if let Err(error) = parsed {
+            result = Err(error);
         }
 
-        true
+        false
     });
 
-    extracted_attr
+    result.map(|_| extracted_attrs)
 }
 
-fn try_set_attribute(old_attr: &mut Option<ConspiracyAttribute>, attr: ConspiracyAttribute) {
-    if old_attr.is_none() {
-        *old_attr = Some(attr)
-    } else {
-        panic!("You can't use multiple conspiracy attributes on a single field")
-    }
+/// Checks if any `#[serde(...)]` attribute contains the given top level flag, e.g. `flatten`.
+pub(crate) fn has_serde_flag(attrs: &[Attribute], flag: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| attr.meta.require_list().ok())
+        .any(|list| {
+            list.tokens
+                .clone()
+                .into_iter()
+                .any(|token| matches!(token, TokenTree::Ident(ident) if ident == flag))
+        })
 }
 
 pub(crate) fn restart_required_single_field_comparison(field_expr: TokenStream) -> TokenStream {
@@ -39,3 +215,81 @@ pub(crate) fn restart_required_single_field_comparison(field_expr: TokenStream)
         self.#field_expr != other.#field_expr
     }
 }
+
+/// A restart check for a single field, paired with the dotted path reported by `restart_reasons`.
+pub(crate) struct RestartComparison {
+    pub(crate) path: String,
+    pub(crate) comparison: TokenStream,
+}
+
+pub(crate) fn impl_restart_required(
+    ty: &TokenStream,
+    restart_comparisons: &[RestartComparison],
+    reload_comparisons: &[RestartComparison],
+) -> TokenStream {
+    let (restart_required, restart_reasons) = change_required("restart", restart_comparisons);
+
+    // Fall back to the trait's defaults (never reload) when nothing is marked
+    let reload = (!reload_comparisons.is_empty()).then(|| {
+        let (reload_required, reload_reasons) = change_required("reload", reload_comparisons);
+        quote! {
+            #[inline]
+            #reload_required
+
+            #reload_reasons
+        }
+    });
+
+    quote! {
+        impl ::conspiracy::config::RestartRequired for #ty {
+            // This is effectively a specialization of PartialEq, which is inlined in derive
+            // generated impls so we do the same here.
+            #[inline]
+            #restart_required
+
+            #restart_reasons
+
+            #reload
+        }
+    }
+}
+
+/// Generates the `{category}_required` and `{category}_reasons` methods for one change category.
+fn change_required(
+    category: &str,
+    comparisons: &[RestartComparison],
+) -> (TokenStream, TokenStream) {
+    let required_fn = format_ident!("{category}_required");
+    let reasons_fn = format_ident!("{category}_reasons");
+    let (paths, comparisons): (Vec<_>, Vec<_>) = comparisons
+        .iter()
+        .map(|RestartComparison { path, comparison }| (path, comparison))
+        .unzip();
+
+    let required = if comparisons.is_empty() {
+        // If no fields were marked, then the change is never required
+        quote! { false }
+    } else {
+        quote! { #(#comparisons)||* }
+    };
+
+    (
+        quote! {
+            fn #required_fn(&self, other: &Self) -> bool {
+                #required
+            }
+        },
+        quote! {
+            fn #reasons_fn(&self, other: &Self) -> Vec<&'static str> {
+                #[allow(unused_mut)]
+                let mut reasons = Vec::new();
+                #(
+                    if #comparisons {
+                        reasons.push(#paths);
+                    }
+                )*
+                reasons
+            }
+        },
+    )
+}
@@ -1,238 +1,2002 @@
+use std::iter::zip;
+
+use convert_case::{Case, Casing};
 use proc_macro::TokenStream as LegacyTokenStream;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::{format_ident, quote};
 use syn::{
     braced,
-    parse::{discouraged::Speculative, Parse, ParseStream},
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
     parse_macro_input, parse_quote,
     punctuated::Punctuated,
     token,
     token::{Colon, Pub},
-    Attribute, Field, FieldMutability, Ident, Token, Type, Visibility,
+    Attribute, Data, DataStruct, DeriveInput, Expr, Field, FieldMutability, Fields,
+    GenericArgument, Ident, LitStr, Meta, Path, PathArguments, Token, Type, Visibility,
+};
+
+use crate::common::{
+    extract_conspiracy_attributes, extract_conspiracy_struct_attributes, has_serde_flag,
+    impl_restart_required, restart_required_single_field_comparison, ConspiracyAttribute,
+    ConspiracyStructAttribute, RestartComparison,
 };
 
-use crate::common::{
-    extract_conspiracy_attributes, restart_required_single_field_comparison, ConspiracyAttribute,
-};
+fn restart_required(input: &mut NestableStruct) -> TokenStream {
+    if input.no_restart {
+        return TokenStream::new();
+    }
+
+    let mut restart_comparisons = Vec::new();
+    build_restart_comparison_for_struct(
+        &mut Vec::new(),
+        &mut restart_comparisons,
+        input,
+        Change::Restart,
+    );
+    let mut reload_comparisons = Vec::new();
+    build_restart_comparison_for_struct(
+        &mut Vec::new(),
+        &mut reload_comparisons,
+        input,
+        Change::Reload,
+    );
+
+    let ty = &input.ty;
+    impl_restart_required(&quote! { #ty }, &restart_comparisons, &reload_comparisons)
+}
+
+/// The category of change tracked by a set of comparisons, selected with
+/// `#[conspiracy(change = "...")]`.
+#[derive(Clone, Copy)]
+enum Change {
+    Restart,
+    Reload,
+}
+
+impl Change {
+    fn attribute(self) -> ConspiracyAttribute {
+        match self {
+            Change::Restart => ConspiracyAttribute::Restart,
+            Change::Reload => ConspiracyAttribute::Reload,
+        }
+    }
+
+    fn required_fn(self) -> Ident {
+        match self {
+            Change::Restart => format_ident!("restart_required"),
+            Change::Reload => format_ident!("reload_required"),
+        }
+    }
+
+    fn reasons_fn(self) -> Ident {
+        match self {
+            Change::Restart => format_ident!("restart_reasons"),
+            Change::Reload => format_ident!("reload_reasons"),
+        }
+    }
+}
+
+fn build_restart_comparison_for_struct(
+    lineage: &mut Vec<Ident>,
+    output: &mut Vec<RestartComparison>,
+    item: &NestableStruct,
+    change: Change,
+) {
+    for field in item.fields.iter() {
+        let marked = field.has_attr(change.attribute());
+        let shared = field.has_attr(ConspiracyAttribute::Shared);
+        let key = field.restart_key().cloned();
+        let compare = field.compare_fn().cloned();
+
+        match &field.kind {
+            NestableFieldKind::NestedStruct(nested_struct) => {
+                if marked {
+                    output.push(comparison_for_field(lineage, &field.field));
+                }
+
+                lineage.push(
+                    field
+                        .field
+                        .ident
+                        .clone()
+                        .expect("Unnamed fields are rejected when parsing"),
+                );
+                build_restart_comparison_for_struct(lineage, output, nested_struct, change);
+                lineage.pop();
+            }
+            // A marked external field already covers any change the external config would signal,
+            // so only one of the two comparisons is needed.
+            NestableFieldKind::External(_)
+            | NestableFieldKind::Derived(_)
+            | NestableFieldKind::NestedEnum(_)
+                if !marked =>
+            {
+                output.push(comparison_for_external_field(lineage, &field.field, change));
+            }
+            NestableFieldKind::OptionalExternal(_) if !marked => {
+                output.push(comparison_for_optional_field(lineage, &field.field, change));
+            }
+            NestableFieldKind::Leaf if marked && compare.is_some() => {
+                let compare = compare.as_ref().expect("Checked by the guard");
+                output.push(comparison_for_custom_field(
+                    lineage,
+                    &field.field,
+                    compare,
+                    shared,
+                ));
+            }
+            NestableFieldKind::Leaf if shared => {
+                if marked {
+                    output.push(comparison_for_shared_field(lineage, &field.field));
+                }
+            }
+            NestableFieldKind::Leaf if marked && key.is_some() => {
+                let key = key.as_ref().expect("Checked by the guard");
+                output.push(comparison_for_keyed_field(lineage, &field.field, key));
+            }
+            NestableFieldKind::External(_)
+            | NestableFieldKind::Derived(_)
+            | NestableFieldKind::NestedEnum(_)
+            | NestableFieldKind::OptionalExternal(_)
+            | NestableFieldKind::Leaf => {
+                if marked {
+                    output.push(comparison_for_field(lineage, &field.field));
+                }
+            }
+        }
+    }
+}
+
+fn field_path(lineage: &[Ident], field: &Field) -> (TokenStream, String) {
+    let field_name = field
+        .ident
+        .as_ref()
+        .expect("Unnamed fields are rejected when parsing");
+    let path = lineage
+        .iter()
+        .chain([field_name])
+        .map(Ident::to_string)
+        .collect::<Vec<_>>()
+        .join(".");
+
+    (quote! { #(#lineage.)*#field_name }, path)
+}
+
+fn comparison_for_field(lineage: &[Ident], field: &Field) -> RestartComparison {
+    let (field_expr, path) = field_path(lineage, field);
+    RestartComparison {
+        path,
+        comparison: restart_required_single_field_comparison(field_expr),
+    }
+}
+
+/// Shared leaves may not implement `PartialEq`, so they're compared by pointer identity.
+fn comparison_for_shared_field(lineage: &[Ident], field: &Field) -> RestartComparison {
+    let (field_expr, path) = field_path(lineage, field);
+    RestartComparison {
+        path,
+        comparison: quote! {
+            !::std::sync::Arc::ptr_eq(&self.#field_expr, &other.#field_expr)
+        },
+    }
+}
+
+/// Keyed collections are compared as maps, so reordering the entries isn't a change.
+fn comparison_for_keyed_field(lineage: &[Ident], field: &Field, key: &Ident) -> RestartComparison {
+    let (field_expr, path) = field_path(lineage, field);
+    RestartComparison {
+        path,
+        comparison: quote! {
+            ::conspiracy::config::macro_targets::keyed_restart_required(
+                &self.#field_expr,
+                &other.#field_expr,
+                |entry| &entry.#key,
+            )
+        },
+    }
+}
+
+/// Fields with `compare = "..."` are changed exactly when the comparison function says so.
+fn comparison_for_custom_field(
+    lineage: &[Ident],
+    field: &Field,
+    compare: &Path,
+    shared: bool,
+) -> RestartComparison {
+    let (field_expr, path) = field_path(lineage, field);
+    // The comparison is over the leaf type, not the `Arc` it's stored behind
+    let deref = shared.then(|| quote! { * });
+    RestartComparison {
+        path,
+        comparison: quote! {
+            #compare(&#deref self.#field_expr, &#deref other.#field_expr)
+        },
+    }
+}
+
+/// External configs aren't visible to the macro (and enum configs depend on the active variant), so
+/// defer to their own [`RestartRequired`] impl.
+fn comparison_for_external_field(
+    lineage: &[Ident],
+    field: &Field,
+    change: Change,
+) -> RestartComparison {
+    let (field_expr, path) = field_path(lineage, field);
+    let required_fn = change.required_fn();
+    RestartComparison {
+        path,
+        comparison: quote! {
+            ::conspiracy::config::RestartRequired::#required_fn(&*self.#field_expr, &*other.#field_expr)
+        },
+    }
+}
+
+/// Like [`comparison_for_external_field`], but the sub-config appearing or disappearing is also a
+/// change, as decided by the [`Option`] implementation of `RestartRequired`.
+fn comparison_for_optional_field(
+    lineage: &[Ident],
+    field: &Field,
+    change: Change,
+) -> RestartComparison {
+    let (field_expr, path) = field_path(lineage, field);
+    let required_fn = change.required_fn();
+    RestartComparison {
+        path,
+        comparison: quote! {
+            ::conspiracy::config::RestartRequired::#required_fn(&self.#field_expr, &other.#field_expr)
+        },
+    }
+}
+
+pub(super) fn config_struct(input: LegacyTokenStream) -> LegacyTokenStream {
+    let mut input = parse_macro_input!(input as NestableStruct);
+    if let Err(error) = apply_attributes(&mut input, InheritedAttributes::default(), true) {
+        return LegacyTokenStream::from(error.to_compile_error());
+    }
+
+    if let Err(error) = check_unique_types(&input) {
+        return LegacyTokenStream::from(error.to_compile_error());
+    }
+
+    let mut output = restart_required(&mut input);
+    output.extend(generate_compact_struct(&input));
+    output.extend(generate_config_structs(input, &mut vec![]));
+
+    LegacyTokenStream::from(output)
+}
+
+pub(super) fn derive_config_struct(input: LegacyTokenStream) -> LegacyTokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let output = derived_struct(input).and_then(|mut input| {
+        // The struct can't be modified, so the shared fields must already be declared as `Arc`
+        let declared_tys = input
+            .fields
+            .iter()
+            .map(|field| field.field.ty.clone())
+            .collect::<Vec<_>>();
+        apply_attributes(&mut input, InheritedAttributes::default(), true)?;
+        for (field, ty) in zip(input.fields.iter_mut(), declared_tys) {
+            field.field.ty = ty;
+        }
+
+        let mut output = restart_required(&mut input);
+        output.extend(generate_compact_struct(&input));
+        output.extend(generate_config_structs(input, &mut vec![]));
+        Ok(output)
+    });
+
+    LegacyTokenStream::from(output.unwrap_or_else(|error| error.to_compile_error()))
+}
+
+/// Converts a user defined struct into the form produced by parsing `config_struct!`, where fields
+/// marked `#[conspiracy(nested)]` are sub-configs that derive `ConfigStruct` themselves.
+fn derived_struct(input: DeriveInput) -> syn::Result<NestableStruct> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`#[derive(ConfigStruct)]` doesn't support generic structs",
+        ));
+    }
+
+    let Data::Struct(DataStruct {
+        struct_token,
+        fields: Fields::Named(named),
+        ..
+    }) = input.data
+    else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`#[derive(ConfigStruct)]` requires a struct with named fields",
+        ));
+    };
+
+    let attrs = input.attrs;
+    // These add derives or serde attributes to the struct, which a derive can't do
+    for attr in extract_conspiracy_struct_attributes(&mut attrs.clone())? {
+        let name = match attr {
+            ConspiracyStructAttribute::Strict => "strict",
+            ConspiracyStructAttribute::Schema => "schema",
+            ConspiracyStructAttribute::Arbitrary => "arbitrary",
+            ConspiracyStructAttribute::Hashable => "hashable",
+            ConspiracyStructAttribute::NoRestart
+            | ConspiracyStructAttribute::Builder
+            | ConspiracyStructAttribute::Transparent
+            | ConspiracyStructAttribute::RestartGroup(_) => continue,
+        };
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "`#[conspiracy({name})]` can't be used with `#[derive(ConfigStruct)]`, apply the \
+                serde attributes or derives it stands for to the struct directly"
+            ),
+        ));
+    }
+
+    let mut fields = Punctuated::new();
+    for field in named.named {
+        let nested = extract_conspiracy_attributes(&mut field.attrs.clone())?
+            .contains(&ConspiracyAttribute::Nested);
+        let kind = if nested {
+            NestableFieldKind::Derived(derived_sub_config_ty(&field)?)
+        } else {
+            NestableFieldKind::Leaf
+        };
+
+        fields.push(NestableField {
+            field,
+            conspiracy_attrs: Vec::new(),
+            kind,
+        });
+    }
+
+    Ok(NestableStruct {
+        attrs,
+        vis: input.vis,
+        struct_token,
+        ty: ident_to_type(input.ident),
+        _brace_token: named.brace_token,
+        fields,
+        schema: false,
+        no_restart: false,
+        transparent: false,
+        builder: false,
+        derived: true,
+    })
+}
+
+/// The `T` of a `#[conspiracy(nested)]` field declared as `Arc<T>`.
+fn derived_sub_config_ty(field: &Field) -> syn::Result<Type> {
+    if let Type::Path(ty) = &field.ty {
+        if let Some(segment) = ty
+            .path
+            .segments
+            .last()
+            .filter(|segment| segment.ident == "Arc")
+        {
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(GenericArgument::Type(Type::Path(inner))) = args.args.first() {
+                    if args.args.len() == 1 && inner.qself.is_none() {
+                        return Ok(Type::Path(inner.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        &field.ty,
+        "`#[conspiracy(nested)]` fields must be declared as `Arc<T>`, where `T` also derives \
+        `ConfigStruct`",
+    ))
+}
+
+/// The compact form of a derived sub-config, which lives next to it, e.g. `a::CompactB` for `a::B`.
+fn compact_derived_ty(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    if let Type::Path(path) = &mut ty {
+        if let Some(segment) = path.path.segments.last_mut() {
+            segment.ident = format_ident!("Compact{}", segment.ident);
+        }
+    }
+    ty
+}
+
+/// Struct attributes that, once applied to a struct, also apply to every struct nested beneath it.
+#[derive(Clone, Copy, Default)]
+struct InheritedAttributes {
+    strict: bool,
+    schema: bool,
+    arbitrary: bool,
+    hashable: bool,
+    builder: bool,
+    no_restart: bool,
+}
+
+/// Consumes the `#[conspiracy(...)]` struct and field attributes of the hierarchy, applying their
+/// effects.
+fn apply_attributes(
+    item: &mut NestableStruct,
+    mut inherited: InheritedAttributes,
+    root: bool,
+) -> syn::Result<()> {
+    for attr in extract_conspiracy_struct_attributes(&mut item.attrs)? {
+        match attr {
+            ConspiracyStructAttribute::Strict => inherited.strict = true,
+            ConspiracyStructAttribute::Schema => inherited.schema = true,
+            ConspiracyStructAttribute::Arbitrary => inherited.arbitrary = true,
+            ConspiracyStructAttribute::Hashable => inherited.hashable = true,
+            ConspiracyStructAttribute::Builder => inherited.builder = true,
+            // Only the root implements `RestartRequired`, and a nested struct without `PartialEq`
+            // would break the derive of its parent.
+            ConspiracyStructAttribute::NoRestart if !root => {
+                return Err(syn::Error::new_spanned(
+                    &item.ty,
+                    "`#[conspiracy(no_restart)]` can only be applied to the root struct, where it \
+                    applies to the whole config",
+                ))
+            }
+            ConspiracyStructAttribute::NoRestart => inherited.no_restart = true,
+            ConspiracyStructAttribute::Transparent if item.fields.len() != 1 => {
+                return Err(syn::Error::new_spanned(
+                    &item.ty,
+                    "`#[conspiracy(transparent)]` requires the struct to have exactly one field",
+                ))
+            }
+            ConspiracyStructAttribute::Transparent => item.transparent = true,
+            ConspiracyStructAttribute::RestartGroup(_) => {
+                return Err(syn::Error::new_spanned(
+                    &item.ty,
+                    "`#[conspiracy(restart_group = \"...\")]` only applies to `define_features!`",
+                ))
+            }
+        }
+    }
+
+    item.no_restart = inherited.no_restart;
+    item.builder = inherited.builder;
+
+    // `Eq` builds on the `PartialEq` derive that `no_restart` skips
+    if inherited.hashable && inherited.no_restart {
+        return Err(syn::Error::new_spanned(
+            &item.ty,
+            "`#[conspiracy(hashable)]` can't be combined with `#[conspiracy(no_restart)]`, which \
+            skips the `PartialEq` derive that `Eq` requires",
+        ));
+    }
+
+    if inherited.strict {
+        apply_strict(item)?;
+    }
+
+    if inherited.schema {
+        item.schema = true;
+        item.attrs
+            .push(parse_quote! { #[derive(::schemars::JsonSchema)] });
+    }
+
+    if inherited.arbitrary {
+        item.attrs
+            .push(parse_quote! { #[derive(::arbitrary::Arbitrary)] });
+    }
+
+    if inherited.hashable {
+        item.attrs.push(parse_quote! { #[derive(Eq, Hash)] });
+    }
+
+    if !item.derived {
+        apply_serde_as(item);
+    }
+
+    let deserializable = derives_deserialize(&item.attrs);
+    if deserializable {
+        check_wire_name_collisions(item)?;
+    }
+
+    for field in item.fields.iter_mut() {
+        field.conspiracy_attrs = extract_conspiracy_attributes(&mut field.field.attrs)?;
+
+        if let Some(attr) = field.conspiracy_attrs.iter().find(|attr| {
+            matches!(
+                attr,
+                ConspiracyAttribute::Owner(_)
+                    | ConspiracyAttribute::Group(_)
+                    | ConspiracyAttribute::Required
+            )
+        }) {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                format!(
+                    "`#[conspiracy({})]` only applies to features, not config fields",
+                    attr.name()
+                ),
+            ));
+        }
+
+        if field.has_attr(ConspiracyAttribute::Nested)
+            && !matches!(field.kind, NestableFieldKind::Derived(_))
+        {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(nested)]` only applies to `#[derive(ConfigStruct)]`, within \
+                `config_struct!` declare the sub-config inline or as `field: @Type`",
+            ));
+        }
+
+        if field.deprecated().is_some() && parse_serde_naming(&field.field.attrs)?.no_key {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(deprecated = \"...\")]` requires a field with its own key, not a \
+                skipped or flattened field",
+            ));
+        }
+
+        if field.env_var().is_some() && !matches!(field.kind, NestableFieldKind::Leaf) {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(env = \"...\")]` can only override leaf fields, not sub-configs",
+            ));
+        }
+
+        if field.has_attr(ConspiracyAttribute::Restart)
+            && field.has_attr(ConspiracyAttribute::Reload)
+        {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "a field can only have one change category, either `restart` or \
+                `change = \"reload\"`",
+            ));
+        }
+
+        if field.compare_fn().is_some()
+            && (!(field.has_attr(ConspiracyAttribute::Restart)
+                || field.has_attr(ConspiracyAttribute::Reload))
+                || field.restart_key().is_some()
+                || !matches!(field.kind, NestableFieldKind::Leaf))
+        {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(compare = \"...\")]` requires `restart` or `change = \"reload\"`, \
+                only applies to leaf fields and can't be combined with `key`",
+            ));
+        }
+
+        if field.restart_key().is_some()
+            && (!field.has_attr(ConspiracyAttribute::Restart)
+                || field.has_attr(ConspiracyAttribute::Shared)
+                || !matches!(field.kind, NestableFieldKind::Leaf))
+        {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(key = \"...\")]` requires `restart` and only applies to collection \
+                leaf fields that aren't `shared`",
+            ));
+        }
+
+        // A derived struct's fields can't be changed, so its compact form mustn't diverge either.
+        // Applied before `shared` wraps the type in an `Arc`, which would hide the `Option`.
+        if deserializable
+            && !item.derived
+            && matches!(
+                field.kind,
+                NestableFieldKind::Leaf | NestableFieldKind::OptionalExternal(_)
+            )
+            && !field.has_attr(ConspiracyAttribute::NoDefault)
+        {
+            apply_option_default(&mut field.field);
+        }
+
+        if field.has_attr(ConspiracyAttribute::Shared) {
+            if !matches!(field.kind, NestableFieldKind::Leaf) {
+                return Err(syn::Error::new_spanned(
+                    &field.field,
+                    "`#[conspiracy(shared)]` only applies to leaf fields, sub-configs are already \
+                    behind an `Arc`",
+                ));
+            }
+
+            let ty = &field.field.ty;
+            field.field.ty = parse_quote! { ::std::sync::Arc<#ty> };
+        }
+
+        match &mut field.kind {
+            NestableFieldKind::NestedStruct(nested_struct) => {
+                apply_attributes(nested_struct, inherited, false)?;
+            }
+            NestableFieldKind::NestedEnum(nested_enum) => {
+                apply_enum_attributes(nested_enum, inherited)?;
+            }
+            NestableFieldKind::External(_)
+            | NestableFieldKind::Derived(_)
+            | NestableFieldKind::OptionalExternal(_)
+            | NestableFieldKind::Leaf => {}
+        }
+
+        if field.has_attr(ConspiracyAttribute::Restart) && item.no_restart {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(restart)]` can't be used within a `#[conspiracy(no_restart)]` \
+                config, which doesn't generate `RestartRequired`, so the field would never signal \
+                a restart",
+            ));
+        }
+
+        if field.has_attr(ConspiracyAttribute::Reload) && item.no_restart {
+            return Err(syn::Error::new_spanned(
+                &field.field,
+                "`#[conspiracy(change = \"reload\")]` can't be used within a \
+                `#[conspiracy(no_restart)]` config",
+            ));
+        }
+    }
+
+    if derives_trait(&item.attrs, "Eq") {
+        check_eq_leaves(item)?;
+    }
+
+    Ok(())
+}
+
+/// Names a leaf field that can't implement `Eq` when the struct derives it, rather than leaving the
+/// user with an unsatisfied bound in the generated code. Only the float primitives are detected,
+/// other types are left to the compiler.
+fn check_eq_leaves(item: &NestableStruct) -> syn::Result<()> {
+    fn float_in(tokens: TokenStream) -> Option<Ident> {
+        tokens.into_iter().find_map(|token| match token {
+            TokenTree::Ident(ident) if ident == "f32" || ident == "f64" => Some(ident),
+            TokenTree::Group(group) => float_in(group.stream()),
+            _ => None,
+        })
+    }
+
+    for field in item.fields.iter() {
+        if !matches!(field.kind, NestableFieldKind::Leaf) {
+            continue;
+        }
+
+        let ty = &field.field.ty;
+        if let Some(float) = float_in(quote! { #ty }) {
+            let ident = field
+                .field
+                .ident
+                .as_ref()
+                .expect("Unnamed fields are rejected when parsing");
+            let struct_ty = &item.ty;
+            return Err(syn::Error::new_spanned(
+                ty,
+                format!(
+                    "`{}` derives `Eq` (e.g. via `#[conspiracy(hashable)]`), but its field `{}` \
+                    contains `{float}`, which doesn't implement `Eq` or `Hash`. Store it as a type \
+                    that does, e.g. an integer in a smaller unit",
+                    quote! { #struct_ty },
+                    ident.unraw(),
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_enum_attributes(
+    item: &mut NestableEnum,
+    inherited: InheritedAttributes,
+) -> syn::Result<()> {
+    if let Some(attr) = extract_conspiracy_struct_attributes(&mut item.attrs)?.first() {
+        let name = match attr {
+            ConspiracyStructAttribute::Strict => "strict",
+            ConspiracyStructAttribute::Schema => "schema",
+            ConspiracyStructAttribute::Arbitrary => "arbitrary",
+            ConspiracyStructAttribute::Hashable => "hashable",
+            ConspiracyStructAttribute::Builder => "builder",
+            ConspiracyStructAttribute::NoRestart => "no_restart",
+            ConspiracyStructAttribute::Transparent => "transparent",
+            ConspiracyStructAttribute::RestartGroup(_) => "restart_group",
+        };
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            format!(
+                "`#[conspiracy({name})]` can't be applied to an enum, apply it to an enclosing \
+                struct instead"
+            ),
+        ));
+    }
+
+    item.no_restart = inherited.no_restart;
+
+    if inherited.schema {
+        item.attrs
+            .push(parse_quote! { #[derive(::schemars::JsonSchema)] });
+    }
+
+    if inherited.arbitrary {
+        item.attrs
+            .push(parse_quote! { #[derive(::arbitrary::Arbitrary)] });
+    }
+
+    if inherited.hashable {
+        item.attrs.push(parse_quote! { #[derive(Eq, Hash)] });
+    }
+
+    for variant in item.variants.iter_mut() {
+        apply_attributes(&mut variant.config, inherited, false)?;
+    }
+
+    Ok(())
+}
+
+/// Checks if the struct will implement `Deserialize`, either by deriving it directly or via
+/// [`full_serde`] / [`full_serde_as`].
+fn derives_deserialize(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let path = attr.path();
+        if path.is_ident("derive") {
+            attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .is_ok_and(|derives| {
+                    derives
+                        .iter()
+                        .any(|derive| last_segment_is(derive, "Deserialize"))
+                })
+        } else {
+            last_segment_is(path, "full_serde") || last_segment_is(path, "full_serde_as")
+        }
+    })
+}
+
+/// Checks if a `#[derive(...)]` attribute includes `trait_name`, e.g. `Default`.
+fn derives_trait(attrs: &[Attribute], trait_name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("derive")
+            && attr
+                .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .is_ok_and(|derives| {
+                    derives
+                        .iter()
+                        .any(|derive| last_segment_is(derive, trait_name))
+                })
+    })
+}
+
+/// The subset of `#[serde(...)]` attributes that determine which keys a field deserializes from.
+#[derive(Default)]
+struct SerdeNaming {
+    rename: Option<String>,
+    rename_all: Option<(String, Span)>,
+    /// Additional keys accepted with `alias = "..."`.
+    aliases: Vec<String>,
+    /// Fields that are skipped or flattened don't have a key of their own.
+    no_key: bool,
+    /// Flattened fields are read from the keys of their parent.
+    flatten: bool,
+}
+
+fn parse_serde_naming(attrs: &[Attribute]) -> syn::Result<SerdeNaming> {
+    let mut naming = SerdeNaming::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            let is_rename = meta.path.is_ident("rename");
+            if is_rename || meta.path.is_ident("rename_all") {
+                // Either `rename = "..."` or `rename(serialize = "...", deserialize = "...")`
+                let mut name = None;
+                if meta.input.peek(Token![=]) {
+                    name = Some(meta.value()?.parse::<LitStr>()?);
+                } else {
+                    meta.parse_nested_meta(|inner| {
+                        let value = inner.value()?.parse::<LitStr>()?;
+                        if inner.path.is_ident("deserialize") {
+                            name = Some(value);
+                        }
+                        Ok(())
+                    })?;
+                }
+
+                if let Some(name) = name {
+                    if is_rename {
+                        naming.rename = Some(name.value());
+                    } else {
+                        naming.rename_all = Some((name.value(), name.span()));
+                    }
+                }
+            } else if meta.path.is_ident("alias") {
+                naming
+                    .aliases
+                    .push(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                if ["skip", "skip_deserializing", "flatten"]
+                    .iter()
+                    .any(|flag| meta.path.is_ident(flag))
+                {
+                    naming.no_key = true;
+                }
+                naming.flatten |= meta.path.is_ident("flatten");
+
+                // Consume the value of any other attribute, e.g. `default = "..."` or `bound(...)`
+                if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                } else if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                    meta.input.parse::<TokenTree>()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(naming)
+}
+
+/// Applies a serde `rename_all` rule to a (snake_case) field name, mirroring serde's own rules.
+fn apply_rename_all(rule: &str, field: &str, span: Span) -> syn::Result<String> {
+    // Only split on underscores like serde does, e.g. `ipv4_addr` is two words, not three
+    let convert = |case| field.from_case(Case::Snake).to_case(case);
+
+    Ok(match rule {
+        "lowercase" | "snake_case" => field.to_string(),
+        "UPPERCASE" => field.to_ascii_uppercase(),
+        "SCREAMING_SNAKE_CASE" => convert(Case::Constant),
+        "PascalCase" => convert(Case::Pascal),
+        "camelCase" => convert(Case::Camel),
+        "kebab-case" => convert(Case::Kebab),
+        "SCREAMING-KEBAB-CASE" => convert(Case::Cobol),
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                format!("unknown serde `rename_all` rule `{rule}`"),
+            ))
+        }
+    })
+}
+
+/// Rejects fields of a struct that deserialize from the same key, since serde would silently only
+/// ever populate one of them.
+fn check_wire_name_collisions(item: &NestableStruct) -> syn::Result<()> {
+    let rename_all = parse_serde_naming(&item.attrs)?.rename_all;
+    let mut seen: Vec<(String, &Ident)> = Vec::new();
+
+    for NestableField { field, .. } in item.fields.iter() {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("Unnamed fields are rejected when parsing");
+
+        for key in wire_keys(field, &rename_all)? {
+            if let Some((_, other)) = seen.iter().find(|(name, _)| *name == key) {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    format!("fields `{other}` and `{ident}` both deserialize from the key `{key}`"),
+                ));
+            }
+            seen.push((key, ident));
+        }
+    }
+
+    Ok(())
+}
+
+/// The keys a field deserializes from, its wire name followed by any aliases. Empty for fields that
+/// don't have a key of their own, i.e. skipped or flattened fields.
+fn wire_keys(field: &Field, rename_all: &Option<(String, Span)>) -> syn::Result<Vec<String>> {
+    let naming = parse_serde_naming(&field.attrs)?;
+    if naming.no_key {
+        return Ok(Vec::new());
+    }
+
+    let ident = field
+        .ident
+        .as_ref()
+        .expect("Unnamed fields are rejected when parsing");
+    let wire_name = match (naming.rename, rename_all) {
+        (Some(rename), _) => rename,
+        (None, Some((rule, span))) => apply_rename_all(rule, &ident.unraw().to_string(), *span)?,
+        (None, None) => ident.unraw().to_string(),
+    };
+
+    // Aliases are keys just the same, e.g. the old name of a renamed field
+    let mut keys = vec![wire_name];
+    for alias in naming.aliases {
+        if !keys.contains(&alias) {
+            keys.push(alias);
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Rejects a hierarchy where the same config type is reachable by two paths, which would make
+/// projecting to it ambiguous. The conflicting `AsField` impls would be rejected by the compiler
+/// anyway, but this names the paths involved. Only the types declared in this invocation are seen.
+fn check_unique_types(root: &NestableStruct) -> syn::Result<()> {
+    fn visit(
+        item: &NestableStruct,
+        path: &str,
+        seen: &mut Vec<(String, String)>,
+    ) -> syn::Result<()> {
+        for field in item.fields.iter() {
+            let ident = field
+                .field
+                .ident
+                .as_ref()
+                .expect("Unnamed fields are rejected when parsing");
+            let field_path = match path {
+                "" => ident.to_string(),
+                _ => format!("{path}.{ident}"),
+            };
+
+            match &field.kind {
+                NestableFieldKind::NestedStruct(nested) => {
+                    check(&nested.ty, &field_path, seen)?;
+                    visit(nested, &field_path, seen)?;
+                }
+                NestableFieldKind::NestedEnum(nested) => {
+                    check(&nested.ty, &field_path, seen)?;
+                    for variant in nested.variants.iter() {
+                        let variant_path = format!("{field_path}({})", variant.ident);
+                        check(&variant.config.ty, &variant_path, seen)?;
+                        visit(&variant.config, &variant_path, seen)?;
+                    }
+                }
+                NestableFieldKind::External(ty)
+                | NestableFieldKind::Derived(ty)
+                | NestableFieldKind::OptionalExternal(ty) => check(ty, &field_path, seen)?,
+                NestableFieldKind::Leaf => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check(ty: &Type, path: &str, seen: &mut Vec<(String, String)>) -> syn::Result<()> {
+        let name = quote! { #ty }.to_string();
+        if let Some((_, other)) = seen.iter().find(|(seen_name, _)| *seen_name == name) {
+            return Err(syn::Error::new_spanned(
+                ty,
+                format!(
+                    "the config type `{name}` is used by both `{other}` and `{path}`, every type in \
+                    a config hierarchy must be unique so that projecting to it isn't ambiguous"
+                ),
+            ));
+        }
+        seen.push((name, path.to_string()));
+        Ok(())
+    }
+
+    let root_ty = &root.ty;
+    let mut seen = vec![(quote! { #root_ty }.to_string(), "the root".to_string())];
+    visit(root, "", &mut seen)
+}
+
+/// Absent `Option` leaves deserialize to `None`, unless the field already specifies its own default.
+fn apply_option_default(field: &mut Field) {
+    let is_option = matches!(&field.ty, Type::Path(ty) if ty.qself.is_none() && last_segment_is(&ty.path, "Option"));
+
+    if is_option && !has_serde_flag(&field.attrs, "default") {
+        field.attrs.push(parse_quote! { #[serde(default)] });
+    }
+}
+
+/// `#[serde_as(...)]` field attributes only take effect beneath the `#[serde_as]` struct attribute,
+/// which is added in front of the derives when the struct doesn't already have it.
+fn apply_serde_as(item: &mut NestableStruct) {
+    let fields_use_serde_as = item.fields.iter().any(|field| {
+        field
+            .field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("serde_as"))
+    });
+    let struct_has_serde_as = item.attrs.iter().any(|attr| {
+        last_segment_is(attr.path(), "serde_as") || last_segment_is(attr.path(), "full_serde_as")
+    });
+
+    if fields_use_serde_as && !struct_has_serde_as {
+        item.attrs
+            .insert(0, parse_quote! { #[::serde_with::serde_as] });
+    }
+}
+
+fn last_segment_is(path: &Path, ident: &str) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == ident)
+}
+
+fn apply_strict(item: &mut NestableStruct) -> syn::Result<()> {
+    for NestableField { field, .. } in item.fields.iter() {
+        if has_serde_flag(&field.attrs, "flatten") {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`#[serde(flatten)]` can't be used with `#[conspiracy(strict)]`, serde doesn't \
+                support `deny_unknown_fields` in combination with `flatten`",
+            ));
+        }
+    }
+
+    // Without a `Deserialize` derive there's nothing to deny, and no derive to accept the attribute
+    if derives_deserialize(&item.attrs) && !has_serde_flag(&item.attrs, "deny_unknown_fields") {
+        item.attrs
+            .push(parse_quote! { #[serde(deny_unknown_fields)] });
+    }
+
+    Ok(())
+}
+
+fn compact_ty_name(ty: &Type) -> Ident {
+    format_ident!(
+        "Compact{}",
+        Ident::new(&quote! { #ty }.to_string(), Span::call_site())
+    )
+}
+
+/// The attributes that determine how a struct or field (de)serializes, i.e. serde derives and
+/// helper attributes, [`full_serde`] / [`full_serde_as`], and `serde_as`.
+fn serde_attributes(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter_map(|attr| {
+            let path = attr.path();
+            if path.is_ident("derive") {
+                let derives = attr
+                    .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                    .ok()?
+                    .into_iter()
+                    .filter(|derive| {
+                        last_segment_is(derive, "Serialize")
+                            || last_segment_is(derive, "Deserialize")
+                    })
+                    .collect::<Vec<_>>();
+                (!derives.is_empty()).then(|| parse_quote! { #[derive(#(#derives),*)] })
+            } else {
+                (path.is_ident("serde")
+                    || last_segment_is(path, "serde_as")
+                    || last_segment_is(path, "full_serde")
+                    || last_segment_is(path, "full_serde_as"))
+                .then(|| attr.clone())
+            }
+        })
+        .collect()
+}
+
+/// Drops the container conversions (`from`, `try_from` and `into`) from a `#[serde(...)]`
+/// attribute. They convert to and from the real struct, the compact form has no such impls.
+fn without_conversions(attr: Attribute) -> Option<Attribute> {
+    if !attr.path().is_ident("serde") {
+        return Some(attr);
+    }
+
+    let Ok(items) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+        return Some(attr);
+    };
+    let items = items
+        .into_iter()
+        .filter(|item| {
+            !(item.path().is_ident("from")
+                || item.path().is_ident("try_from")
+                || item.path().is_ident("into"))
+        })
+        .collect::<Vec<_>>();
+
+    (!items.is_empty()).then(|| parse_quote! { #[serde(#(#items),*)] })
+}
+
+fn generate_compact_struct(input: &NestableStruct) -> TokenStream {
+    let mut output = TokenStream::new();
+    let ty = &input.ty;
+    let compact_ty = compact_ty_name(ty);
+
+    let fields = input
+        .fields
+        .iter()
+        .map(|config_field| {
+            let mut field = config_field.field.clone();
+            match &config_field.kind {
+                NestableFieldKind::NestedStruct(nested_struct) => {
+                    output.extend(generate_compact_struct(nested_struct));
+                    field.ty = ident_to_type(compact_ty_name(&nested_struct.ty));
+                }
+                // The enum itself stays behind its `Arc`, but each variant has a compact form
+                NestableFieldKind::NestedEnum(nested_enum) => {
+                    for variant in &nested_enum.variants {
+                        output.extend(generate_compact_struct(&variant.config));
+                    }
+                }
+                NestableFieldKind::Derived(derived_ty) => {
+                    field.ty = compact_derived_ty(derived_ty);
+                }
+                NestableFieldKind::External(_)
+                | NestableFieldKind::OptionalExternal(_)
+                | NestableFieldKind::Leaf => {}
+            }
+
+            Field {
+                attrs: serde_attributes(&field.attrs),
+                vis: Visibility::Public(Pub::default()),
+                mutability: FieldMutability::None,
+                ident: field.ident.clone(),
+                colon_token: Some(Colon::default()),
+                ty: field.ty,
+            }
+        })
+        .collect::<Vec<Field>>()
+        .into_iter();
+
+    // The compact form is a stand-in for the real struct, so it's exactly as visible and shares its
+    // wire format. Nested configs serialize the same inline as behind an `Arc`.
+    let vis = &input.vis;
+    let mut attrs = serde_attributes(&input.attrs)
+        .into_iter()
+        .filter_map(without_conversions)
+        .collect::<Vec<_>>();
+    // So a fuzzer can build the compact form and `arcify` it
+    if derives_trait(&input.attrs, "Arbitrary") {
+        attrs.push(parse_quote! { #[derive(::arbitrary::Arbitrary)] });
+    }
+    output.extend(quote! {
+        #(#attrs)*
+        #vis struct #compact_ty {
+            #(#fields),*
+        }
+    });
+
+    let arcified_fields = input.fields.iter().map(|field| {
+        let ident = field.field.ident.clone();
+        match field.kind {
+            // External configs stay behind their `Arc`, their compact form (if any) isn't known to us
+            NestableFieldKind::External(_)
+            | NestableFieldKind::NestedEnum(_)
+            | NestableFieldKind::OptionalExternal(_)
+            | NestableFieldKind::Leaf => {
+                quote! { #ident: self.#ident }
+            }
+            NestableFieldKind::NestedStruct(_) | NestableFieldKind::Derived(_) => {
+                quote! { #ident: self.#ident.arcify() }
+            }
+        }
+    });
+
+    output.extend(quote! {
+        impl #compact_ty {
+            // This isn't inlined because it's only intended to be used under test
+            pub fn arcify(self) -> ::std::sync::Arc<#ty> {
+                ::std::sync::Arc::new(#ty {
+                    #(#arcified_fields),*
+                })
+            }
+        }
+    });
+
+    // Defer to the real struct, so the defaults are the same regardless of which form is built
+    if derives_trait(&input.attrs, "Default") {
+        output.extend(quote! {
+            impl Default for #compact_ty {
+                fn default() -> Self {
+                    #ty::default().compact()
+                }
+            }
+        });
+    }
+
+    output
+}
+
+fn generate_config_structs(input: NestableStruct, lineage: &mut Vec<(Ident, Type)>) -> TokenStream {
+    let mut output = TokenStream::new();
+    let fields = input
+        .fields
+        .iter()
+        .map(|config_field| {
+            let field = &config_field.field;
+            let child_ty = match &config_field.kind {
+                NestableFieldKind::NestedStruct(nested) => Some(&nested.ty),
+                NestableFieldKind::NestedEnum(nested) => Some(&nested.ty),
+                NestableFieldKind::External(external_ty)
+                | NestableFieldKind::Derived(external_ty) => Some(external_ty),
+                NestableFieldKind::OptionalExternal(optional_ty) => {
+                    lineage.push((
+                        field
+                            .ident
+                            .clone()
+                            .expect("Unnamed fields are rejected when parsing"),
+                        input.ty.clone(),
+                    ));
+                    output.extend(impl_as_variant_for_lineage(lineage, optional_ty));
+                    lineage.pop();
+                    None
+                }
+                NestableFieldKind::Leaf => None,
+            };
+
+            if let Some(child_ty) = child_ty {
+                lineage.push((
+                    field
+                        .ident
+                        .clone()
+                        .expect("Unnamed fields are rejected when parsing"),
+                    input.ty.clone(),
+                ));
+                output.extend(impl_as_field_for_lineage(lineage, child_ty));
+                match &config_field.kind {
+                    NestableFieldKind::NestedStruct(nested) => {
+                        output.extend(generate_config_structs(nested.clone(), lineage));
+                    }
+                    NestableFieldKind::NestedEnum(nested) => {
+                        output.extend(generate_config_enum(nested.clone(), lineage));
+                    }
+                    NestableFieldKind::External(_)
+                    | NestableFieldKind::Derived(_)
+                    | NestableFieldKind::OptionalExternal(_)
+                    | NestableFieldKind::Leaf => {}
+                }
+                lineage.pop();
+            }
+
+            field.clone()
+        })
+        .collect::<Vec<Field>>()
+        .into_iter();
+
+    let rename_all = parse_serde_naming(&input.attrs).map(|naming| naming.rename_all);
+    let attrs = input.attrs;
+    let vis = input.vis;
+    let struct_token = input.struct_token;
+    let ty = input.ty;
+
+    let shared_fields = input
+        .fields
+        .iter()
+        .any(|field| field.has_attr(ConspiracyAttribute::Shared));
+
+    // A derived struct is already defined, along with whichever of these traits its author chose
+    if !input.derived {
+        // PartialEq is only needed for restart comparisons
+        let derives = if input.no_restart {
+            quote! { #[derive(Clone)] }
+        } else if shared_fields {
+            output.extend(impl_partial_eq_with_shared_fields(&ty, &input.fields));
+            quote! { #[derive(Clone)] }
+        } else {
+            quote! { #[derive(Clone, PartialEq)] }
+        };
+
+        output.extend(quote! {
+            #derives
+            #(#attrs)*
+            #vis #struct_token #ty {
+                #(#fields),*
+            }
+        });
+    }
+
+    // Identity projection, so generic code over `AsField` doesn't need to special case the config
+    // itself. Nested configs are behind `Arc`, so the clone is only as deep as the leaf fields.
+    output.extend(quote! {
+        impl ::conspiracy::config::AsField<#ty> for #ty {
+            #[inline]
+            fn share(&self) -> ::std::sync::Arc<#ty> {
+                ::std::sync::Arc::new(self.clone())
+            }
+        }
+    });
+
+    let compact_ty = compact_ty_name(&ty);
+    let compacted_fields = input.fields.iter().map(|field| {
+        let ident = field.field.ident.clone();
+        match field.kind {
+            NestableFieldKind::NestedStruct(_) | NestableFieldKind::Derived(_) => {
+                quote! { #ident: (*self.#ident).clone().compact() }
+            }
+            NestableFieldKind::External(_)
+            | NestableFieldKind::NestedEnum(_)
+            | NestableFieldKind::OptionalExternal(_)
+            | NestableFieldKind::Leaf => {
+                quote! { #ident: self.#ident.clone() }
+            }
+        }
+    });
+
+    output.extend(quote! {
+        impl #ty {
+            // This isn't inlined because it's only intended to be used under test
+            pub fn compact(&self) -> #compact_ty {
+                #compact_ty {
+                    #(#compacted_fields),*
+                }
+            }
+        }
+    });
+
+    // Named accessors and copy-on-write replacements for each sub-config
+    let replacements = input.fields.iter().filter_map(|field| {
+        let child_ty = match &field.kind {
+            NestableFieldKind::NestedStruct(nested) => &nested.ty,
+            NestableFieldKind::NestedEnum(nested) => &nested.ty,
+            NestableFieldKind::External(external_ty) | NestableFieldKind::Derived(external_ty) => {
+                external_ty
+            }
+            NestableFieldKind::OptionalExternal(_) | NestableFieldKind::Leaf => return None,
+        };
+        let ident = field.field.ident.as_ref()?;
+        let vis = &field.field.vis;
+        let method = format_ident!("replace_{}", ident.unraw());
+        let doc = format!(
+            "Copy-on-write update of `{}`. The returned config shares every other branch with `self`.",
+            ident.unraw()
+        );
+        let accessor_doc = format!(
+            "The `{}` sub-config, shared rather than cloned.",
+            ident.unraw()
+        );
+        Some(quote! {
+            #[doc = #accessor_doc]
+            #[inline]
+            #vis fn #ident(&self) -> ::std::sync::Arc<#child_ty> {
+                self.#ident.clone()
+            }
+
+            #[doc = #doc]
+            #vis fn #method(&self, #ident: ::std::sync::Arc<#child_ty>) -> ::std::sync::Arc<Self> {
+                let mut replaced = self.clone();
+                replaced.#ident = #ident;
+                ::std::sync::Arc::new(replaced)
+            }
+        })
+    });
+
+    output.extend(quote! {
+        impl #ty {
+            #(#replacements)*
+        }
+    });
+
+    let env_overrides = input.fields.iter().filter_map(|field| {
+        let ident = &field.field.ident;
+        match &field.kind {
+            NestableFieldKind::Leaf => field.env_var().map(|var| {
+                let value = if field.has_attr(ConspiracyAttribute::Shared) {
+                    quote! { ::std::sync::Arc::new(value) }
+                } else {
+                    quote! { value }
+                };
+                quote! {
+                    if let Some(value) = ::conspiracy::config::macro_targets::env_override(#var)? {
+                        self.#ident = #value;
+                    }
+                }
+            }),
+            NestableFieldKind::NestedStruct(_)
+            | NestableFieldKind::NestedEnum(_)
+            | NestableFieldKind::Derived(_) => Some(quote! {
+                ::std::sync::Arc::make_mut(&mut self.#ident).apply_env_overrides()?;
+            }),
+            // The external config may not have been generated by the macro
+            NestableFieldKind::External(_) | NestableFieldKind::OptionalExternal(_) => None,
+        }
+    });
+
+    output.extend(quote! {
+        impl #ty {
+            /// Override the fields marked `#[conspiracy(env = "...")]` (including those of nested
+            /// sub-configs) with the values of their environment variables, if set.
+            pub fn apply_env_overrides(
+                &mut self,
+            ) -> Result<(), ::conspiracy::config::EnvOverrideError> {
+                #(#env_overrides)*
+                Ok(())
+            }
+        }
+    });
+
+    let dotted_fields = input.fields.iter().map(|field| {
+        let ident = &field.field.ident;
+        let name = ident
+            .as_ref()
+            .expect("Unnamed fields are rejected when parsing")
+            .unraw()
+            .to_string();
+        let write = if field.has_attr(ConspiracyAttribute::Secret) {
+            quote! { lines.push(format!("{path} = <redacted>")); }
+        } else {
+            match field.kind {
+                NestableFieldKind::NestedStruct(_)
+                | NestableFieldKind::NestedEnum(_)
+                | NestableFieldKind::Derived(_) => quote! {
+                    ::conspiracy::config::macro_targets::WriteDotted::write_dotted(
+                        &*self.#ident,
+                        &path,
+                        lines,
+                    );
+                },
+                // The external config may not have been generated by the macro, in which case it's
+                // written like a leaf
+                NestableFieldKind::External(_) => quote! {
+                    (&&&&::conspiracy::config::macro_targets::Dotted(&*self.#ident))
+                        .write_dotted(&path, lines);
+                },
+                // An absent sub-config has no fields to write
+                NestableFieldKind::OptionalExternal(_) => quote! {
+                    if let Some(config) = &self.#ident {
+                        (&&&&::conspiracy::config::macro_targets::Dotted(&**config))
+                            .write_dotted(&path, lines);
+                    }
+                },
+                NestableFieldKind::Leaf => quote! {
+                    (&&&&::conspiracy::config::macro_targets::Dotted(&self.#ident))
+                        .write_dotted(&path, lines);
+                },
+            }
+        };
+
+        quote! {
+            {
+                let path = ::conspiracy::config::macro_targets::dotted_path(path, #name);
+                #write
+            }
+        }
+    });
+
+    output.extend(quote! {
+        impl ::conspiracy::config::macro_targets::WriteDotted for #ty {
+            fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+                #[allow(unused_imports)]
+                use ::conspiracy::config::macro_targets::{
+                    DottedConfig as _, DottedDebug as _, DottedDisplay as _,
+                    DottedUnprintable as _,
+                };
+                #(#dotted_fields)*
+            }
+        }
+
+        impl #ty {
+            /// Renders every field as a `path = value` line, e.g. `database.name = prod`, for
+            /// logging the effective config. Fields marked `#[conspiracy(secret)]` are redacted.
+            pub fn to_dotted_string(&self) -> String {
+                let mut lines = Vec::new();
+                ::conspiracy::config::macro_targets::WriteDotted::write_dotted(self, "", &mut lines);
+                lines.join("\n")
+            }
+        }
+    });
+
+    output.extend(
+        impl_deprecated_fields(&ty, &rename_all, &input.fields)
+            .unwrap_or_else(|error| error.to_compile_error()),
+    );
+
+    output.extend(quote! {
+        impl ::conspiracy::config::macro_targets::GeneratedConfig for #ty {}
+    });
+
+    // Sharing unchanged branches relies on the `PartialEq` derive, which a derived struct may not have
+    if !input.no_restart && !input.derived {
+        output.extend(impl_reconcile(&ty, &input.fields));
+    }
+
+    if input.builder {
+        output.extend(impl_builder(&vis, &ty, &input.fields));
+    }
+
+    if input.transparent {
+        let field = &input.fields[0].field;
+        let ident = &field.ident;
+        let field_ty = &field.ty;
+        output.extend(quote! {
+            impl std::ops::Deref for #ty {
+                type Target = #field_ty;
+
+                #[inline]
+                fn deref(&self) -> &#field_ty {
+                    &self.#ident
+                }
+            }
+
+            impl AsRef<#field_ty> for #ty {
+                #[inline]
+                fn as_ref(&self) -> &#field_ty {
+                    &self.#ident
+                }
+            }
+        });
+    }
+
+    if input.schema {
+        output.extend(quote! {
+            impl #ty {
+                /// The JSON Schema describing the serialized form of this config.
+                pub fn schema() -> ::schemars::Schema {
+                    ::schemars::schema_for!(#ty)
+                }
+            }
+        });
+    }
+
+    output
+}
+
+/// An enum sub-config, where each variant wraps its own config struct. `lineage` leads to the enum,
+/// the variants start their own lineage since they can't be reached infallibly from above.
+fn generate_config_enum(input: NestableEnum, lineage: &[(Ident, Type)]) -> TokenStream {
+    let mut output = TokenStream::new();
+    let ty = &input.ty;
+    let attrs = &input.attrs;
+    let vis = &input.vis;
+    let enum_token = &input.enum_token;
+    let variant_idents = input
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let variant_tys = input
+        .variants
+        .iter()
+        .map(|variant| &variant.config.ty)
+        .collect::<Vec<_>>();
+    let variant_attrs = input.variants.iter().map(|variant| &variant.attrs);
+
+    output.extend(quote! {
+        impl ::conspiracy::config::macro_targets::GeneratedConfig for #ty {}
+    });
 
-fn restart_required(input: &mut NestableStruct) -> TokenStream {
-    let comparison = build_restart_comparison(input);
-    let ty = &input.ty;
+    let derives = if input.no_restart {
+        quote! { #[derive(Clone)] }
+    } else {
+        // The active variant's config is reconciled when the variant is unchanged
+        output.extend(quote! {
+            impl ::conspiracy::config::Reconcile for #ty {
+                fn reconcile(
+                    previous: &::std::sync::Arc<Self>,
+                    next: ::std::sync::Arc<Self>,
+                ) -> ::std::sync::Arc<Self> {
+                    if ::std::sync::Arc::ptr_eq(previous, &next) {
+                        return next;
+                    }
 
-    quote! {
-        impl ::conspiracy::config::RestartRequired for #ty {
-            // This is effectively a specialization of PartialEq, which is inlined in derive
-            // generated impls so we do the same here.
+                    let next = match (&**previous, ::std::sync::Arc::unwrap_or_clone(next)) {
+                        #(
+                            (#ty::#variant_idents(previous), #ty::#variant_idents(next)) => {
+                                #ty::#variant_idents(
+                                    ::conspiracy::config::Reconcile::reconcile(previous, next),
+                                )
+                            }
+                        )*
+                        #[allow(unreachable_patterns)]
+                        (_, next) => next,
+                    };
+                    if **previous == next {
+                        previous.clone()
+                    } else {
+                        ::std::sync::Arc::new(next)
+                    }
+                }
+            }
+        });
+        quote! { #[derive(Clone, PartialEq)] }
+    };
+
+    output.extend(quote! {
+        #derives
+        #(#attrs)*
+        #vis #enum_token #ty {
+            #(
+                #(#variant_attrs)*
+                #variant_idents(::std::sync::Arc<#variant_tys>)
+            ),*
+        }
+
+        impl ::conspiracy::config::AsField<#ty> for #ty {
             #[inline]
-            fn restart_required(&self, other: &Self) -> bool {
-                #comparison
+            fn share(&self) -> ::std::sync::Arc<#ty> {
+                ::std::sync::Arc::new(self.clone())
             }
         }
-    }
-}
 
-fn build_restart_comparison(input: &mut NestableStruct) -> TokenStream {
-    let mut lineage = Vec::new();
-    let mut comparisons = Vec::new();
-    build_restart_comparison_for_struct(&mut lineage, &mut comparisons, input);
+        impl #ty {
+            /// Override the fields marked `#[conspiracy(env = "...")]` of the active variant with
+            /// the values of their environment variables, if set.
+            pub fn apply_env_overrides(
+                &mut self,
+            ) -> Result<(), ::conspiracy::config::EnvOverrideError> {
+                match self {
+                    #(
+                        #ty::#variant_idents(variant) => {
+                            ::std::sync::Arc::make_mut(variant).apply_env_overrides()
+                        }
+                    )*
+                }
+            }
+        }
+    });
+
+    // The active variant is written as a segment of the path, e.g. `backend.Postgres.url`
+    let variant_names = variant_idents.iter().map(|ident| ident.unraw().to_string());
+    output.extend(quote! {
+        impl ::conspiracy::config::macro_targets::WriteDotted for #ty {
+            fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+                match self {
+                    #(
+                        #ty::#variant_idents(variant) => {
+                            ::conspiracy::config::macro_targets::WriteDotted::write_dotted(
+                                &**variant,
+                                &::conspiracy::config::macro_targets::dotted_path(path, #variant_names),
+                                lines,
+                            )
+                        }
+                    )*
+                }
+            }
+        }
+    });
 
-    if comparisons.is_empty() {
-        // If no fields were marked restart required, then a restart is never required
-        quote! { false }
-    } else {
-        quote! { #(#comparisons)||* }
+    for (variant_ident, variant_ty) in zip(&variant_idents, &variant_tys) {
+        output.extend(quote! {
+            impl ::conspiracy::config::AsVariant<#variant_ty> for #ty {
+                #[inline]
+                fn share_variant(&self) -> Option<::std::sync::Arc<#variant_ty>> {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        #ty::#variant_ident(variant) => Some(variant.clone()),
+                        _ => None,
+                    }
+                }
+            }
+        });
+
+        for i in 0..lineage.len() {
+            let ancestor_ty = &lineage[i].1;
+            let fields = lineage[i..].iter().map(|ancestor| &ancestor.0);
+            output.extend(quote! {
+                impl ::conspiracy::config::AsVariant<#variant_ty> for #ancestor_ty {
+                    #[inline]
+                    fn share_variant(&self) -> Option<::std::sync::Arc<#variant_ty>> {
+                        ::conspiracy::config::AsVariant::<#variant_ty>::share_variant(&*self.#(#fields).*)
+                    }
+                }
+            });
+        }
     }
-}
 
-fn build_restart_comparison_for_struct(
-    lineage: &mut Vec<Ident>,
-    output: &mut Vec<TokenStream>,
-    item: &mut NestableStruct,
-) {
-    for field in item.fields.iter_mut() {
-        match field {
-            NestableField::NestedStruct((field, nested_struct)) => {
-                build_restart_comparison_for_field(lineage, output, field);
+    if !input.no_restart {
+        let methods = [Change::Restart, Change::Reload].map(|change| {
+            // Marked fields can't be compared across variants, so switching variants is treated as
+            // a change to all of them.
+            let mut switch_required = false;
+            for variant in &input.variants {
+                let mut comparisons = Vec::new();
+                build_restart_comparison_for_struct(
+                    &mut vec![],
+                    &mut comparisons,
+                    &variant.config,
+                    change,
+                );
+                switch_required |= !comparisons.is_empty();
+            }
 
-                lineage.push(field.ident.clone().expect("All fields must be named"));
-                build_restart_comparison_for_struct(lineage, output, nested_struct);
-                lineage.pop();
+            let required_fn = change.required_fn();
+            let reasons_fn = change.reasons_fn();
+            let variant_names = variant_idents.iter().map(|ident| ident.to_string());
+            quote! {
+                fn #required_fn(&self, other: &Self) -> bool {
+                    #[allow(unreachable_patterns)]
+                    match (self, other) {
+                        #(
+                            (#ty::#variant_idents(this), #ty::#variant_idents(other)) => {
+                                ::conspiracy::config::RestartRequired::#required_fn(&**this, &**other)
+                            }
+                        )*
+                        _ => #switch_required,
+                    }
+                }
+
+                fn #reasons_fn(&self, other: &Self) -> Vec<&'static str> {
+                    #[allow(unreachable_patterns)]
+                    match (self, other) {
+                        #(
+                            (#ty::#variant_idents(this), #ty::#variant_idents(other)) => {
+                                ::conspiracy::config::RestartRequired::#reasons_fn(&**this, &**other)
+                            }
+                        )*
+                        // Report the switch by the name of the newly active variant
+                        _ if #switch_required => match other {
+                            #(#ty::#variant_idents(_) => vec![#variant_names],)*
+                        },
+                        _ => Vec::new(),
+                    }
+                }
             }
-            NestableField::Field(field) => {
-                build_restart_comparison_for_field(lineage, output, field)
+        });
+
+        output.extend(quote! {
+            impl ::conspiracy::config::RestartRequired for #ty {
+                #(#methods)*
             }
-        }
+        });
+    }
+
+    for variant in input.variants {
+        let mut config = variant.config;
+        output.extend(restart_required(&mut config));
+        output.extend(generate_config_structs(config, &mut vec![]));
     }
+
+    output
 }
 
-fn build_restart_comparison_for_field(
-    lineage: &mut Vec<Ident>,
-    output: &mut Vec<TokenStream>,
-    field: &mut Field,
-) {
-    if let Some(attr) = extract_conspiracy_attributes(&mut field.attrs) {
-        match attr {
-            ConspiracyAttribute::Restart => output.push(comparison_for_field(lineage, field)),
+/// Implements `Reconcile`, keeping the previous `Arc` of every sub-config that didn't change so
+/// unchanged branches stay shared across reloads.
+fn impl_reconcile(ty: &Type, fields: &Punctuated<NestableField, Token![,]>) -> TokenStream {
+    let branches = fields.iter().filter_map(|field| {
+        let ident = &field.field.ident;
+        match field.kind {
+            NestableFieldKind::NestedStruct(_) | NestableFieldKind::NestedEnum(_) => Some(quote! {
+                next.#ident = ::conspiracy::config::Reconcile::reconcile(&previous.#ident, next.#ident);
+            }),
+            NestableFieldKind::External(_) | NestableFieldKind::Derived(_) => Some(quote! {
+                next.#ident = ::conspiracy::config::macro_targets::reconcile_by_eq(
+                    &previous.#ident,
+                    next.#ident,
+                );
+            }),
+            NestableFieldKind::OptionalExternal(_) => Some(quote! {
+                next.#ident = match (&previous.#ident, next.#ident.take()) {
+                    (Some(previous), Some(next)) => Some(
+                        ::conspiracy::config::macro_targets::reconcile_by_eq(previous, next),
+                    ),
+                    (_, next) => next,
+                };
+            }),
+            NestableFieldKind::Leaf => None,
+        }
+    });
+
+    quote! {
+        impl ::conspiracy::config::Reconcile for #ty {
+            fn reconcile(
+                previous: &::std::sync::Arc<Self>,
+                next: ::std::sync::Arc<Self>,
+            ) -> ::std::sync::Arc<Self> {
+                if ::std::sync::Arc::ptr_eq(previous, &next) {
+                    return next;
+                }
+
+                #[allow(unused_mut)]
+                let mut next = ::std::sync::Arc::unwrap_or_clone(next);
+                #(#branches)*
+                if **previous == next {
+                    previous.clone()
+                } else {
+                    ::std::sync::Arc::new(next)
+                }
+            }
         }
     }
 }
 
-fn comparison_for_field(lineage: &mut Vec<Ident>, field: &Field) -> TokenStream {
-    let field_name = field.ident.as_ref().expect("All fields must be named");
-    restart_required_single_field_comparison(if lineage.is_empty() {
-        quote! { #field_name }
-    } else {
-        quote! { #(#lineage).*.#field_name }
-    })
-}
+/// Lists the fields marked `#[conspiracy(deprecated = "...")]` by the keys they're loaded from,
+/// including those of nested sub-configs. Enum and external sub-configs aren't searched, the keys
+/// beneath them depend on how they (de)serialize.
+fn impl_deprecated_fields(
+    ty: &Type,
+    rename_all: &syn::Result<Option<(String, Span)>>,
+    fields: &Punctuated<NestableField, Token![,]>,
+) -> syn::Result<TokenStream> {
+    let rename_all = rename_all.as_ref().map_err(Clone::clone)?;
+    let mut own = Vec::new();
+    let mut nested = Vec::new();
 
-pub(super) fn config_struct(input: LegacyTokenStream) -> LegacyTokenStream {
-    let mut input = parse_macro_input!(input as NestableStruct);
-    let mut output = restart_required(&mut input);
-    output.extend(generate_compact_struct(&input));
-    output.extend(generate_config_structs(input, &mut vec![]));
+    for field in fields {
+        if let Some(message) = field.deprecated() {
+            for key in wire_keys(&field.field, rename_all)? {
+                own.push(quote! {
+                    ::conspiracy::config::DeprecatedField {
+                        path: vec![#key],
+                        message: #message,
+                    }
+                });
+            }
+        }
 
-    LegacyTokenStream::from(output)
-}
+        let child_ty = match &field.kind {
+            NestableFieldKind::NestedStruct(nested) => &nested.ty,
+            NestableFieldKind::Derived(derived) => derived,
+            NestableFieldKind::NestedEnum(_)
+            | NestableFieldKind::External(_)
+            | NestableFieldKind::OptionalExternal(_)
+            | NestableFieldKind::Leaf => continue,
+        };
+        // A flattened sub-config's keys are read from this level, so they aren't prefixed
+        if parse_serde_naming(&field.field.attrs)?.flatten {
+            nested.push(quote! {
+                fields.extend(
+                    <#child_ty as ::conspiracy::config::DeprecatedFields>::deprecated_fields(),
+                );
+            });
+        }
+        for key in wire_keys(&field.field, rename_all)? {
+            nested.push(quote! {
+                fields.extend(
+                    <#child_ty as ::conspiracy::config::DeprecatedFields>::deprecated_fields()
+                        .into_iter()
+                        .map(|field| ::conspiracy::config::DeprecatedField {
+                            path: [vec![#key], field.path].concat(),
+                            ..field
+                        }),
+                );
+            });
+        }
+    }
 
-fn compact_ty_name(ty: &Type) -> Ident {
-    format_ident!(
-        "Compact{}",
-        Ident::new(&quote! { #ty }.to_string(), Span::call_site())
-    )
+    Ok(quote! {
+        impl ::conspiracy::config::DeprecatedFields for #ty {
+            fn deprecated_fields() -> Vec<::conspiracy::config::DeprecatedField> {
+                #[allow(unused_mut)]
+                let mut fields: Vec<::conspiracy::config::DeprecatedField> = vec![#(#own),*];
+                #(#nested)*
+                fields
+            }
+        }
+    })
 }
 
-fn generate_compact_struct(input: &NestableStruct) -> TokenStream {
-    let mut output = TokenStream::new();
-    let ty = &input.ty;
-    let compact_ty = compact_ty_name(ty);
-
-    let fields = input
-        .fields
+/// A builder whose setters accept `impl Into<FieldType>`, e.g. `&str` for a `String` field or a
+/// sub-config for its `Arc`. `Option` fields are set by their inner type and default to `None`, every
+/// other field must be set.
+fn impl_builder(
+    vis: &Visibility,
+    ty: &Type,
+    fields: &Punctuated<NestableField, Token![,]>,
+) -> TokenStream {
+    let builder_name = format_ident!(
+        "{}Builder",
+        Ident::new(&quote! { #ty }.to_string(), Span::call_site())
+    );
+    let idents = fields
         .iter()
         .map(|field| {
-            let field = match field {
-                NestableField::NestedStruct((field, nested_struct)) => {
-                    output.extend(generate_compact_struct(nested_struct));
-                    let mut field = field.clone();
-                    field.ty = ident_to_type(compact_ty_name(&nested_struct.ty));
-                    field
-                }
-                NestableField::Field(field) => field.clone(),
-            };
-
-            Field {
-                attrs: vec![],
-                vis: Visibility::Public(Pub::default()),
-                mutability: FieldMutability::None,
-                ident: field.ident.clone(),
-                colon_token: Some(Colon::default()),
-                ty: field.ty,
-            }
+            field
+                .field
+                .ident
+                .clone()
+                .expect("Unnamed fields are rejected when parsing")
         })
-        .collect::<Vec<Field>>()
-        .into_iter();
+        .collect::<Vec<_>>();
+    // The builder holds an `Option` of each field, which for an `Option` field is the field itself
+    let value_tys = fields
+        .iter()
+        .map(|field| option_inner(&field.field.ty).unwrap_or(&field.field.ty))
+        .collect::<Vec<_>>();
 
-    output.extend(quote! {
-        pub struct #compact_ty {
-            #(#fields),*
+    let setters = zip(&idents, &value_tys).map(|(ident, value_ty)| {
+        let doc = format!("Set `{}`.", ident.unraw());
+        // Literals only infer their type from a concrete parameter, `impl Into<u16>` would see `8080`
+        // as an `i32`
+        let param = if is_primitive(value_ty) {
+            quote! { value: #value_ty }
+        } else {
+            quote! { value: impl Into<#value_ty> }
+        };
+        quote! {
+            #[doc = #doc]
+            pub fn #ident(mut self, #param) -> Self {
+                self.#ident = Some(value.into());
+                self
+            }
         }
     });
 
-    let arcified_fields = input.fields.iter().map(|field| match field {
-        NestableField::Field(field) => {
-            let ident = field.ident.clone();
+    let required = fields
+        .iter()
+        .zip(&idents)
+        .filter(|(field, _)| option_inner(&field.field.ty).is_none())
+        .map(|(_, ident)| ident)
+        .collect::<Vec<_>>();
+    let required_names = required.iter().map(|ident| ident.unraw().to_string());
+    let required_count = required.len();
+    let values = fields.iter().zip(&idents).map(|(field, ident)| {
+        if option_inner(&field.field.ty).is_some() {
             quote! { #ident: self.#ident }
-        }
-        NestableField::NestedStruct((field, _)) => {
-            let ident = field.ident.clone();
-            quote! { #ident: self.#ident.arcify() }
+        } else {
+            quote! { #ident: self.#ident.expect("Missing fields are reported above") }
         }
     });
 
-    output.extend(quote! {
-        impl #compact_ty {
-            // This isn't inlined because it's only intended to be used under test
-            pub fn arcify(self) -> std::sync::Arc<#ty> {
-                std::sync::Arc::new(#ty {
-                    #(#arcified_fields),*
+    quote! {
+        #[derive(Default)]
+        #vis struct #builder_name {
+            #(#idents: Option<#value_tys>),*
+        }
+
+        impl #builder_name {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            #(#setters)*
+
+            /// Fails if any field other than an `Option` wasn't set.
+            pub fn build(self) -> Result<#ty, ::conspiracy::config::MissingConfigFieldsError> {
+                let set: [(&'static str, bool); #required_count] =
+                    [#((#required_names, self.#required.is_some())),*];
+                let missing = set
+                    .into_iter()
+                    .filter(|(_, set)| !set)
+                    .map(|(name, _)| name)
+                    .collect::<Vec<&'static str>>();
+                if !missing.is_empty() {
+                    return Err(::conspiracy::config::MissingConfigFieldsError(missing));
+                }
+
+                Ok(#ty {
+                    #(#values),*
                 })
             }
         }
-    });
 
-    output
+        impl #ty {
+            pub fn builder() -> #builder_name {
+                #builder_name::new()
+            }
+        }
+    }
 }
 
-fn generate_config_structs(input: NestableStruct, lineage: &mut Vec<(Ident, Type)>) -> TokenStream {
-    let mut output = TokenStream::new();
-    let fields = input
-        .fields
-        .iter()
-        .map(|config_field| match config_field {
-            NestableField::NestedStruct((field, nested)) => {
-                lineage.push((
-                    field
-                        .ident
-                        .clone()
-                        .expect("At this stage, only named fields can be present"),
-                    input.ty.clone(),
-                ));
-                output.extend(impl_as_field_for_lineage(lineage, nested));
-                output.extend(generate_config_structs((*nested).clone(), lineage));
-                lineage.pop();
-                field
-            }
-            NestableField::Field(field) => field,
-        })
-        .cloned()
-        .collect::<Vec<Field>>()
-        .into_iter();
+/// The `T` of an `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path
+        .path
+        .segments
+        .last()
+        .filter(|segment| segment.ident == "Option")?;
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
 
-    let attrs = input.attrs;
-    let vis = input.vis;
-    let struct_token = input.struct_token;
-    let ty = input.ty;
+fn is_primitive(ty: &Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+        "u32", "u64", "u128", "usize",
+    ];
 
-    output.extend(quote! {
-        #[derive(Clone, PartialEq)]
-        #(#attrs)*
-        #vis #struct_token #ty {
-            #(#fields),*
-        }
-    });
+    matches!(ty, Type::Path(path) if path.qself.is_none()
+        && PRIMITIVES.iter().any(|primitive| path.path.is_ident(primitive)))
+}
 
-    let compact_ty = compact_ty_name(&ty);
-    let compacted_fields = input.fields.iter().map(|field| match field {
-        NestableField::NestedStruct((field, _)) => {
-            let ident = field.ident.clone();
-            quote! { #ident: (*self.#ident).clone().compact() }
-        }
-        NestableField::Field(field) => {
-            let ident = field.ident.clone();
-            quote! { #ident: self.#ident.clone() }
+/// Same as the derive, except shared leaves are compared by pointer identity since they may not
+/// implement `PartialEq`.
+fn impl_partial_eq_with_shared_fields(
+    ty: &Type,
+    fields: &Punctuated<NestableField, Token![,]>,
+) -> TokenStream {
+    let comparisons = fields.iter().map(|field| {
+        let ident = &field.field.ident;
+        if field.has_attr(ConspiracyAttribute::Shared) {
+            quote! { ::std::sync::Arc::ptr_eq(&self.#ident, &other.#ident) }
+        } else {
+            quote! { self.#ident == other.#ident }
         }
     });
 
-    output.extend(quote! {
-        impl #ty {
-            // This isn't inlined because it's only intended to be used under test
-            pub fn compact(&self) -> #compact_ty {
-                #compact_ty {
-                    #(#compacted_fields),*
-                }
+    quote! {
+        impl PartialEq for #ty {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                true #(&& #comparisons)*
             }
         }
-    });
-
-    output
+    }
 }
 
-fn impl_as_field_for_lineage(lineage: &[(Ident, Type)], nested: &NestableStruct) -> TokenStream {
+fn impl_as_field_for_lineage(lineage: &[(Ident, Type)], child_ty: &Type) -> TokenStream {
     let mut output = TokenStream::new();
 
     for i in (0..lineage.len()).rev() {
-        output.extend(impl_as_field(&lineage[i..], nested.ty.clone()));
+        output.extend(impl_as_field(&lineage[i..], child_ty.clone()));
     }
 
     output
@@ -250,11 +2014,40 @@ fn impl_as_field(lineage: &[(Ident, Type)], child_ty: Type) -> TokenStream {
         impl ::conspiracy::config::AsField<#child_ty> for #root_ty {
             // One-liner, no reason not to inline
             #[inline]
-            fn share(&self) -> std::sync::Arc<#child_ty> {
+            fn share(&self) -> ::std::sync::Arc<#child_ty> {
                 self.#fields.clone()
             }
         }
+
+        // `From<Arc<Root>> for Arc<Child>` would violate the orphan rule, `&Root` is local
+        impl From<&#root_ty> for ::std::sync::Arc<#child_ty> {
+            #[inline]
+            fn from(config: &#root_ty) -> Self {
+                config.#fields.clone()
+            }
+        }
+    }
+}
+
+/// Like [`impl_as_field_for_lineage`], for a sub-config that may be absent. The last entry of the
+/// lineage is the `Option` field itself.
+fn impl_as_variant_for_lineage(lineage: &[(Ident, Type)], child_ty: &Type) -> TokenStream {
+    let mut output = TokenStream::new();
+
+    for i in (0..lineage.len()).rev() {
+        let root_ty = &lineage[i].1;
+        let fields = lineage[i..].iter().map(|ancestor| &ancestor.0);
+        output.extend(quote! {
+            impl ::conspiracy::config::AsVariant<#child_ty> for #root_ty {
+                #[inline]
+                fn share_variant(&self) -> Option<::std::sync::Arc<#child_ty>> {
+                    self.#(#fields).*.clone()
+                }
+            }
+        });
     }
+
+    output
 }
 
 #[derive(Clone)]
@@ -265,24 +2058,161 @@ struct NestableStruct {
     ty: Type,
     _brace_token: token::Brace,
     fields: Punctuated<NestableField, Token![,]>,
+    /// Set by `#[conspiracy(schema)]`, either on this struct or inherited from an ancestor.
+    schema: bool,
+    /// Set by `#[conspiracy(no_restart)]` on the root struct, applies to the whole hierarchy.
+    no_restart: bool,
+    /// Set by `#[conspiracy(transparent)]`, only valid for single field structs.
+    transparent: bool,
+    /// Set by `#[conspiracy(builder)]`, either on this struct or inherited from an ancestor.
+    builder: bool,
+    /// Set for `#[derive(ConfigStruct)]`, where the user already defined the struct itself.
+    derived: bool,
+}
+
+#[derive(Clone)]
+struct NestableField {
+    /// The field as it will be emitted, nested and external configs are already `Arc` wrapped.
+    field: Field,
+    /// Populated by [`apply_attributes`], which removes them from the field's attributes.
+    conspiracy_attrs: Vec<ConspiracyAttribute>,
+    kind: NestableFieldKind,
+}
+
+impl NestableField {
+    fn has_attr(&self, attr: ConspiracyAttribute) -> bool {
+        self.conspiracy_attrs.contains(&attr)
+    }
+
+    fn deprecated(&self) -> Option<&str> {
+        self.conspiracy_attrs.iter().find_map(|attr| match attr {
+            ConspiracyAttribute::Deprecated(message) => Some(message.as_str()),
+            _ => None,
+        })
+    }
+
+    fn env_var(&self) -> Option<&str> {
+        self.conspiracy_attrs.iter().find_map(|attr| match attr {
+            ConspiracyAttribute::Env(var) => Some(var.as_str()),
+            _ => None,
+        })
+    }
+
+    fn restart_key(&self) -> Option<&Ident> {
+        self.conspiracy_attrs.iter().find_map(|attr| match attr {
+            ConspiracyAttribute::Key(key) => Some(key),
+            _ => None,
+        })
+    }
+
+    fn compare_fn(&self) -> Option<&Path> {
+        self.conspiracy_attrs.iter().find_map(|attr| match attr {
+            ConspiracyAttribute::Compare(compare) => Some(&compare.0),
+            _ => None,
+        })
+    }
+}
+
+/// An enum sub-config, declared inline as `field: enum Name { Variant(struct VariantConfig { .. }), .. }`.
+#[derive(Clone)]
+struct NestableEnum {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    enum_token: Token![enum],
+    ty: Type,
+    ident: Ident,
+    variants: Punctuated<NestableVariant, Token![,]>,
+    /// Inherited from the root's `#[conspiracy(no_restart)]`.
+    no_restart: bool,
+}
+
+#[derive(Clone)]
+struct NestableVariant {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    config: NestableStruct,
 }
 
 #[derive(Clone)]
-enum NestableField {
-    NestedStruct((Field, NestableStruct)),
-    Field(Field),
+enum NestableFieldKind {
+    NestedStruct(NestableStruct),
+    NestedEnum(NestableEnum),
+    /// A sub-config defined outside of this macro invocation, declared as `field: @Type`. The
+    /// [`Type`] is the config type itself, the field's type is the `Arc` wrapped form.
+    External(Type),
+    /// A sub-config marked `#[conspiracy(nested)]` within `#[derive(ConfigStruct)]`. Like
+    /// [`NestableFieldKind::External`], but the sub-config also derives `ConfigStruct` so its
+    /// compact form is known.
+    Derived(Type),
+    /// A sub-config that may be absent, declared as `field: Option<@Type>`. Like
+    /// [`NestableFieldKind::External`], the [`Type`] is the config type itself and the field's
+    /// type is `Option<Arc<Type>>`.
+    OptionalExternal(Type),
+    Leaf,
 }
 
 impl Parse for NestableStruct {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let raw_fields;
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let struct_token = input.parse()?;
+        let ident: Ident = input.parse()?;
+        if !input.peek(token::Brace) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "config structs must have named fields, tuple and unit structs aren't supported",
+            ));
+        }
+
         Ok(NestableStruct {
-            attrs: input.call(Attribute::parse_outer)?,
-            vis: input.parse()?,
-            struct_token: input.parse()?,
-            ty: ident_to_type(input.parse()?),
+            attrs,
+            vis,
+            struct_token,
+            ty: ident_to_type(ident),
             _brace_token: braced!(raw_fields in input),
             fields: raw_fields.parse_terminated(NestableField::parse, Token![,])?,
+            schema: false,
+            no_restart: false,
+            transparent: false,
+            builder: false,
+            derived: false,
+        })
+    }
+}
+
+impl Parse for NestableEnum {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let raw_variants;
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let enum_token = input.parse()?;
+        let ident: Ident = input.parse()?;
+        braced!(raw_variants in input);
+
+        Ok(NestableEnum {
+            attrs,
+            vis,
+            enum_token,
+            ty: ident_to_type(ident.clone()),
+            ident,
+            variants: raw_variants.parse_terminated(NestableVariant::parse, Token![,])?,
+            no_restart: false,
+        })
+    }
+}
+
+impl Parse for NestableVariant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let raw_config;
+        let attrs = input.call(Attribute::parse_outer)?;
+        let ident = input.parse()?;
+        parenthesized!(raw_config in input);
+
+        Ok(NestableVariant {
+            attrs,
+            ident,
+            config: raw_config.parse()?,
         })
     }
 }
@@ -292,19 +2222,43 @@ impl Parse for NestableField {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let attrs = input.call(Attribute::parse_outer)?;
         let vis: Visibility = input.parse()?;
+        if !input.peek2(Token![:]) {
+            return Err(input.error("config fields must be named, e.g. `name: Type`"));
+        }
         let ident = input.parse()?;
         let colon_token: Token![:] = input.parse()?;
 
         let ty: Type;
         let mut nested_struct: Option<NestableStruct> = None;
+        let mut nested_enum: Option<NestableEnum> = None;
+        let mut external_ty: Option<Type> = None;
+        let mut optional_ty: Option<Type> = None;
 
-        let fork = input.fork();
-        if let Ok(nested) = fork.parse::<NestableStruct>() {
-            input.advance_to(&fork);
-            ty = wrap_in_arc(nested.ty.clone());
-            nested_struct = Some(nested);
+        if input.parse::<Option<Token![@]>>()?.is_some() {
+            let external = input.parse::<Type>()?;
+            ty = wrap_in_arc(external.clone());
+            external_ty = Some(external);
+        } else if is_optional_external(input) {
+            input.parse::<Ident>()?;
+            input.parse::<Token![<]>()?;
+            input.parse::<Token![@]>()?;
+            let optional = input.parse::<Type>()?;
+            input.parse::<Token![>]>()?;
+            let arc = wrap_in_arc(optional.clone());
+            ty = parse_quote! { Option<#arc> };
+            optional_ty = Some(optional);
         } else {
-            ty = input.parse::<Type>()?;
+            if is_definition(input, |fork| fork.peek(Token![struct])) {
+                let nested = input.parse::<NestableStruct>()?;
+                ty = wrap_in_arc(nested.ty.clone());
+                nested_struct = Some(nested);
+            } else if is_definition(input, |fork| fork.peek(Token![enum])) {
+                let nested = input.parse::<NestableEnum>()?;
+                ty = wrap_in_arc(nested.ty.clone());
+                nested_enum = Some(nested);
+            } else {
+                ty = input.parse::<Type>()?;
+            }
         }
 
         let field = Field {
@@ -316,23 +2270,52 @@ impl Parse for NestableField {
             ty,
         };
 
-        Ok(match nested_struct {
-            None => NestableField::Field(field),
-            Some(nested_struct) => NestableField::NestedStruct((field, nested_struct)),
+        let kind = match (nested_struct, nested_enum, external_ty) {
+            (Some(nested_struct), _, _) => NestableFieldKind::NestedStruct(nested_struct),
+            (None, Some(nested_enum), _) => NestableFieldKind::NestedEnum(nested_enum),
+            (None, None, Some(external_ty)) => NestableFieldKind::External(external_ty),
+            (None, None, None) => match optional_ty {
+                Some(optional_ty) => NestableFieldKind::OptionalExternal(optional_ty),
+                None => NestableFieldKind::Leaf,
+            },
+        };
+
+        Ok(NestableField {
+            field,
+            conspiracy_attrs: Vec::new(),
+            kind,
         })
     }
 }
 
+/// Checks if the input is an inline sub-config definition, i.e. `keyword` follows any attributes
+/// and visibility.
+fn is_definition(input: ParseStream, keyword: impl FnOnce(ParseStream) -> bool) -> bool {
+    let fork = input.fork();
+    fork.call(Attribute::parse_outer).is_ok()
+        && fork.parse::<Visibility>().is_ok()
+        && keyword(&fork)
+}
+
+/// Checks if the input is an optional sub-config, i.e. `Option<@Type>`.
+fn is_optional_external(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Ident>().is_ok_and(|ident| ident == "Option")
+        && fork.parse::<Token![<]>().is_ok()
+        && fork.peek(Token![@])
+}
+
 fn ident_to_type(ident: Ident) -> Type {
     syn::parse_quote! { #ident }
 }
 
 fn wrap_in_arc(ty: Type) -> Type {
     parse_quote! {
-        std::sync::Arc<#ty>
+        ::std::sync::Arc<#ty>
     }
 }
 
+#[cfg(feature = "serde")]
 pub(super) fn full_serde(_attr: LegacyTokenStream, item: LegacyTokenStream) -> LegacyTokenStream {
     let item = TokenStream::from(item);
     LegacyTokenStream::from(quote! {
@@ -341,6 +2324,7 @@ pub(super) fn full_serde(_attr: LegacyTokenStream, item: LegacyTokenStream) -> L
     })
 }
 
+#[cfg(feature = "serde")]
 pub(super) fn full_serde_as(
     _attr: LegacyTokenStream,
     item: LegacyTokenStream,
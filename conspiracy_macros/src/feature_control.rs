@@ -9,17 +9,27 @@ use syn::{
     parse_macro_input,
     punctuated::Punctuated,
     token::Comma,
-    Attribute, Expr, LitBool, Path, PathSegment, Token, Visibility,
+    Attribute, Expr, ExprLit, Lit, LitBool, Meta, MetaNameValue, Path, PathSegment, Token,
+    Visibility,
 };
 
-use crate::common::{extract_conspiracy_attributes, ConspiracyAttribute};
+use crate::common::{
+    extract_conspiracy_attributes, extract_conspiracy_struct_attributes, impl_restart_required,
+    restart_required_single_field_comparison, ConspiracyAttribute, ConspiracyStructAttribute,
+    RestartComparison,
+};
 
 struct Features {
+    /// Passed through to the generated enum.
+    attrs: Vec<Attribute>,
+    /// Groups named by `#[conspiracy(restart_group = "...")]`.
+    restart_groups: Vec<String>,
     visibility: Visibility,
     name: Ident,
     features: Punctuated<Feature, Token![,]>,
     state_name: Ident,
     state_builder_name: Ident,
+    overrides_name: Ident,
 }
 
 impl Features {
@@ -37,7 +47,7 @@ impl Features {
                 format_ident!("default_{}", feature.name.to_string().to_case(Case::Snake));
             let default = feature.default.clone();
             functions.extend(quote::quote! {
-                pub fn #function_name() -> bool {
+                pub const fn #function_name() -> bool {
                     #default
                 }
             })
@@ -46,14 +56,36 @@ impl Features {
         functions
     }
 
+    /// Features marked `#[conspiracy(required)]`, which the builder must be given explicitly.
+    fn required_features(&self) -> impl Iterator<Item = &Feature> {
+        self.features.iter().filter(|feature| {
+            feature
+                .conspiracy_attrs
+                .contains(&ConspiracyAttribute::Required)
+        })
+    }
+
     fn builder_fns(&self) -> TokenStream {
         let mut functions = TokenStream::new();
+        let mut required_index = 0usize;
 
         for feature in &self.features {
             let function_name = format_ident!("{}", feature.name.to_string().to_case(Case::Snake));
+            let mark_set = if feature
+                .conspiracy_attrs
+                .contains(&ConspiracyAttribute::Required)
+            {
+                required_index += 1;
+                let index = required_index - 1;
+                quote! { self.required_set[#index] = true; }
+            } else {
+                TokenStream::new()
+            };
+
             functions.extend(quote::quote! {
                 pub fn #function_name(mut self, value: bool) -> Self {
                     self.state.#function_name = value;
+                    #mark_set
                     self
                 }
             })
@@ -95,6 +127,7 @@ impl Features {
         }
 
         let features_state = format_ident!("{}State", &self.name);
+        let overrides_name = &self.overrides_name;
         quote! {
             impl ::conspiracy::feature_control::AsFeature for #features_state {
                 type Feature = #features_name;
@@ -109,25 +142,62 @@ impl Features {
 
             impl ::conspiracy::feature_control::FeatureSet for #features_name {
                 type State = #features_state;
+                type Overrides = #overrides_name;
+
+                #[inline]
+                fn name(&self) -> &'static str {
+                    #features_name::name(self)
+                }
+
+                #[inline]
+                fn all() -> &'static [Self] {
+                    &Self::ALL
+                }
             }
         }
     }
 }
 
 struct Feature {
-    attrs: Vec<Attribute>,
+    conspiracy_attrs: Vec<ConspiracyAttribute>,
+    /// Doc comments, passed through to the variant and used as its description.
+    docs: Vec<Attribute>,
     name: Ident,
-    default: LitBool,
+    default: Expr,
 }
 
 impl Parse for Feature {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let conspiracy_attrs = extract_conspiracy_attributes(&mut attrs)?;
         let name: Ident = input.parse()?;
+
+        if let Some(attr) = conspiracy_attrs.iter().find(|attr| {
+            !matches!(
+                attr,
+                ConspiracyAttribute::Restart
+                    | ConspiracyAttribute::Owner(_)
+                    | ConspiracyAttribute::Group(_)
+                    | ConspiracyAttribute::Required
+            )
+        }) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!(
+                    "`#[conspiracy({})]` only applies to config fields, not features",
+                    attr.name()
+                ),
+            ));
+        }
+
         input.parse::<Token![=>]>()?;
-        let default: LitBool = input.parse()?;
+        let default: Expr = input.parse()?;
         Ok(Feature {
-            attrs,
+            conspiracy_attrs,
+            docs: attrs
+                .into_iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .collect(),
             name,
             default,
         })
@@ -136,25 +206,85 @@ impl Parse for Feature {
 
 impl Parse for Features {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let conspiracy_attrs = extract_conspiracy_struct_attributes(&mut attrs)?;
         let visibility: Visibility = input.parse()?;
         input.parse::<Token![enum]>()?;
         let name: Ident = input.parse()?;
+
+        let mut restart_groups = Vec::new();
+        for attr in conspiracy_attrs {
+            match attr {
+                ConspiracyStructAttribute::RestartGroup(group) => restart_groups.push(group),
+                _ => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "Only `#[conspiracy(restart_group = \"...\")]` applies to features, the \
+                        others only apply to config structs",
+                    ))
+                }
+            }
+        }
+
         let content;
         syn::braced!(content in input);
         let features = content.parse_terminated(Feature::parse, Comma)?;
+        check_for_name_collisions(&features)?;
         let state_name = format_ident!("{}State", name);
         let state_builder_name = format_ident!("{}Builder", state_name);
+        let overrides_name = format_ident!("{}Overrides", name);
 
         Ok(Features {
+            attrs,
+            restart_groups,
             visibility,
             name,
             features,
             state_name,
             state_builder_name,
+            overrides_name,
         })
     }
 }
 
+/// The generated state struct and builder derive their member names from the variant names, so
+/// distinct variants can still collide once converted (e.g. `FooBar` and `Foo_Bar`). Detect this
+/// here so the user gets a pointed error rather than a duplicate definition in generated code.
+fn check_for_name_collisions(features: &Punctuated<Feature, Token![,]>) -> syn::Result<()> {
+    let mut seen: Vec<(String, String, &Ident)> = Vec::new();
+
+    for feature in features {
+        let name = feature.name.to_string();
+        let pascal = name.to_case(Case::Pascal);
+        let snake = name.to_case(Case::Snake);
+
+        if let Some((other_pascal, _, other)) = seen
+            .iter()
+            .find(|(other_pascal, other_snake, _)| *other_pascal == pascal || *other_snake == snake)
+        {
+            let colliding = if *other_pascal == pascal {
+                &pascal
+            } else {
+                &snake
+            };
+            return Err(syn::Error::new(
+                feature.name.span(),
+                format!(
+                    "Feature `{name}` collides with feature `{other}`, both generate the name \
+                    `{colliding}`. Feature names must be unique after case conversion. If instead \
+                    you are seeing conflicts between separate `define_features!` invocations, wrap \
+                    each in its own module. See https://docs.rs/conspiracy/latest/conspiracy/\
+                    feature_control/macro.define_features.html#generated-names"
+                ),
+            ));
+        }
+
+        seen.push((pascal, snake, &feature.name));
+    }
+
+    Ok(())
+}
+
 pub(super) fn define_features(input: LegacyTokenStream) -> LegacyTokenStream {
     let features = parse_macro_input!(input as Features);
     let mut output = TokenStream::new();
@@ -164,69 +294,177 @@ pub(super) fn define_features(input: LegacyTokenStream) -> LegacyTokenStream {
     output.extend(features.default_impl());
     output.extend(features.as_feature_and_feature_set_impls());
     output.extend(make_builder(&features));
+    output.extend(make_overrides(&features));
 
     LegacyTokenStream::from(output)
 }
 
+/// Features are plain unit variants, so they get the usual value type derives. `PartialOrd` / `Ord`
+/// follow declaration order. Derives the user already wrote are skipped rather than duplicated.
+fn feature_enum_derives(attrs: &[Attribute]) -> TokenStream {
+    let user_derives = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .filter_map(|derive| {
+            derive
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+        })
+        .collect::<Vec<_>>();
+
+    let derives = [
+        "Debug",
+        "Clone",
+        "Copy",
+        "PartialEq",
+        "Eq",
+        "PartialOrd",
+        "Ord",
+        "Hash",
+    ]
+    .into_iter()
+    .filter(|derive| !user_derives.iter().any(|user_derive| user_derive == derive))
+    .map(|derive| format_ident!("{derive}"));
+
+    quote! { #[derive(#(#derives),*)] }
+}
+
 fn make_features_enum(features: &Features) -> TokenStream {
+    let attrs = &features.attrs;
     let vis = &features.visibility;
     let name = &features.name;
-    let variants = features.names(Case::Pascal);
+    let variants = features.names(Case::Pascal).collect::<Vec<_>>();
+    let docs = features.features.iter().map(|feature| &feature.docs);
+    let descriptions = features
+        .features
+        .iter()
+        .map(|feature| doc_text(&feature.docs));
+    let owners = features.features.iter().map(|feature| {
+        match feature.conspiracy_attrs.iter().find_map(|attr| match attr {
+            ConspiracyAttribute::Owner(owner) => Some(owner),
+            _ => None,
+        }) {
+            Some(owner) => quote! { Some(#owner) },
+            None => quote! { None },
+        }
+    });
+    let names = features.names(Case::Snake).map(|name| name.to_string());
+    let count = variants.len();
     let state_name = &features.state_name;
     let state_builder_name = &features.state_builder_name;
+    let derives = feature_enum_derives(attrs);
 
     quote! {
+        #derives
+        #(#attrs)*
         #vis enum #name {
-            #(#variants),*
+            #(
+                #(#docs)*
+                #variants
+            ),*
         }
 
         impl #name {
+            /// Every feature, in declaration order.
+            pub const ALL: [#name; #count] = [#(#name::#variants),*];
+
             pub fn builder() -> #state_builder_name {
                 #state_name::builder()
             }
+
+            /// Iterates over every feature, in declaration order.
+            pub fn iter() -> std::array::IntoIter<#name, #count> {
+                Self::ALL.into_iter()
+            }
+
+            /// The name of the feature, matching its key in the serialized state.
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    #(#name::#variants => #names),*
+                }
+            }
+
+            /// The doc comment of the feature, or an empty string if it isn't documented.
+            pub fn description(&self) -> &'static str {
+                match *self {
+                    #(#name::#variants => #descriptions),*
+                }
+            }
+
+            /// The owner set with `#[conspiracy(owner = "...")]`, if any.
+            pub fn owner(&self) -> Option<&'static str> {
+                match *self {
+                    #(#name::#variants => #owners),*
+                }
+            }
         }
     }
 }
 
+/// Joins the lines of doc comments, dropping the leading space that `///` comments carry.
+fn doc_text(docs: &[Attribute]) -> String {
+    docs.iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(line),
+                        ..
+                    }),
+                ..
+            }) => Some(line.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
 fn make_features_state_struct(features: &Features) -> TokenStream {
     let vis = &features.visibility;
     let state_name = &features.state_name;
     let state_builder_name = &features.state_builder_name;
 
-    let feature_names = features.names(Case::Snake);
+    let features_name = &features.name;
+    let feature_names = features.names(Case::Snake).collect::<Vec<_>>();
+    let variants = features.names(Case::Pascal).collect::<Vec<_>>();
+    let serde_derives = serde_derives();
     let default_fns = features.default_fns();
 
-    let mut restart_required_fields = features
+    let restart_comparisons = features
         .features
         .iter()
-        .map(|feature| {
-            let mut attrs = feature.attrs.clone();
-            (
-                feature.name.clone(),
-                extract_conspiracy_attributes(&mut attrs),
-            )
-        })
-        .filter(|record| {
-            record.1.clone().is_some_and(|attr| match attr {
+        .filter(|feature| {
+            feature.conspiracy_attrs.iter().any(|attr| match attr {
                 ConspiracyAttribute::Restart => true,
+                ConspiracyAttribute::Group(group) => features.restart_groups.contains(group),
+                _ => false,
             })
         })
-        .map(|record| record.0)
-        .peekable();
-
-    let comparison = if restart_required_fields.peek().is_none() {
-        // If no fields were marked restart required, then a restart is never required
-        quote! { false }
-    } else {
-        let comparisons = restart_required_fields.map(|ident| {
-            let ident = format_ident!("{}", ident.to_string().to_case(Case::Snake));
-            quote! { self.#ident != other.#ident }
-        });
-        quote! { #(#comparisons)||* }
-    };
+        .map(|feature| {
+            let ident = format_ident!("{}", feature.name.to_string().to_case(Case::Snake));
+            RestartComparison {
+                path: ident.to_string(),
+                comparison: restart_required_single_field_comparison(quote! { #ident }),
+            }
+        })
+        .collect::<Vec<_>>();
+    let restart_required =
+        impl_restart_required(&quote! { #state_name }, &restart_comparisons, &[]);
+    let (wire_attr, wire_struct) = make_state_wire_struct(features);
 
     quote! {
-        #[derive(::serde::Serialize, ::serde::Deserialize, Debug, PartialEq)]
+        #serde_derives
+        #wire_attr
+        #[derive(Debug, Clone, PartialEq)]
         #vis struct #state_name {
             #(#feature_names: bool),*
         }
@@ -236,15 +474,93 @@ fn make_features_state_struct(features: &Features) -> TokenStream {
                 #state_builder_name::new()
             }
 
+            /// Build state from a map keyed by feature. Features missing from the map take their
+            /// defaults.
+            pub fn from_map(
+                map: ::std::collections::HashMap<#features_name, bool>,
+            ) -> Self {
+                let mut state = Self::default();
+                for (feature, value) in map {
+                    match feature {
+                        #(
+                            #features_name::#variants => state.#feature_names = value,
+                        )*
+                    }
+                }
+                state
+            }
+
             #default_fns
         }
 
-        impl ::conspiracy::config::RestartRequired for #state_name {
-            #[inline]
-            fn restart_required(&self, other: &Self) -> bool {
-                #comparison
+        #restart_required
+
+        #wire_struct
+    }
+}
+
+/// Persisted state can outlive the features in it, so the state deserializes through a wire struct
+/// that captures the keys of removed features. They're reported, then ignored. Likewise, state
+/// persisted from before a feature was added still loads, falling back to the default.
+fn make_state_wire_struct(features: &Features) -> (TokenStream, TokenStream) {
+    if !cfg!(feature = "serde") {
+        return (TokenStream::new(), TokenStream::new());
+    }
+
+    let vis = &features.visibility;
+    let state_name = &features.state_name;
+    let wire_name = format_ident!("__{}Wire", state_name);
+    let wire_name_str = wire_name.to_string();
+    let feature_names = features.names(Case::Snake).collect::<Vec<_>>();
+    let defaults = feature_names.iter().map(|name| {
+        let default_fn = format!("{state_name}::default_{name}");
+        quote! { #[serde(default = #default_fn)] }
+    });
+
+    let wire_struct = quote! {
+        #[doc(hidden)]
+        #[derive(::serde::Deserialize)]
+        #vis struct #wire_name {
+            #(
+                #defaults
+                #feature_names: bool,
+            )*
+            #[serde(flatten)]
+            unknown: ::std::collections::BTreeMap<String, ::serde::de::IgnoredAny>,
+        }
+
+        impl From<#wire_name> for #state_name {
+            fn from(wire: #wire_name) -> Self {
+                ::conspiracy::feature_control::macro_targets::ignore_unknown_features(
+                    ::std::any::type_name::<#state_name>(),
+                    wire.unknown.keys().map(String::as_str),
+                );
+
+                Self {
+                    #(#feature_names: wire.#feature_names),*
+                }
             }
         }
+    };
+
+    (quote! { #[serde(from = #wire_name_str)] }, wire_struct)
+}
+
+/// The generated state types are only (de)serializable with the `serde` feature, so users that don't
+/// persist feature state aren't forced to depend on serde.
+fn serde_derives() -> TokenStream {
+    if cfg!(feature = "serde") {
+        quote! { #[derive(::serde::Serialize, ::serde::Deserialize)] }
+    } else {
+        TokenStream::new()
+    }
+}
+
+fn serde_attr(args: TokenStream) -> TokenStream {
+    if cfg!(feature = "serde") {
+        quote! { #[serde(#args)] }
+    } else {
+        TokenStream::new()
     }
 }
 
@@ -253,33 +569,192 @@ fn make_builder(features: &Features) -> TokenStream {
     let state_name = format_ident!("{}State", features.name);
     let builder_name = format_ident!("{}Builder", state_name);
     let builder_fns = features.builder_fns();
+    let required_names = features
+        .required_features()
+        .map(|feature| feature.name.to_string())
+        .collect::<Vec<_>>();
 
+    // Only features marked required are tracked, but `build` is fallible either way so marking a
+    // feature required doesn't change its signature.
+    let required_count = required_names.len();
     quote! {
         #vis struct #builder_name {
             state: #state_name,
+            required_set: [bool; #required_count],
         }
 
         impl #builder_name {
             pub fn new() -> Self {
                 Self {
-                    state: #state_name::default()
+                    state: #state_name::default(),
+                    required_set: [false; #required_count],
                 }
             }
 
-            pub fn build(self) -> #state_name {
-                self.state
+            /// Fails if any feature marked `#[conspiracy(required)]` wasn't explicitly set.
+            pub fn build(
+                self,
+            ) -> Result<#state_name, ::conspiracy::feature_control::MissingRequiredFeaturesError> {
+                let missing = [#(#required_names),*]
+                    .into_iter()
+                    .zip(self.required_set)
+                    .filter(|(_, set)| !set)
+                    .map(|(name, _)| name)
+                    .collect::<Vec<&'static str>>();
+
+                if missing.is_empty() {
+                    Ok(self.state)
+                } else {
+                    Err(::conspiracy::feature_control::MissingRequiredFeaturesError(missing))
+                }
             }
 
             #builder_fns
         }
+    }
+}
 
+struct FeatureEnabled {
+    path: Path,
+    panic: bool,
+}
+
+impl Parse for FeatureEnabled {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut panic = true;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let option: Ident = input.parse()?;
+            if option != "panic" {
+                return Err(syn::Error::new(
+                    option.span(),
+                    "Unknown option, expected `panic = <bool>`",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            panic = input.parse::<LitBool>()?.value;
+        }
+
+        Ok(FeatureEnabled { path, panic })
+    }
+}
+
+fn make_overrides(features: &Features) -> TokenStream {
+    let vis = &features.visibility;
+    let state_name = &features.state_name;
+    let overrides_name = &features.overrides_name;
+    let feature_names = features.names(Case::Snake).collect::<Vec<_>>();
+    let feature_name_strs = feature_names.iter().map(Ident::to_string);
+    let features_name = &features.name;
+    let variants = features.names(Case::Pascal).collect::<Vec<_>>();
+    let serde_derives = serde_derives();
+    let serde_field_attr = serde_attr(quote! { default, skip_serializing_if = "Option::is_none" });
+
+    quote! {
+        #serde_derives
+        #[derive(Debug, Default, Clone, PartialEq)]
+        #vis struct #overrides_name {
+            #(
+                #serde_field_attr
+                #feature_names: Option<bool>
+            ),*
+        }
+
+        impl #overrides_name {
+            #(
+                pub fn #feature_names(mut self, value: bool) -> Self {
+                    self.#feature_names = Some(value);
+                    self
+                }
+            )*
+
+            /// Build overrides from `(name, value)` pairs, where names are the `snake_case` field
+            /// names of the state. Features that aren't present are left unset.
+            pub fn from_map<S: AsRef<str>>(
+                map: impl IntoIterator<Item = (S, bool)>,
+                unknown: ::conspiracy::feature_control::UnknownFeatures,
+            ) -> Result<Self, ::conspiracy::feature_control::UnknownFeatureError> {
+                let mut overrides = Self::default();
+                for (name, value) in map {
+                    match name.as_ref() {
+                        #(
+                            #feature_name_strs => overrides.#feature_names = Some(value),
+                        )*
+                        name => {
+                            if unknown == ::conspiracy::feature_control::UnknownFeatures::Error {
+                                return Err(::conspiracy::feature_control::UnknownFeatureError(
+                                    name.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                }
+                Ok(overrides)
+            }
+        }
+
+        impl #state_name {
+            /// Apply the features set in `overrides`, leaving the rest unchanged.
+            pub fn merge(&mut self, overrides: &#overrides_name) {
+                ::conspiracy::feature_control::FeatureOverrides::apply(overrides, self);
+            }
+
+            /// Parse a comma separated `name=bool` list, e.g. from a `--features` flag, applying
+            /// it on top of the defaults. Unknown names and values other than `true` / `false`
+            /// are errors.
+            pub fn from_overrides_str(
+                overrides: &str,
+            ) -> Result<Self, ::conspiracy::feature_control::OverridesParseError> {
+                let pairs = ::conspiracy::feature_control::macro_targets::parse_overrides(overrides)?;
+                let overrides = #overrides_name::from_map(
+                    pairs,
+                    ::conspiracy::feature_control::UnknownFeatures::Error,
+                )?;
+                let mut state = Self::default();
+                state.merge(&overrides);
+                Ok(state)
+            }
+        }
+
+        impl ::conspiracy::feature_control::FeatureOverrides for #overrides_name {
+            type State = #state_name;
+
+            fn apply(&self, state: &mut #state_name) {
+                #(
+                    if let Some(value) = self.#feature_names {
+                        state.#feature_names = value;
+                    }
+                )*
+            }
+
+            fn get(&self, feature: &#features_name) -> Option<bool> {
+                match *feature {
+                    #(#features_name::#variants => self.#feature_names),*
+                }
+            }
+
+            fn set(&mut self, feature: &#features_name, value: bool) {
+                match *feature {
+                    #(#features_name::#variants => self.#feature_names = Some(value)),*
+                }
+            }
+        }
     }
 }
 
 pub(super) fn feature_enabled(input: LegacyTokenStream) -> LegacyTokenStream {
-    let variant_path = parse_macro_input!(input as Path);
+    let parsed_input = parse_macro_input!(input as FeatureEnabled);
+    let variant_path = parsed_input.path;
     let associated_state_path = get_associated_state_path(variant_path.clone());
 
+    if !parsed_input.panic {
+        return LegacyTokenStream::from(feature_enable_or_default_inner(
+            &variant_path,
+            &associated_state_path,
+        ));
+    }
+
     use_default_in_cfg_test(
         &variant_path,
         &associated_state_path,
@@ -292,6 +767,97 @@ pub(super) fn feature_enabled(input: LegacyTokenStream) -> LegacyTokenStream {
     )
 }
 
+/// The variants passed to [`feature_enabled_all`] / [`feature_enabled_any`].
+struct FeatureVariants {
+    variants: Vec<Path>,
+    feature_state: Path,
+}
+
+impl Parse for FeatureVariants {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let variants = Punctuated::<Path, Token![,]>::parse_terminated(input)?
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let Some(first) = variants.first() else {
+            return Err(input.error("Expected at least one feature variant"));
+        };
+
+        // The paths aren't resolved here, so `Features::Foo` and `crate::Features::Bar` can't be
+        // told apart from variants of different enums. Each variant is checked against the state
+        // of the first instead, mixing enums fails to compile as a type mismatch.
+        let feature_state = get_associated_state_path(first.clone());
+
+        Ok(FeatureVariants {
+            variants,
+            feature_state,
+        })
+    }
+}
+
+pub(super) fn feature_enabled_all(input: LegacyTokenStream) -> LegacyTokenStream {
+    let parsed_input = parse_macro_input!(input as FeatureVariants);
+    feature_enabled_composite(parsed_input, quote! { && })
+}
+
+pub(super) fn feature_enabled_any(input: LegacyTokenStream) -> LegacyTokenStream {
+    let parsed_input = parse_macro_input!(input as FeatureVariants);
+    feature_enabled_composite(parsed_input, quote! { || })
+}
+
+/// Like [`feature_enabled`], but the state is fetched once and shared by every variant.
+fn feature_enabled_composite(input: FeatureVariants, op: TokenStream) -> LegacyTokenStream {
+    let feature_state = &input.feature_state;
+    let join = |exprs: Vec<TokenStream>| {
+        let mut joined = TokenStream::new();
+        for (i, expr) in exprs.into_iter().enumerate() {
+            if i > 0 {
+                joined.extend(op.clone());
+            }
+            joined.extend(quote! { (#expr) });
+        }
+        joined
+    };
+
+    let enabled = join(
+        input
+            .variants
+            .iter()
+            .map(|variant| quote! { ::conspiracy::feature_control::AsFeature::as_feature(&*state, #variant) })
+            .collect(),
+    );
+    let defaults = join(
+        input
+            .variants
+            .iter()
+            .map(|variant| {
+                generate_call_field_default_fn(variant, &get_associated_state_path(variant.clone()))
+            })
+            .collect(),
+    );
+
+    LegacyTokenStream::from(quote! {
+        {
+            #[cfg(test)]
+            {
+                unsafe {
+                    match ::conspiracy::feature_control::macro_targets::try_feature_state::<#feature_state>() {
+                        Ok(state) => #enabled,
+                        Err(_) => #defaults,
+                    }
+                }
+            }
+            #[cfg(not(test))]
+            {
+                unsafe {
+                    let state = ::conspiracy::feature_control::macro_targets::feature_state_unchecked::<#feature_state>();
+                    #enabled
+                }
+            }
+        }
+    })
+}
+
 fn get_associated_state_path(variant_path: Path) -> Path {
     let mut feature_state_path = variant_path;
     let _variant = feature_state_path.segments.pop().unwrap();
@@ -375,7 +941,7 @@ fn generate_call_field_default_fn(variant: &Path, feature_state: &Path) -> Token
             .map(|v| v.to_owned().ident)
             .expect("Named variant not found")
             .to_string()
-            .to_lowercase()
+            .to_case(Case::Snake)
     );
 
     quote! {
@@ -410,3 +976,240 @@ pub(super) fn try_feature_enabled(input: LegacyTokenStream) -> LegacyTokenStream
         }
     })
 }
+
+/// A feature set combining other [`define_features!`] sets, declared as
+/// `enum Name { Part(path::to::Features), .. }`.
+struct CombinedFeatures {
+    attrs: Vec<Attribute>,
+    visibility: Visibility,
+    name: Ident,
+    parts: Punctuated<CombinedPart, Token![,]>,
+}
+
+struct CombinedPart {
+    variant: Ident,
+    features: Path,
+}
+
+impl Parse for CombinedFeatures {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let visibility = input.parse()?;
+        input.parse::<Token![enum]>()?;
+        let name = input.parse()?;
+
+        let content;
+        syn::braced!(content in input);
+        let parts = content.parse_terminated(CombinedPart::parse, Token![,])?;
+        if parts.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &name,
+                "combined features must have at least one part",
+            ));
+        }
+
+        Ok(CombinedFeatures {
+            attrs,
+            visibility,
+            name,
+            parts,
+        })
+    }
+}
+
+impl Parse for CombinedPart {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let variant = input.parse()?;
+        let content;
+        syn::parenthesized!(content in input);
+
+        Ok(CombinedPart {
+            variant,
+            features: content.parse()?,
+        })
+    }
+}
+
+pub(super) fn combine_features(input: LegacyTokenStream) -> LegacyTokenStream {
+    let combined = parse_macro_input!(input as CombinedFeatures);
+    let attrs = &combined.attrs;
+    let vis = &combined.visibility;
+    let name = &combined.name;
+    let state_name = format_ident!("{}State", name);
+    let overrides_name = format_ident!("{}Overrides", name);
+    let derives = feature_enum_derives(attrs);
+    let serde_derives = serde_derives();
+    let serde_default = serde_attr(quote! { default });
+
+    let variants = combined
+        .parts
+        .iter()
+        .map(|part| &part.variant)
+        .collect::<Vec<_>>();
+    let parts = combined
+        .parts
+        .iter()
+        .map(|part| &part.features)
+        .collect::<Vec<_>>();
+    let fields = variants
+        .iter()
+        .map(|variant| format_ident!("{}", variant.to_string().to_case(Case::Snake)))
+        .collect::<Vec<_>>();
+    let part_states = parts
+        .iter()
+        .map(|part| quote! { <#part as ::conspiracy::feature_control::FeatureSet>::State })
+        .collect::<Vec<_>>();
+    let part_overrides = parts
+        .iter()
+        .map(|part| quote! { <#part as ::conspiracy::feature_control::FeatureSet>::Overrides });
+
+    // A restart of any part is a restart of the whole, reported by the part's field name
+    let restart_comparisons = fields
+        .iter()
+        .map(|field| RestartComparison {
+            path: field.to_string(),
+            comparison: quote! {
+                ::conspiracy::config::RestartRequired::restart_required(&self.#field, &other.#field)
+            },
+        })
+        .collect::<Vec<_>>();
+    let restart_required =
+        impl_restart_required(&quote! { #state_name }, &restart_comparisons, &[]);
+
+    LegacyTokenStream::from(quote! {
+        #derives
+        #(#attrs)*
+        #vis enum #name {
+            #(#variants(#parts)),*
+        }
+
+        #(
+            impl From<#parts> for #name {
+                #[inline]
+                fn from(feature: #parts) -> Self {
+                    #name::#variants(feature)
+                }
+            }
+        )*
+
+        /// The state of every part, each behind an `Arc` so checks of a single part can share it.
+        #serde_derives
+        #[derive(Debug, Default, Clone, PartialEq)]
+        #vis struct #state_name {
+            #(
+                #serde_default
+                pub #fields: ::std::sync::Arc<#part_states>
+            ),*
+        }
+
+        #restart_required
+
+        #serde_derives
+        #[derive(Debug, Default, Clone, PartialEq)]
+        #vis struct #overrides_name {
+            #(
+                #serde_default
+                pub #fields: #part_overrides
+            ),*
+        }
+
+        impl ::conspiracy::feature_control::FeatureOverrides for #overrides_name {
+            type State = #state_name;
+
+            fn apply(&self, state: &mut #state_name) {
+                #(
+                    ::conspiracy::feature_control::FeatureOverrides::apply(
+                        &self.#fields,
+                        ::std::sync::Arc::make_mut(&mut state.#fields),
+                    );
+                )*
+            }
+
+            fn get(&self, feature: &#name) -> Option<bool> {
+                match feature {
+                    #(
+                        #name::#variants(feature) => {
+                            ::conspiracy::feature_control::FeatureOverrides::get(&self.#fields, feature)
+                        }
+                    )*
+                }
+            }
+
+            fn set(&mut self, feature: &#name, value: bool) {
+                match feature {
+                    #(
+                        #name::#variants(feature) => {
+                            ::conspiracy::feature_control::FeatureOverrides::set(
+                                &mut self.#fields,
+                                feature,
+                                value,
+                            )
+                        }
+                    )*
+                }
+            }
+        }
+
+        impl ::conspiracy::feature_control::AsFeature for #state_name {
+            type Feature = #name;
+
+            #[inline]
+            fn as_feature(&self, feature: #name) -> bool {
+                match feature {
+                    #(
+                        #name::#variants(feature) => {
+                            ::conspiracy::feature_control::AsFeature::as_feature(&*self.#fields, feature)
+                        }
+                    )*
+                }
+            }
+        }
+
+        impl ::conspiracy::feature_control::FeatureSet for #name {
+            type State = #state_name;
+            type Overrides = #overrides_name;
+
+            #[inline]
+            fn name(&self) -> &'static str {
+                match self {
+                    #(
+                        #name::#variants(feature) => {
+                            ::conspiracy::feature_control::FeatureSet::name(feature)
+                        }
+                    )*
+                }
+            }
+
+            fn all() -> &'static [Self] {
+                static ALL: std::sync::OnceLock<Vec<#name>> = std::sync::OnceLock::new();
+                ALL.get_or_init(|| {
+                    let mut all = Vec::new();
+                    #(
+                        all.extend(
+                            <#parts as ::conspiracy::feature_control::FeatureSet>::all()
+                                .iter()
+                                .map(|feature| #name::#variants(*feature)),
+                        );
+                    )*
+                    all
+                })
+            }
+
+            fn state_part(
+                state: &#state_name,
+                part: std::any::TypeId,
+            ) -> Option<::std::sync::Arc<dyn std::any::Any + Send + Sync>> {
+                #(
+                    if part == std::any::TypeId::of::<#part_states>() {
+                        return Some(state.#fields.clone());
+                    }
+                )*
+                None
+            }
+
+            fn state_parts() -> Vec<std::any::TypeId> {
+                vec![#(std::any::TypeId::of::<#part_states>()),*]
+            }
+        }
+    })
+}
@@ -23,16 +23,36 @@ pub fn config_struct(item: TokenStream) -> TokenStream {
     config::config_struct(item)
 }
 
+#[proc_macro_derive(ConfigStruct, attributes(conspiracy))]
+pub fn derive_config_struct(item: TokenStream) -> TokenStream {
+    config::derive_config_struct(item)
+}
+
 #[proc_macro]
 pub fn define_features(item: TokenStream) -> TokenStream {
     feature_control::define_features(item)
 }
 
+#[proc_macro]
+pub fn combine_features(item: TokenStream) -> TokenStream {
+    feature_control::combine_features(item)
+}
+
 #[proc_macro]
 pub fn feature_enabled(item: TokenStream) -> TokenStream {
     feature_control::feature_enabled(item)
 }
 
+#[proc_macro]
+pub fn feature_enabled_all(item: TokenStream) -> TokenStream {
+    feature_control::feature_enabled_all(item)
+}
+
+#[proc_macro]
+pub fn feature_enabled_any(item: TokenStream) -> TokenStream {
+    feature_control::feature_enabled_any(item)
+}
+
 #[proc_macro]
 pub fn feature_enabled_or(item: TokenStream) -> TokenStream {
     feature_control::feature_enabled_or(item)
@@ -48,11 +68,13 @@ pub fn try_feature_enabled(item: TokenStream) -> TokenStream {
     feature_control::try_feature_enabled(item)
 }
 
+#[cfg(feature = "serde")]
 #[proc_macro_attribute]
 pub fn full_serde(attr: TokenStream, item: TokenStream) -> TokenStream {
     config::full_serde(attr, item)
 }
 
+#[cfg(feature = "serde")]
 #[proc_macro_attribute]
 pub fn full_serde_as(attr: TokenStream, item: TokenStream) -> TokenStream {
     config::full_serde_as(attr, item)
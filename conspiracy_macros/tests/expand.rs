@@ -0,0 +1,15 @@
+// Snapshots of the code generated for representative inputs, so changes to the generated code are
+// always deliberate. Requires `cargo-expand`, run with `MACROTEST=overwrite` to accept changes.
+//
+// The snapshots include the expansions of the std and serde derives the macros emit, which change
+// between rustc and serde_derive releases. serde_derive is pinned in the dev-dependencies and the
+// test only runs on request, with the toolchain the snapshots were generated with:
+//
+//     cargo +1.95.0 test -p conspiracy_macros --test expand -- --ignored
+#![cfg(feature = "serde")]
+
+#[test]
+#[ignore = "depends on the rustc version, run with the pinned toolchain"]
+fn expand() {
+    macrotest::expand("tests/expand/*.rs");
+}
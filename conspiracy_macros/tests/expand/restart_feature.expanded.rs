@@ -0,0 +1,1127 @@
+use conspiracy::feature_control::define_features;
+pub enum Features {
+    NewScheduler,
+    DarkMode,
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for Features {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::write_str(
+            f,
+            match self {
+                Features::NewScheduler => "NewScheduler",
+                Features::DarkMode => "DarkMode",
+            },
+        )
+    }
+}
+#[automatically_derived]
+#[doc(hidden)]
+unsafe impl ::core::clone::TrivialClone for Features {}
+#[automatically_derived]
+impl ::core::clone::Clone for Features {
+    #[inline]
+    fn clone(&self) -> Features {
+        *self
+    }
+}
+#[automatically_derived]
+impl ::core::marker::Copy for Features {}
+#[automatically_derived]
+impl ::core::marker::StructuralPartialEq for Features {}
+#[automatically_derived]
+impl ::core::cmp::PartialEq for Features {
+    #[inline]
+    fn eq(&self, other: &Features) -> bool {
+        let __self_discr = ::core::intrinsics::discriminant_value(self);
+        let __arg1_discr = ::core::intrinsics::discriminant_value(other);
+        __self_discr == __arg1_discr
+    }
+}
+#[automatically_derived]
+impl ::core::cmp::Eq for Features {
+    #[doc(hidden)]
+    #[coverage(off)]
+    fn assert_fields_are_eq(&self) {}
+}
+#[automatically_derived]
+impl ::core::cmp::PartialOrd for Features {
+    #[inline]
+    fn partial_cmp(
+        &self,
+        other: &Features,
+    ) -> ::core::option::Option<::core::cmp::Ordering> {
+        let __self_discr = ::core::intrinsics::discriminant_value(self);
+        let __arg1_discr = ::core::intrinsics::discriminant_value(other);
+        ::core::cmp::PartialOrd::partial_cmp(&__self_discr, &__arg1_discr)
+    }
+}
+#[automatically_derived]
+impl ::core::cmp::Ord for Features {
+    #[inline]
+    fn cmp(&self, other: &Features) -> ::core::cmp::Ordering {
+        let __self_discr = ::core::intrinsics::discriminant_value(self);
+        let __arg1_discr = ::core::intrinsics::discriminant_value(other);
+        ::core::cmp::Ord::cmp(&__self_discr, &__arg1_discr)
+    }
+}
+#[automatically_derived]
+impl ::core::hash::Hash for Features {
+    #[inline]
+    fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+        let __self_discr = ::core::intrinsics::discriminant_value(self);
+        ::core::hash::Hash::hash(&__self_discr, state)
+    }
+}
+impl Features {
+    /// Every feature, in declaration order.
+    pub const ALL: [Features; 2usize] = [Features::NewScheduler, Features::DarkMode];
+    pub fn builder() -> FeaturesStateBuilder {
+        FeaturesState::builder()
+    }
+    /// Iterates over every feature, in declaration order.
+    pub fn iter() -> std::array::IntoIter<Features, 2usize> {
+        Self::ALL.into_iter()
+    }
+    /// The name of the feature, matching its key in the serialized state.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Features::NewScheduler => "new_scheduler",
+            Features::DarkMode => "dark_mode",
+        }
+    }
+    /// The doc comment of the feature, or an empty string if it isn't documented.
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Features::NewScheduler => "",
+            Features::DarkMode => "",
+        }
+    }
+    /// The owner set with `#[conspiracy(owner = "...")]`, if any.
+    pub fn owner(&self) -> Option<&'static str> {
+        match *self {
+            Features::NewScheduler => None,
+            Features::DarkMode => None,
+        }
+    }
+}
+#[serde(from = "__FeaturesStateWire")]
+pub struct FeaturesState {
+    new_scheduler: bool,
+    dark_mode: bool,
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for FeaturesState {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::debug_struct_field2_finish(
+            f,
+            "FeaturesState",
+            "new_scheduler",
+            &self.new_scheduler,
+            "dark_mode",
+            &&self.dark_mode,
+        )
+    }
+}
+#[automatically_derived]
+impl ::core::clone::Clone for FeaturesState {
+    #[inline]
+    fn clone(&self) -> FeaturesState {
+        FeaturesState {
+            new_scheduler: ::core::clone::Clone::clone(&self.new_scheduler),
+            dark_mode: ::core::clone::Clone::clone(&self.dark_mode),
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::marker::StructuralPartialEq for FeaturesState {}
+#[automatically_derived]
+impl ::core::cmp::PartialEq for FeaturesState {
+    #[inline]
+    fn eq(&self, other: &FeaturesState) -> bool {
+        self.new_scheduler == other.new_scheduler && self.dark_mode == other.dark_mode
+    }
+}
+#[doc(hidden)]
+#[allow(
+    non_upper_case_globals,
+    unused_attributes,
+    unused_qualifications,
+    clippy::absolute_paths,
+)]
+const _: () = {
+    #[allow(unused_extern_crates, clippy::useless_attribute)]
+    extern crate serde as _serde;
+    #[automatically_derived]
+    impl _serde::Serialize for FeaturesState {
+        fn serialize<__S>(
+            &self,
+            __serializer: __S,
+        ) -> _serde::__private229::Result<__S::Ok, __S::Error>
+        where
+            __S: _serde::Serializer,
+        {
+            let mut __serde_state = _serde::Serializer::serialize_struct(
+                __serializer,
+                "FeaturesState",
+                false as usize + 1 + 1,
+            )?;
+            _serde::ser::SerializeStruct::serialize_field(
+                &mut __serde_state,
+                "new_scheduler",
+                &self.new_scheduler,
+            )?;
+            _serde::ser::SerializeStruct::serialize_field(
+                &mut __serde_state,
+                "dark_mode",
+                &self.dark_mode,
+            )?;
+            _serde::ser::SerializeStruct::end(__serde_state)
+        }
+    }
+};
+#[doc(hidden)]
+#[allow(
+    non_upper_case_globals,
+    unused_attributes,
+    unused_qualifications,
+    clippy::absolute_paths,
+)]
+const _: () = {
+    #[allow(unused_extern_crates, clippy::useless_attribute)]
+    extern crate serde as _serde;
+    #[automatically_derived]
+    impl<'de> _serde::Deserialize<'de> for FeaturesState {
+        fn deserialize<__D>(
+            __deserializer: __D,
+        ) -> _serde::__private229::Result<Self, __D::Error>
+        where
+            __D: _serde::Deserializer<'de>,
+        {
+            _serde::__private229::Result::map(
+                <__FeaturesStateWire as _serde::Deserialize>::deserialize(
+                    __deserializer,
+                ),
+                _serde::__private229::From::from,
+            )
+        }
+    }
+};
+impl FeaturesState {
+    pub fn builder() -> FeaturesStateBuilder {
+        FeaturesStateBuilder::new()
+    }
+    /// Build state from a map keyed by feature. Features missing from the map take their
+    /// defaults.
+    pub fn from_map(map: ::std::collections::HashMap<Features, bool>) -> Self {
+        let mut state = Self::default();
+        for (feature, value) in map {
+            match feature {
+                Features::NewScheduler => state.new_scheduler = value,
+                Features::DarkMode => state.dark_mode = value,
+            }
+        }
+        state
+    }
+    pub const fn default_new_scheduler() -> bool {
+        false
+    }
+    pub const fn default_dark_mode() -> bool {
+        true
+    }
+}
+impl ::conspiracy::config::RestartRequired for FeaturesState {
+    #[inline]
+    fn restart_required(&self, other: &Self) -> bool {
+        self.new_scheduler != other.new_scheduler
+    }
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        #[allow(unused_mut)]
+        let mut reasons = Vec::new();
+        if self.new_scheduler != other.new_scheduler {
+            reasons.push("new_scheduler");
+        }
+        reasons
+    }
+}
+#[doc(hidden)]
+pub struct __FeaturesStateWire {
+    #[serde(default = "FeaturesState::default_new_scheduler")]
+    new_scheduler: bool,
+    #[serde(default = "FeaturesState::default_dark_mode")]
+    dark_mode: bool,
+    #[serde(flatten)]
+    unknown: ::std::collections::BTreeMap<String, ::serde::de::IgnoredAny>,
+}
+#[doc(hidden)]
+#[allow(
+    non_upper_case_globals,
+    unused_attributes,
+    unused_qualifications,
+    clippy::absolute_paths,
+)]
+const _: () = {
+    #[allow(unused_extern_crates, clippy::useless_attribute)]
+    extern crate serde as _serde;
+    #[automatically_derived]
+    impl<'de> _serde::Deserialize<'de> for __FeaturesStateWire {
+        fn deserialize<__D>(
+            __deserializer: __D,
+        ) -> _serde::__private229::Result<Self, __D::Error>
+        where
+            __D: _serde::Deserializer<'de>,
+        {
+            #[allow(non_camel_case_types)]
+            #[doc(hidden)]
+            enum __Field<'de> {
+                __field0,
+                __field1,
+                __other(_serde::__private229::de::Content<'de>),
+            }
+            #[doc(hidden)]
+            struct __FieldVisitor;
+            #[automatically_derived]
+            impl<'de> _serde::de::Visitor<'de> for __FieldVisitor {
+                type Value = __Field<'de>;
+                fn expecting(
+                    &self,
+                    __formatter: &mut _serde::__private229::Formatter,
+                ) -> _serde::__private229::fmt::Result {
+                    _serde::__private229::Formatter::write_str(
+                        __formatter,
+                        "field identifier",
+                    )
+                }
+                fn visit_bool<__E>(
+                    self,
+                    __value: bool,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(
+                            _serde::__private229::de::Content::Bool(__value),
+                        ),
+                    )
+                }
+                fn visit_i8<__E>(
+                    self,
+                    __value: i8,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::I8(__value)),
+                    )
+                }
+                fn visit_i16<__E>(
+                    self,
+                    __value: i16,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::I16(__value)),
+                    )
+                }
+                fn visit_i32<__E>(
+                    self,
+                    __value: i32,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::I32(__value)),
+                    )
+                }
+                fn visit_i64<__E>(
+                    self,
+                    __value: i64,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::I64(__value)),
+                    )
+                }
+                fn visit_u8<__E>(
+                    self,
+                    __value: u8,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::U8(__value)),
+                    )
+                }
+                fn visit_u16<__E>(
+                    self,
+                    __value: u16,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::U16(__value)),
+                    )
+                }
+                fn visit_u32<__E>(
+                    self,
+                    __value: u32,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::U32(__value)),
+                    )
+                }
+                fn visit_u64<__E>(
+                    self,
+                    __value: u64,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::U64(__value)),
+                    )
+                }
+                fn visit_f32<__E>(
+                    self,
+                    __value: f32,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::F32(__value)),
+                    )
+                }
+                fn visit_f64<__E>(
+                    self,
+                    __value: f64,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::F64(__value)),
+                    )
+                }
+                fn visit_char<__E>(
+                    self,
+                    __value: char,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(
+                            _serde::__private229::de::Content::Char(__value),
+                        ),
+                    )
+                }
+                fn visit_unit<__E>(
+                    self,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    _serde::__private229::Ok(
+                        __Field::__other(_serde::__private229::de::Content::Unit),
+                    )
+                }
+                fn visit_str<__E>(
+                    self,
+                    __value: &str,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        "new_scheduler" => _serde::__private229::Ok(__Field::__field0),
+                        "dark_mode" => _serde::__private229::Ok(__Field::__field1),
+                        _ => {
+                            let __value = _serde::__private229::de::Content::String(
+                                _serde::__private229::ToString::to_string(__value),
+                            );
+                            _serde::__private229::Ok(__Field::__other(__value))
+                        }
+                    }
+                }
+                fn visit_bytes<__E>(
+                    self,
+                    __value: &[u8],
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        b"new_scheduler" => _serde::__private229::Ok(__Field::__field0),
+                        b"dark_mode" => _serde::__private229::Ok(__Field::__field1),
+                        _ => {
+                            let __value = _serde::__private229::de::Content::ByteBuf(
+                                __value.to_vec(),
+                            );
+                            _serde::__private229::Ok(__Field::__other(__value))
+                        }
+                    }
+                }
+                fn visit_borrowed_str<__E>(
+                    self,
+                    __value: &'de str,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        "new_scheduler" => _serde::__private229::Ok(__Field::__field0),
+                        "dark_mode" => _serde::__private229::Ok(__Field::__field1),
+                        _ => {
+                            let __value = _serde::__private229::de::Content::Str(
+                                __value,
+                            );
+                            _serde::__private229::Ok(__Field::__other(__value))
+                        }
+                    }
+                }
+                fn visit_borrowed_bytes<__E>(
+                    self,
+                    __value: &'de [u8],
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        b"new_scheduler" => _serde::__private229::Ok(__Field::__field0),
+                        b"dark_mode" => _serde::__private229::Ok(__Field::__field1),
+                        _ => {
+                            let __value = _serde::__private229::de::Content::Bytes(
+                                __value,
+                            );
+                            _serde::__private229::Ok(__Field::__other(__value))
+                        }
+                    }
+                }
+            }
+            #[automatically_derived]
+            impl<'de> _serde::Deserialize<'de> for __Field<'de> {
+                #[inline]
+                fn deserialize<__D>(
+                    __deserializer: __D,
+                ) -> _serde::__private229::Result<Self, __D::Error>
+                where
+                    __D: _serde::Deserializer<'de>,
+                {
+                    _serde::Deserializer::deserialize_identifier(
+                        __deserializer,
+                        __FieldVisitor,
+                    )
+                }
+            }
+            #[doc(hidden)]
+            struct __Visitor<'de> {
+                marker: _serde::__private229::PhantomData<__FeaturesStateWire>,
+                lifetime: _serde::__private229::PhantomData<&'de ()>,
+            }
+            #[automatically_derived]
+            impl<'de> _serde::de::Visitor<'de> for __Visitor<'de> {
+                type Value = __FeaturesStateWire;
+                fn expecting(
+                    &self,
+                    __formatter: &mut _serde::__private229::Formatter,
+                ) -> _serde::__private229::fmt::Result {
+                    _serde::__private229::Formatter::write_str(
+                        __formatter,
+                        "struct __FeaturesStateWire",
+                    )
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> _serde::__private229::Result<Self::Value, __A::Error>
+                where
+                    __A: _serde::de::MapAccess<'de>,
+                {
+                    let mut __field0: _serde::__private229::Option<bool> = _serde::__private229::None;
+                    let mut __field1: _serde::__private229::Option<bool> = _serde::__private229::None;
+                    let mut __collect = _serde::__private229::Vec::<
+                        _serde::__private229::Option<
+                            (
+                                _serde::__private229::de::Content,
+                                _serde::__private229::de::Content,
+                            ),
+                        >,
+                    >::new();
+                    while let _serde::__private229::Some(__key) = _serde::de::MapAccess::next_key::<
+                        __Field,
+                    >(&mut __map)? {
+                        match __key {
+                            __Field::__field0 => {
+                                if _serde::__private229::Option::is_some(&__field0) {
+                                    return _serde::__private229::Err(
+                                        <__A::Error as _serde::de::Error>::duplicate_field(
+                                            "new_scheduler",
+                                        ),
+                                    );
+                                }
+                                __field0 = _serde::__private229::Some(
+                                    _serde::de::MapAccess::next_value::<bool>(&mut __map)?,
+                                );
+                            }
+                            __Field::__field1 => {
+                                if _serde::__private229::Option::is_some(&__field1) {
+                                    return _serde::__private229::Err(
+                                        <__A::Error as _serde::de::Error>::duplicate_field(
+                                            "dark_mode",
+                                        ),
+                                    );
+                                }
+                                __field1 = _serde::__private229::Some(
+                                    _serde::de::MapAccess::next_value::<bool>(&mut __map)?,
+                                );
+                            }
+                            __Field::__other(__name) => {
+                                __collect
+                                    .push(
+                                        _serde::__private229::Some((
+                                            __name,
+                                            _serde::de::MapAccess::next_value_seed(
+                                                &mut __map,
+                                                _serde::__private229::de::ContentVisitor::new(),
+                                            )?,
+                                        )),
+                                    );
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        _serde::__private229::Some(__field0) => __field0,
+                        _serde::__private229::None => {
+                            FeaturesState::default_new_scheduler()
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        _serde::__private229::Some(__field1) => __field1,
+                        _serde::__private229::None => FeaturesState::default_dark_mode(),
+                    };
+                    let __field2: ::std::collections::BTreeMap<
+                        String,
+                        ::serde::de::IgnoredAny,
+                    > = _serde::de::Deserialize::deserialize(
+                        _serde::__private229::de::FlatMapDeserializer(
+                            &mut __collect,
+                            _serde::__private229::PhantomData,
+                        ),
+                    )?;
+                    _serde::__private229::Ok(__FeaturesStateWire {
+                        new_scheduler: __field0,
+                        dark_mode: __field1,
+                        unknown: __field2,
+                    })
+                }
+            }
+            _serde::Deserializer::deserialize_map(
+                __deserializer,
+                __Visitor {
+                    marker: _serde::__private229::PhantomData::<__FeaturesStateWire>,
+                    lifetime: _serde::__private229::PhantomData,
+                },
+            )
+        }
+    }
+};
+impl From<__FeaturesStateWire> for FeaturesState {
+    fn from(wire: __FeaturesStateWire) -> Self {
+        ::conspiracy::feature_control::macro_targets::ignore_unknown_features(
+            ::std::any::type_name::<FeaturesState>(),
+            wire.unknown.keys().map(String::as_str),
+        );
+        Self {
+            new_scheduler: wire.new_scheduler,
+            dark_mode: wire.dark_mode,
+        }
+    }
+}
+impl Default for FeaturesState {
+    fn default() -> Self {
+        Self {
+            new_scheduler: Self::default_new_scheduler(),
+            dark_mode: Self::default_dark_mode(),
+        }
+    }
+}
+impl ::conspiracy::feature_control::AsFeature for FeaturesState {
+    type Feature = Features;
+    #[inline]
+    fn as_feature(&self, feature: Features) -> bool {
+        match feature {
+            Features::NewScheduler => self.new_scheduler,
+            Features::DarkMode => self.dark_mode,
+        }
+    }
+}
+impl ::conspiracy::feature_control::FeatureSet for Features {
+    type State = FeaturesState;
+    type Overrides = FeaturesOverrides;
+    #[inline]
+    fn name(&self) -> &'static str {
+        Features::name(self)
+    }
+    #[inline]
+    fn all() -> &'static [Self] {
+        &Self::ALL
+    }
+}
+pub struct FeaturesStateBuilder {
+    state: FeaturesState,
+    required_set: [bool; 0usize],
+}
+impl FeaturesStateBuilder {
+    pub fn new() -> Self {
+        Self {
+            state: FeaturesState::default(),
+            required_set: [false; 0usize],
+        }
+    }
+    /// Fails if any feature marked `#[conspiracy(required)]` wasn't explicitly set.
+    pub fn build(
+        self,
+    ) -> Result<
+        FeaturesState,
+        ::conspiracy::feature_control::MissingRequiredFeaturesError,
+    > {
+        let missing = []
+            .into_iter()
+            .zip(self.required_set)
+            .filter(|(_, set)| !set)
+            .map(|(name, _)| name)
+            .collect::<Vec<&'static str>>();
+        if missing.is_empty() {
+            Ok(self.state)
+        } else {
+            Err(::conspiracy::feature_control::MissingRequiredFeaturesError(missing))
+        }
+    }
+    pub fn new_scheduler(mut self, value: bool) -> Self {
+        self.state.new_scheduler = value;
+        self
+    }
+    pub fn dark_mode(mut self, value: bool) -> Self {
+        self.state.dark_mode = value;
+        self
+    }
+}
+pub struct FeaturesOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    new_scheduler: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dark_mode: Option<bool>,
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for FeaturesOverrides {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::debug_struct_field2_finish(
+            f,
+            "FeaturesOverrides",
+            "new_scheduler",
+            &self.new_scheduler,
+            "dark_mode",
+            &&self.dark_mode,
+        )
+    }
+}
+#[automatically_derived]
+impl ::core::default::Default for FeaturesOverrides {
+    #[inline]
+    fn default() -> FeaturesOverrides {
+        FeaturesOverrides {
+            new_scheduler: ::core::default::Default::default(),
+            dark_mode: ::core::default::Default::default(),
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::clone::Clone for FeaturesOverrides {
+    #[inline]
+    fn clone(&self) -> FeaturesOverrides {
+        FeaturesOverrides {
+            new_scheduler: ::core::clone::Clone::clone(&self.new_scheduler),
+            dark_mode: ::core::clone::Clone::clone(&self.dark_mode),
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::marker::StructuralPartialEq for FeaturesOverrides {}
+#[automatically_derived]
+impl ::core::cmp::PartialEq for FeaturesOverrides {
+    #[inline]
+    fn eq(&self, other: &FeaturesOverrides) -> bool {
+        self.new_scheduler == other.new_scheduler && self.dark_mode == other.dark_mode
+    }
+}
+#[doc(hidden)]
+#[allow(
+    non_upper_case_globals,
+    unused_attributes,
+    unused_qualifications,
+    clippy::absolute_paths,
+)]
+const _: () = {
+    #[allow(unused_extern_crates, clippy::useless_attribute)]
+    extern crate serde as _serde;
+    #[automatically_derived]
+    impl _serde::Serialize for FeaturesOverrides {
+        fn serialize<__S>(
+            &self,
+            __serializer: __S,
+        ) -> _serde::__private229::Result<__S::Ok, __S::Error>
+        where
+            __S: _serde::Serializer,
+        {
+            let mut __serde_state = _serde::Serializer::serialize_struct(
+                __serializer,
+                "FeaturesOverrides",
+                false as usize + if Option::is_none(&self.new_scheduler) { 0 } else { 1 }
+                    + if Option::is_none(&self.dark_mode) { 0 } else { 1 },
+            )?;
+            if !Option::is_none(&self.new_scheduler) {
+                _serde::ser::SerializeStruct::serialize_field(
+                    &mut __serde_state,
+                    "new_scheduler",
+                    &self.new_scheduler,
+                )?;
+            } else {
+                _serde::ser::SerializeStruct::skip_field(
+                    &mut __serde_state,
+                    "new_scheduler",
+                )?;
+            }
+            if !Option::is_none(&self.dark_mode) {
+                _serde::ser::SerializeStruct::serialize_field(
+                    &mut __serde_state,
+                    "dark_mode",
+                    &self.dark_mode,
+                )?;
+            } else {
+                _serde::ser::SerializeStruct::skip_field(
+                    &mut __serde_state,
+                    "dark_mode",
+                )?;
+            }
+            _serde::ser::SerializeStruct::end(__serde_state)
+        }
+    }
+};
+#[doc(hidden)]
+#[allow(
+    non_upper_case_globals,
+    unused_attributes,
+    unused_qualifications,
+    clippy::absolute_paths,
+)]
+const _: () = {
+    #[allow(unused_extern_crates, clippy::useless_attribute)]
+    extern crate serde as _serde;
+    #[automatically_derived]
+    impl<'de> _serde::Deserialize<'de> for FeaturesOverrides {
+        fn deserialize<__D>(
+            __deserializer: __D,
+        ) -> _serde::__private229::Result<Self, __D::Error>
+        where
+            __D: _serde::Deserializer<'de>,
+        {
+            #[allow(non_camel_case_types)]
+            #[doc(hidden)]
+            enum __Field {
+                __field0,
+                __field1,
+                __ignore,
+            }
+            #[doc(hidden)]
+            struct __FieldVisitor;
+            #[automatically_derived]
+            impl<'de> _serde::de::Visitor<'de> for __FieldVisitor {
+                type Value = __Field;
+                fn expecting(
+                    &self,
+                    __formatter: &mut _serde::__private229::Formatter,
+                ) -> _serde::__private229::fmt::Result {
+                    _serde::__private229::Formatter::write_str(
+                        __formatter,
+                        "field identifier",
+                    )
+                }
+                fn visit_u64<__E>(
+                    self,
+                    __value: u64,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        0u64 => _serde::__private229::Ok(__Field::__field0),
+                        1u64 => _serde::__private229::Ok(__Field::__field1),
+                        _ => _serde::__private229::Ok(__Field::__ignore),
+                    }
+                }
+                fn visit_str<__E>(
+                    self,
+                    __value: &str,
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        "new_scheduler" => _serde::__private229::Ok(__Field::__field0),
+                        "dark_mode" => _serde::__private229::Ok(__Field::__field1),
+                        _ => _serde::__private229::Ok(__Field::__ignore),
+                    }
+                }
+                fn visit_bytes<__E>(
+                    self,
+                    __value: &[u8],
+                ) -> _serde::__private229::Result<Self::Value, __E>
+                where
+                    __E: _serde::de::Error,
+                {
+                    match __value {
+                        b"new_scheduler" => _serde::__private229::Ok(__Field::__field0),
+                        b"dark_mode" => _serde::__private229::Ok(__Field::__field1),
+                        _ => _serde::__private229::Ok(__Field::__ignore),
+                    }
+                }
+            }
+            #[automatically_derived]
+            impl<'de> _serde::Deserialize<'de> for __Field {
+                #[inline]
+                fn deserialize<__D>(
+                    __deserializer: __D,
+                ) -> _serde::__private229::Result<Self, __D::Error>
+                where
+                    __D: _serde::Deserializer<'de>,
+                {
+                    _serde::Deserializer::deserialize_identifier(
+                        __deserializer,
+                        __FieldVisitor,
+                    )
+                }
+            }
+            #[doc(hidden)]
+            struct __Visitor<'de> {
+                marker: _serde::__private229::PhantomData<FeaturesOverrides>,
+                lifetime: _serde::__private229::PhantomData<&'de ()>,
+            }
+            #[automatically_derived]
+            impl<'de> _serde::de::Visitor<'de> for __Visitor<'de> {
+                type Value = FeaturesOverrides;
+                fn expecting(
+                    &self,
+                    __formatter: &mut _serde::__private229::Formatter,
+                ) -> _serde::__private229::fmt::Result {
+                    _serde::__private229::Formatter::write_str(
+                        __formatter,
+                        "struct FeaturesOverrides",
+                    )
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> _serde::__private229::Result<Self::Value, __A::Error>
+                where
+                    __A: _serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match _serde::de::SeqAccess::next_element::<
+                        Option<bool>,
+                    >(&mut __seq)? {
+                        _serde::__private229::Some(__value) => __value,
+                        _serde::__private229::None => {
+                            _serde::__private229::Default::default()
+                        }
+                    };
+                    let __field1 = match _serde::de::SeqAccess::next_element::<
+                        Option<bool>,
+                    >(&mut __seq)? {
+                        _serde::__private229::Some(__value) => __value,
+                        _serde::__private229::None => {
+                            _serde::__private229::Default::default()
+                        }
+                    };
+                    _serde::__private229::Ok(FeaturesOverrides {
+                        new_scheduler: __field0,
+                        dark_mode: __field1,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> _serde::__private229::Result<Self::Value, __A::Error>
+                where
+                    __A: _serde::de::MapAccess<'de>,
+                {
+                    let mut __field0: _serde::__private229::Option<Option<bool>> = _serde::__private229::None;
+                    let mut __field1: _serde::__private229::Option<Option<bool>> = _serde::__private229::None;
+                    while let _serde::__private229::Some(__key) = _serde::de::MapAccess::next_key::<
+                        __Field,
+                    >(&mut __map)? {
+                        match __key {
+                            __Field::__field0 => {
+                                if _serde::__private229::Option::is_some(&__field0) {
+                                    return _serde::__private229::Err(
+                                        <__A::Error as _serde::de::Error>::duplicate_field(
+                                            "new_scheduler",
+                                        ),
+                                    );
+                                }
+                                __field0 = _serde::__private229::Some(
+                                    _serde::de::MapAccess::next_value::<
+                                        Option<bool>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Field::__field1 => {
+                                if _serde::__private229::Option::is_some(&__field1) {
+                                    return _serde::__private229::Err(
+                                        <__A::Error as _serde::de::Error>::duplicate_field(
+                                            "dark_mode",
+                                        ),
+                                    );
+                                }
+                                __field1 = _serde::__private229::Some(
+                                    _serde::de::MapAccess::next_value::<
+                                        Option<bool>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            _ => {
+                                let _ = _serde::de::MapAccess::next_value::<
+                                    _serde::de::IgnoredAny,
+                                >(&mut __map)?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        _serde::__private229::Some(__field0) => __field0,
+                        _serde::__private229::None => {
+                            _serde::__private229::Default::default()
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        _serde::__private229::Some(__field1) => __field1,
+                        _serde::__private229::None => {
+                            _serde::__private229::Default::default()
+                        }
+                    };
+                    _serde::__private229::Ok(FeaturesOverrides {
+                        new_scheduler: __field0,
+                        dark_mode: __field1,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const FIELDS: &'static [&'static str] = &["new_scheduler", "dark_mode"];
+            _serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "FeaturesOverrides",
+                FIELDS,
+                __Visitor {
+                    marker: _serde::__private229::PhantomData::<FeaturesOverrides>,
+                    lifetime: _serde::__private229::PhantomData,
+                },
+            )
+        }
+    }
+};
+impl FeaturesOverrides {
+    pub fn new_scheduler(mut self, value: bool) -> Self {
+        self.new_scheduler = Some(value);
+        self
+    }
+    pub fn dark_mode(mut self, value: bool) -> Self {
+        self.dark_mode = Some(value);
+        self
+    }
+    /// Build overrides from `(name, value)` pairs, where names are the `snake_case` field
+    /// names of the state. Features that aren't present are left unset.
+    pub fn from_map<S: AsRef<str>>(
+        map: impl IntoIterator<Item = (S, bool)>,
+        unknown: ::conspiracy::feature_control::UnknownFeatures,
+    ) -> Result<Self, ::conspiracy::feature_control::UnknownFeatureError> {
+        let mut overrides = Self::default();
+        for (name, value) in map {
+            match name.as_ref() {
+                "new_scheduler" => overrides.new_scheduler = Some(value),
+                "dark_mode" => overrides.dark_mode = Some(value),
+                name => {
+                    if unknown == ::conspiracy::feature_control::UnknownFeatures::Error {
+                        return Err(
+                            ::conspiracy::feature_control::UnknownFeatureError(
+                                name.to_string(),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        Ok(overrides)
+    }
+}
+impl FeaturesState {
+    /// Apply the features set in `overrides`, leaving the rest unchanged.
+    pub fn merge(&mut self, overrides: &FeaturesOverrides) {
+        ::conspiracy::feature_control::FeatureOverrides::apply(overrides, self);
+    }
+    /// Parse a comma separated `name=bool` list, e.g. from a `--features` flag, applying
+    /// it on top of the defaults. Unknown names and values other than `true` / `false`
+    /// are errors.
+    pub fn from_overrides_str(
+        overrides: &str,
+    ) -> Result<Self, ::conspiracy::feature_control::OverridesParseError> {
+        let pairs = ::conspiracy::feature_control::macro_targets::parse_overrides(
+            overrides,
+        )?;
+        let overrides = FeaturesOverrides::from_map(
+            pairs,
+            ::conspiracy::feature_control::UnknownFeatures::Error,
+        )?;
+        let mut state = Self::default();
+        state.merge(&overrides);
+        Ok(state)
+    }
+}
+impl ::conspiracy::feature_control::FeatureOverrides for FeaturesOverrides {
+    type State = FeaturesState;
+    fn apply(&self, state: &mut FeaturesState) {
+        if let Some(value) = self.new_scheduler {
+            state.new_scheduler = value;
+        }
+        if let Some(value) = self.dark_mode {
+            state.dark_mode = value;
+        }
+    }
+    fn get(&self, feature: &Features) -> Option<bool> {
+        match *feature {
+            Features::NewScheduler => self.new_scheduler,
+            Features::DarkMode => self.dark_mode,
+        }
+    }
+    fn set(&mut self, feature: &Features, value: bool) {
+        match *feature {
+            Features::NewScheduler => self.new_scheduler = Some(value),
+            Features::DarkMode => self.dark_mode = Some(value),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use conspiracy::feature_control::define_features;
+
+define_features!(
+    pub enum Features {
+        #[conspiracy(restart)]
+        NewScheduler => false,
+        DarkMode => true,
+    }
+);
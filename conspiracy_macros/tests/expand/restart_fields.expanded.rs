@@ -0,0 +1,150 @@
+use conspiracy::config::config_struct;
+impl ::conspiracy::config::RestartRequired for ServerConfig {
+    #[inline]
+    fn restart_required(&self, other: &Self) -> bool {
+        self.port != other.port
+    }
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        #[allow(unused_mut)]
+        let mut reasons = Vec::new();
+        if self.port != other.port {
+            reasons.push("port");
+        }
+        reasons
+    }
+    #[inline]
+    fn reload_required(&self, other: &Self) -> bool {
+        self.workers != other.workers
+    }
+    fn reload_reasons(&self, other: &Self) -> Vec<&'static str> {
+        #[allow(unused_mut)]
+        let mut reasons = Vec::new();
+        if self.workers != other.workers {
+            reasons.push("workers");
+        }
+        reasons
+    }
+}
+pub struct CompactServerConfig {
+    pub port: u16,
+    pub workers: u32,
+    pub verbose: bool,
+}
+impl CompactServerConfig {
+    pub fn arcify(self) -> ::std::sync::Arc<ServerConfig> {
+        ::std::sync::Arc::new(ServerConfig {
+            port: self.port,
+            workers: self.workers,
+            verbose: self.verbose,
+        })
+    }
+}
+pub struct ServerConfig {
+    pub port: u16,
+    pub workers: u32,
+    pub verbose: bool,
+}
+#[automatically_derived]
+impl ::core::clone::Clone for ServerConfig {
+    #[inline]
+    fn clone(&self) -> ServerConfig {
+        ServerConfig {
+            port: ::core::clone::Clone::clone(&self.port),
+            workers: ::core::clone::Clone::clone(&self.workers),
+            verbose: ::core::clone::Clone::clone(&self.verbose),
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::marker::StructuralPartialEq for ServerConfig {}
+#[automatically_derived]
+impl ::core::cmp::PartialEq for ServerConfig {
+    #[inline]
+    fn eq(&self, other: &ServerConfig) -> bool {
+        self.port == other.port && self.workers == other.workers
+            && self.verbose == other.verbose
+    }
+}
+impl ::conspiracy::config::AsField<ServerConfig> for ServerConfig {
+    #[inline]
+    fn share(&self) -> ::std::sync::Arc<ServerConfig> {
+        ::std::sync::Arc::new(self.clone())
+    }
+}
+impl ServerConfig {
+    pub fn compact(&self) -> CompactServerConfig {
+        CompactServerConfig {
+            port: self.port.clone(),
+            workers: self.workers.clone(),
+            verbose: self.verbose.clone(),
+        }
+    }
+}
+impl ServerConfig {}
+impl ServerConfig {
+    /// Override the fields marked `#[conspiracy(env = "...")]` (including those of nested
+    /// sub-configs) with the values of their environment variables, if set.
+    pub fn apply_env_overrides(
+        &mut self,
+    ) -> Result<(), ::conspiracy::config::EnvOverrideError> {
+        Ok(())
+    }
+}
+impl ::conspiracy::config::macro_targets::WriteDotted for ServerConfig {
+    fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+        #[allow(unused_imports)]
+        use ::conspiracy::config::macro_targets::{
+            DottedConfig as _, DottedDebug as _, DottedDisplay as _,
+            DottedUnprintable as _,
+        };
+        {
+            let path = ::conspiracy::config::macro_targets::dotted_path(path, "port");
+            (&&&&::conspiracy::config::macro_targets::Dotted(&self.port))
+                .write_dotted(&path, lines);
+        }
+        {
+            let path = ::conspiracy::config::macro_targets::dotted_path(path, "workers");
+            (&&&&::conspiracy::config::macro_targets::Dotted(&self.workers))
+                .write_dotted(&path, lines);
+        }
+        {
+            let path = ::conspiracy::config::macro_targets::dotted_path(path, "verbose");
+            (&&&&::conspiracy::config::macro_targets::Dotted(&self.verbose))
+                .write_dotted(&path, lines);
+        }
+    }
+}
+impl ServerConfig {
+    /// Renders every field as a `path = value` line, e.g. `database.name = prod`, for
+    /// logging the effective config. Fields marked `#[conspiracy(secret)]` are redacted.
+    pub fn to_dotted_string(&self) -> String {
+        let mut lines = Vec::new();
+        ::conspiracy::config::macro_targets::WriteDotted::write_dotted(
+            self,
+            "",
+            &mut lines,
+        );
+        lines.join("\n")
+    }
+}
+impl ::conspiracy::config::DeprecatedFields for ServerConfig {
+    fn deprecated_fields() -> Vec<::conspiracy::config::DeprecatedField> {
+        #[allow(unused_mut)]
+        let mut fields: Vec<::conspiracy::config::DeprecatedField> = ::alloc::vec::Vec::new();
+        fields
+    }
+}
+impl ::conspiracy::config::macro_targets::GeneratedConfig for ServerConfig {}
+impl ::conspiracy::config::Reconcile for ServerConfig {
+    fn reconcile(
+        previous: &::std::sync::Arc<Self>,
+        next: ::std::sync::Arc<Self>,
+    ) -> ::std::sync::Arc<Self> {
+        if ::std::sync::Arc::ptr_eq(previous, &next) {
+            return next;
+        }
+        #[allow(unused_mut)]
+        let mut next = ::std::sync::Arc::unwrap_or_clone(next);
+        if **previous == next { previous.clone() } else { ::std::sync::Arc::new(next) }
+    }
+}
@@ -0,0 +1,11 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct ServerConfig {
+        #[conspiracy(restart)]
+        pub port: u16,
+        #[conspiracy(change = "reload")]
+        pub workers: u32,
+        pub verbose: bool,
+    }
+);
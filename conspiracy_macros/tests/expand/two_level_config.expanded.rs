@@ -0,0 +1,280 @@
+use conspiracy::config::config_struct;
+impl ::conspiracy::config::RestartRequired for AppConfig {
+    #[inline]
+    fn restart_required(&self, other: &Self) -> bool {
+        false
+    }
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        #[allow(unused_mut)]
+        let mut reasons = Vec::new();
+        reasons
+    }
+}
+pub struct CompactDatabaseConfig {
+    pub url: String,
+}
+impl CompactDatabaseConfig {
+    pub fn arcify(self) -> ::std::sync::Arc<DatabaseConfig> {
+        ::std::sync::Arc::new(DatabaseConfig { url: self.url })
+    }
+}
+pub struct CompactAppConfig {
+    pub name: String,
+    pub database: CompactDatabaseConfig,
+}
+impl CompactAppConfig {
+    pub fn arcify(self) -> ::std::sync::Arc<AppConfig> {
+        ::std::sync::Arc::new(AppConfig {
+            name: self.name,
+            database: self.database.arcify(),
+        })
+    }
+}
+impl ::conspiracy::config::AsField<DatabaseConfig> for AppConfig {
+    #[inline]
+    fn share(&self) -> ::std::sync::Arc<DatabaseConfig> {
+        self.database.clone()
+    }
+}
+impl From<&AppConfig> for ::std::sync::Arc<DatabaseConfig> {
+    #[inline]
+    fn from(config: &AppConfig) -> Self {
+        config.database.clone()
+    }
+}
+pub struct DatabaseConfig {
+    pub url: String,
+}
+#[automatically_derived]
+impl ::core::clone::Clone for DatabaseConfig {
+    #[inline]
+    fn clone(&self) -> DatabaseConfig {
+        DatabaseConfig {
+            url: ::core::clone::Clone::clone(&self.url),
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::marker::StructuralPartialEq for DatabaseConfig {}
+#[automatically_derived]
+impl ::core::cmp::PartialEq for DatabaseConfig {
+    #[inline]
+    fn eq(&self, other: &DatabaseConfig) -> bool {
+        self.url == other.url
+    }
+}
+impl ::conspiracy::config::AsField<DatabaseConfig> for DatabaseConfig {
+    #[inline]
+    fn share(&self) -> ::std::sync::Arc<DatabaseConfig> {
+        ::std::sync::Arc::new(self.clone())
+    }
+}
+impl DatabaseConfig {
+    pub fn compact(&self) -> CompactDatabaseConfig {
+        CompactDatabaseConfig {
+            url: self.url.clone(),
+        }
+    }
+}
+impl DatabaseConfig {}
+impl DatabaseConfig {
+    /// Override the fields marked `#[conspiracy(env = "...")]` (including those of nested
+    /// sub-configs) with the values of their environment variables, if set.
+    pub fn apply_env_overrides(
+        &mut self,
+    ) -> Result<(), ::conspiracy::config::EnvOverrideError> {
+        Ok(())
+    }
+}
+impl ::conspiracy::config::macro_targets::WriteDotted for DatabaseConfig {
+    fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+        #[allow(unused_imports)]
+        use ::conspiracy::config::macro_targets::{
+            DottedConfig as _, DottedDebug as _, DottedDisplay as _,
+            DottedUnprintable as _,
+        };
+        {
+            let path = ::conspiracy::config::macro_targets::dotted_path(path, "url");
+            (&&&&::conspiracy::config::macro_targets::Dotted(&self.url))
+                .write_dotted(&path, lines);
+        }
+    }
+}
+impl DatabaseConfig {
+    /// Renders every field as a `path = value` line, e.g. `database.name = prod`, for
+    /// logging the effective config. Fields marked `#[conspiracy(secret)]` are redacted.
+    pub fn to_dotted_string(&self) -> String {
+        let mut lines = Vec::new();
+        ::conspiracy::config::macro_targets::WriteDotted::write_dotted(
+            self,
+            "",
+            &mut lines,
+        );
+        lines.join("\n")
+    }
+}
+impl ::conspiracy::config::DeprecatedFields for DatabaseConfig {
+    fn deprecated_fields() -> Vec<::conspiracy::config::DeprecatedField> {
+        #[allow(unused_mut)]
+        let mut fields: Vec<::conspiracy::config::DeprecatedField> = ::alloc::vec::Vec::new();
+        fields
+    }
+}
+impl ::conspiracy::config::macro_targets::GeneratedConfig for DatabaseConfig {}
+impl ::conspiracy::config::Reconcile for DatabaseConfig {
+    fn reconcile(
+        previous: &::std::sync::Arc<Self>,
+        next: ::std::sync::Arc<Self>,
+    ) -> ::std::sync::Arc<Self> {
+        if ::std::sync::Arc::ptr_eq(previous, &next) {
+            return next;
+        }
+        #[allow(unused_mut)]
+        let mut next = ::std::sync::Arc::unwrap_or_clone(next);
+        if **previous == next { previous.clone() } else { ::std::sync::Arc::new(next) }
+    }
+}
+pub struct AppConfig {
+    pub name: String,
+    pub database: ::std::sync::Arc<DatabaseConfig>,
+}
+#[automatically_derived]
+impl ::core::clone::Clone for AppConfig {
+    #[inline]
+    fn clone(&self) -> AppConfig {
+        AppConfig {
+            name: ::core::clone::Clone::clone(&self.name),
+            database: ::core::clone::Clone::clone(&self.database),
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::marker::StructuralPartialEq for AppConfig {}
+#[automatically_derived]
+impl ::core::cmp::PartialEq for AppConfig {
+    #[inline]
+    fn eq(&self, other: &AppConfig) -> bool {
+        self.name == other.name && self.database == other.database
+    }
+}
+impl ::conspiracy::config::AsField<AppConfig> for AppConfig {
+    #[inline]
+    fn share(&self) -> ::std::sync::Arc<AppConfig> {
+        ::std::sync::Arc::new(self.clone())
+    }
+}
+impl AppConfig {
+    pub fn compact(&self) -> CompactAppConfig {
+        CompactAppConfig {
+            name: self.name.clone(),
+            database: (*self.database).clone().compact(),
+        }
+    }
+}
+impl AppConfig {
+    ///The `database` sub-config, shared rather than cloned.
+    #[inline]
+    pub fn database(&self) -> ::std::sync::Arc<DatabaseConfig> {
+        self.database.clone()
+    }
+    ///Copy-on-write update of `database`. The returned config shares every other branch with `self`.
+    pub fn replace_database(
+        &self,
+        database: ::std::sync::Arc<DatabaseConfig>,
+    ) -> ::std::sync::Arc<Self> {
+        let mut replaced = self.clone();
+        replaced.database = database;
+        ::std::sync::Arc::new(replaced)
+    }
+}
+impl AppConfig {
+    /// Override the fields marked `#[conspiracy(env = "...")]` (including those of nested
+    /// sub-configs) with the values of their environment variables, if set.
+    pub fn apply_env_overrides(
+        &mut self,
+    ) -> Result<(), ::conspiracy::config::EnvOverrideError> {
+        ::std::sync::Arc::make_mut(&mut self.database).apply_env_overrides()?;
+        Ok(())
+    }
+}
+impl ::conspiracy::config::macro_targets::WriteDotted for AppConfig {
+    fn write_dotted(&self, path: &str, lines: &mut Vec<String>) {
+        #[allow(unused_imports)]
+        use ::conspiracy::config::macro_targets::{
+            DottedConfig as _, DottedDebug as _, DottedDisplay as _,
+            DottedUnprintable as _,
+        };
+        {
+            let path = ::conspiracy::config::macro_targets::dotted_path(path, "name");
+            (&&&&::conspiracy::config::macro_targets::Dotted(&self.name))
+                .write_dotted(&path, lines);
+        }
+        {
+            let path = ::conspiracy::config::macro_targets::dotted_path(
+                path,
+                "database",
+            );
+            ::conspiracy::config::macro_targets::WriteDotted::write_dotted(
+                &*self.database,
+                &path,
+                lines,
+            );
+        }
+    }
+}
+impl AppConfig {
+    /// Renders every field as a `path = value` line, e.g. `database.name = prod`, for
+    /// logging the effective config. Fields marked `#[conspiracy(secret)]` are redacted.
+    pub fn to_dotted_string(&self) -> String {
+        let mut lines = Vec::new();
+        ::conspiracy::config::macro_targets::WriteDotted::write_dotted(
+            self,
+            "",
+            &mut lines,
+        );
+        lines.join("\n")
+    }
+}
+impl ::conspiracy::config::DeprecatedFields for AppConfig {
+    fn deprecated_fields() -> Vec<::conspiracy::config::DeprecatedField> {
+        #[allow(unused_mut)]
+        let mut fields: Vec<::conspiracy::config::DeprecatedField> = ::alloc::vec::Vec::new();
+        fields
+            .extend(
+                <DatabaseConfig as ::conspiracy::config::DeprecatedFields>::deprecated_fields()
+                    .into_iter()
+                    .map(|field| ::conspiracy::config::DeprecatedField {
+                        path: [
+                            ::alloc::boxed::box_assume_init_into_vec_unsafe(
+                                ::alloc::intrinsics::write_box_via_move(
+                                    ::alloc::boxed::Box::new_uninit(),
+                                    ["database"],
+                                ),
+                            ),
+                            field.path,
+                        ]
+                            .concat(),
+                        ..field
+                    }),
+            );
+        fields
+    }
+}
+impl ::conspiracy::config::macro_targets::GeneratedConfig for AppConfig {}
+impl ::conspiracy::config::Reconcile for AppConfig {
+    fn reconcile(
+        previous: &::std::sync::Arc<Self>,
+        next: ::std::sync::Arc<Self>,
+    ) -> ::std::sync::Arc<Self> {
+        if ::std::sync::Arc::ptr_eq(previous, &next) {
+            return next;
+        }
+        #[allow(unused_mut)]
+        let mut next = ::std::sync::Arc::unwrap_or_clone(next);
+        next.database = ::conspiracy::config::Reconcile::reconcile(
+            &previous.database,
+            next.database,
+        );
+        if **previous == next { previous.clone() } else { ::std::sync::Arc::new(next) }
+    }
+}
@@ -0,0 +1,10 @@
+use conspiracy::config::config_struct;
+
+config_struct!(
+    pub struct AppConfig {
+        pub name: String,
+        pub database: pub struct DatabaseConfig {
+            pub url: String,
+        }
+    }
+);
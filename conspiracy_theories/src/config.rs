@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::any::type_name;
 
 /// Fetches the current state of configuration as a shared atomic snapshot. Implementors of this
 /// trait use atomic copy on write semantics to optimize reads as far as possible. On typical
@@ -18,6 +19,15 @@ pub trait ConfigFetcher<T> {
     fn latest_snapshot(&self) -> Arc<T>;
 }
 
+/// Shared fetchers (such as `SharedConfigFetcher`) are themselves fetchers, which allows adapters
+/// that are generic over a [`ConfigFetcher`] to wrap them directly.
+impl<T, F: ConfigFetcher<T> + ?Sized> ConfigFetcher<T> for Arc<F> {
+    #[inline]
+    fn latest_snapshot(&self) -> Arc<T> {
+        (**self).latest_snapshot()
+    }
+}
+
 /// Express a config snapshot as sub-config snapshot. The purpose of this is that code can depend on
 /// the subset of an app level config that's actually relevant to them. This leads to better
 /// separation of concerns, lower coupling, and less boilerplate in testing without having to give
@@ -27,6 +37,38 @@ pub trait AsField<T> {
     fn share(&self) -> Arc<T>;
 }
 
+/// Snapshots are held as [`Arc`], so they project the same way as the config they point to.
+impl<T: AsField<U> + ?Sized, U> AsField<U> for Arc<T> {
+    #[inline]
+    fn share(&self) -> Arc<U> {
+        (**self).share()
+    }
+}
+
+impl<T: AsField<U> + ?Sized, U> AsField<U> for &T {
+    #[inline]
+    fn share(&self) -> Arc<U> {
+        (**self).share()
+    }
+}
+
+/// The counterpart of [`AsField`] for sub-configs that may be absent, the variants of an enum
+/// sub-config and optional sub-configs. Only one variant is active at a time, so the projection is
+/// [`None`] when a different variant is active or the optional sub-config isn't set.
+pub trait AsVariant<T> {
+    /// Share a copy of the variant's sub-config, if it's the active variant.
+    fn share_variant(&self) -> Option<Arc<T>>;
+}
+
+/// Rebuilds a newly loaded snapshot so it shares every branch that's unchanged from the previous
+/// snapshot. Reloading a large config then only allocates for the branches that changed, and a
+/// sub-config that didn't change keeps its [`Arc`], so [`Arc::ptr_eq`] is a cheap change check.
+pub trait Reconcile {
+    /// Returns `next`, with each branch (or `next` as a whole) that's equal to its counterpart in
+    /// `previous` replaced by a clone of the counterpart's [`Arc`].
+    fn reconcile(previous: &Arc<Self>, next: Arc<Self>) -> Arc<Self>;
+}
+
 /// Enables a config struct to indicate if a restart is required.
 ///
 /// Ultimately, it is up to the consumer of the config struct (an implementor of `ConfigFetcher`) to
@@ -36,4 +78,107 @@ pub trait RestartRequired {
     /// Compare against another snapshot. If any `#[conspiracy(restart)]` tagged fields have changed it will
     /// return `true`.
     fn restart_required(&self, other: &Self) -> bool;
+
+    /// Compare against another snapshot, listing the dotted paths (e.g. `"web_server.addr"`) of the
+    /// `#[conspiracy(restart)]` tagged fields that changed. Empty exactly when [`restart_required`]
+    /// is `false`, intended for explaining a restart in logs.
+    ///
+    /// Defaults to the name of the type when a restart is required, for implementations that don't
+    /// track individual fields.
+    ///
+    /// [`restart_required`]: RestartRequired::restart_required
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        if self.restart_required(other) {
+            vec![type_name::<Self>()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Compare against another snapshot. If any `#[conspiracy(change = "reload")]` tagged fields
+    /// have changed it will return `true`. A reload is a lighter response than a restart, such as
+    /// reinitializing a single subsystem, and is tracked independently of [`restart_required`].
+    ///
+    /// Defaults to `false` for types without reload fields.
+    ///
+    /// [`restart_required`]: RestartRequired::restart_required
+    fn reload_required(&self, _other: &Self) -> bool {
+        false
+    }
+
+    /// The [`restart_reasons`] counterpart of [`reload_required`].
+    ///
+    /// [`restart_reasons`]: RestartRequired::restart_reasons
+    /// [`reload_required`]: RestartRequired::reload_required
+    fn reload_reasons(&self, _other: &Self) -> Vec<&'static str> {
+        Vec::new()
+    }
+}
+
+/// Compares the pointed to configs, short circuiting when both point to the same snapshot.
+impl<T: RestartRequired + ?Sized> RestartRequired for Arc<T> {
+    #[inline]
+    fn restart_required(&self, other: &Self) -> bool {
+        !Arc::ptr_eq(self, other) && (**self).restart_required(other)
+    }
+
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        if Arc::ptr_eq(self, other) {
+            Vec::new()
+        } else {
+            (**self).restart_reasons(other)
+        }
+    }
+
+    #[inline]
+    fn reload_required(&self, other: &Self) -> bool {
+        !Arc::ptr_eq(self, other) && (**self).reload_required(other)
+    }
+
+    fn reload_reasons(&self, other: &Self) -> Vec<&'static str> {
+        if Arc::ptr_eq(self, other) {
+            Vec::new()
+        } else {
+            (**self).reload_reasons(other)
+        }
+    }
+}
+
+/// Compares the inner configs when both are [`Some`]. A config appearing or disappearing (i.e.
+/// [`None`] to [`Some`] or back) always requires a restart, which is reported by the name of the
+/// new state, `"Some"` or `"None"`. Since the restart subsumes it, such a transition never signals
+/// a reload.
+impl<T: RestartRequired> RestartRequired for Option<T> {
+    #[inline]
+    fn restart_required(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(this), Some(other)) => this.restart_required(other),
+            (None, None) => false,
+            _ => true,
+        }
+    }
+
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        match (self, other) {
+            (Some(this), Some(other)) => this.restart_reasons(other),
+            (None, None) => Vec::new(),
+            (None, Some(_)) => vec!["Some"],
+            (Some(_), None) => vec!["None"],
+        }
+    }
+
+    #[inline]
+    fn reload_required(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(this), Some(other)) => this.reload_required(other),
+            _ => false,
+        }
+    }
+
+    fn reload_reasons(&self, other: &Self) -> Vec<&'static str> {
+        match (self, other) {
+            (Some(this), Some(other)) => this.reload_reasons(other),
+            _ => Vec::new(),
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use std::{any::Any, sync::Arc};
+use alloc::{sync::Arc, vec::Vec};
+use core::any::{Any, TypeId};
 
 /// Allows determining the current state of a feature, where the feature is specified as an enum
 /// variant.
@@ -18,10 +19,115 @@ pub trait AsFeature {
 pub trait FeatureSet: Send + Sync + 'static {
     /// The generated state representation type that corresponds to this feature set.
     type State: AsFeature<Feature = Self> + Default + Send + Sync + 'static;
+
+    /// The generated partial state representation, where each feature may or may not be set.
+    type Overrides: FeatureOverrides<State = Self::State> + Default + Send + Sync + 'static;
+
+    /// The name of the feature, matching its key in the serialized state.
+    fn name(&self) -> &'static str;
+
+    /// Every feature in the set, in declaration order.
+    fn all() -> &'static [Self]
+    where
+        Self: Sized;
+
+    /// For a set combining several feature sets, the state of the part whose state type has the
+    /// [`TypeId`] `part`. This lets a single tracker serve the checks of every part. Defaults to
+    /// [`None`] for sets that aren't combined.
+    fn state_part(_state: &Self::State, _part: TypeId) -> Option<Arc<dyn Any + Send + Sync>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// For a set combining several feature sets, the [`TypeId`] of every part's state, i.e. each
+    /// `part` that [`state_part`][FeatureSet::state_part] serves. Defaults to none for sets that
+    /// aren't combined.
+    fn state_parts() -> Vec<TypeId>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+}
+
+/// A partial set of feature values, used to layer overrides on top of a complete state. Features
+/// that aren't set have no opinion and leave the underlying state untouched.
+pub trait FeatureOverrides {
+    /// The complete state these overrides apply to.
+    type State: AsFeature;
+
+    /// Overwrite each feature of `state` that these overrides have a value for.
+    fn apply(&self, state: &mut Self::State);
+
+    /// The value these overrides set for `feature`, if they have an opinion on it.
+    ///
+    /// Defaults to no opinion on any feature, so explanations attribute every value to the layers
+    /// beneath. The overrides generated by `define_features!` report what they set.
+    fn get(&self, _feature: &<Self::State as AsFeature>::Feature) -> Option<bool> {
+        None
+    }
+
+    /// Give these overrides an opinion on `feature`.
+    fn set(&mut self, feature: &<Self::State as AsFeature>::Feature, value: bool);
 }
 
 /// Tracks if a statically typed (enum variant) feature is currently enabled or disabled.
 pub trait FeatureTracker: 'static {
     /// Get the current state of all tracked features.
     fn static_feature_state(&self) -> Arc<dyn Any + Send + Sync>;
+
+    /// Get the current state of a single part of the tracked features, when they combine several
+    /// feature sets (see [`FeatureSet::state_part`]). `part` is the [`TypeId`] of the part's state.
+    /// Defaults to [`None`], i.e. the tracked features aren't combined.
+    fn static_feature_state_part(&self, _part: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        None
+    }
+
+    /// The [`TypeId`] of every part of the tracked features, when they combine several feature sets
+    /// (see [`FeatureSet::state_parts`]). Defaults to none, i.e. the tracked features aren't
+    /// combined.
+    fn static_feature_state_parts(&self) -> Vec<TypeId> {
+        Vec::new()
+    }
+}
+
+/// Explains why a [`FeatureTracker`] reports the value it does for a feature, e.g. to answer "why is
+/// feature X enabled on this host?".
+pub trait ExplainFeature<T: FeatureSet>: FeatureTracker {
+    /// Determine the current state of `feature` along with what decided it. By default, the tracker
+    /// is treated as having a single [`FeatureSource::Static`] source.
+    fn explain(&self, feature: T) -> FeatureExplanation {
+        let state = self
+            .static_feature_state()
+            .downcast::<T::State>()
+            .expect("The tracker's state type should match its feature set");
+
+        FeatureExplanation {
+            enabled: state.as_feature(feature),
+            source: FeatureSource::Static,
+        }
+    }
+}
+
+/// The result of [`ExplainFeature::explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureExplanation {
+    /// The current state of the feature.
+    pub enabled: bool,
+    /// What decided the state of the feature.
+    pub source: FeatureSource,
+}
+
+/// What decided the state of a feature, see [`ExplainFeature`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FeatureSource {
+    /// The tracker's only source of state.
+    Static,
+    /// The base state of a layered tracker, no layer had an opinion on the feature.
+    Base,
+    /// The layer of a layered tracker at the given index, in order of precedence.
+    Layer(usize),
 }
@@ -9,6 +9,13 @@
 //! This is the collection of traits re-exported by the configuration crate [`conspiracy`](https://crates.io/crates/conspiracy).
 //! Conspiracy is a very opinionated crate, but each component is designed to be used in isolation
 //! or replaced if desired. This provides a minimal dependency for alternate implementations.
+//!
+//! The traits only depend on `core` and `alloc`, so this crate is `no_std` and can be implemented
+//! by configuration types shared with embedded or other `no_std` targets.
+
+#![no_std]
+
+extern crate alloc;
 
 pub mod config;
 pub mod feature;
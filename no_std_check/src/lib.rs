@@ -0,0 +1,40 @@
+//! Not published. Implements the `conspiracy_theories` traits from a `no_std` crate so that
+//! building it for a target without `std`, as CI does with
+//! `cargo build -p no_std_check --target thumbv7em-none-eabihf`, fails if the theories crate starts
+//! depending on `std`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use conspiracy_theories::config::{ConfigFetcher, RestartRequired};
+
+#[derive(PartialEq)]
+pub struct DeviceConfig {
+    pub sample_rate: u32,
+    pub label: &'static str,
+}
+
+impl RestartRequired for DeviceConfig {
+    fn restart_required(&self, other: &Self) -> bool {
+        self.sample_rate != other.sample_rate
+    }
+
+    fn restart_reasons(&self, other: &Self) -> Vec<&'static str> {
+        if self.restart_required(other) {
+            vec!["sample_rate"]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct StaticFetcher(pub Arc<DeviceConfig>);
+
+impl ConfigFetcher<DeviceConfig> for StaticFetcher {
+    fn latest_snapshot(&self) -> Arc<DeviceConfig> {
+        self.0.clone()
+    }
+}